@@ -0,0 +1,169 @@
+//! Derive macros for [`spanner-rs`](https://docs.rs/spanner-rs), enabled using its `derive` crate feature.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::ext::IdentExt;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, Ident, LitStr};
+
+/// Implements `spanner_rs::ToRow` for a struct with named fields, each field being a column of the row.
+///
+/// Columns are named after their field, unless renamed using `#[spanner(rename = "column")]`.
+/// The value of each field is converted using `spanner_rs::ToSpanner`.
+#[proc_macro_derive(ToRow, attributes(spanner))]
+pub fn derive_to_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_to_row(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `spanner_rs::TableRow` for a struct that also implements `spanner_rs::ToRow`.
+///
+/// The table is named after the struct, unless named using `#[spanner(table = "table")]` on the struct.
+/// The primary key is made of the fields marked using `#[spanner(pk)]`, in the order they are declared,
+/// which also determines `TableRow::KEY_COLUMNS`.
+#[proc_macro_derive(TableRow, attributes(spanner))]
+pub fn derive_table_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand_table_row(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// A field of the struct and the column it maps to.
+struct Column {
+    field: Ident,
+    name: String,
+    pk: bool,
+}
+
+/// Returns the table named by the struct's `spanner` attributes, if any.
+fn table(input: &DeriveInput) -> syn::Result<Option<String>> {
+    let mut table = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("spanner"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("table") {
+                table = Some(meta.value()?.parse::<LitStr>()?.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported spanner attribute"))
+            }
+        })?;
+    }
+    Ok(table)
+}
+
+fn columns(input: &DeriveInput) -> syn::Result<Vec<Column>> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "only structs with named fields are supported",
+                ))
+            }
+        },
+        _ => return Err(syn::Error::new_spanned(input, "only structs are supported")),
+    };
+
+    fields
+        .iter()
+        .map(|field| {
+            let ident = field.ident.clone().expect("fields should be named");
+            let mut name = ident.unraw().to_string();
+            let mut pk = false;
+            for attr in field
+                .attrs
+                .iter()
+                .filter(|attr| attr.path().is_ident("spanner"))
+            {
+                attr.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("rename") {
+                        name = meta.value()?.parse::<LitStr>()?.value();
+                        Ok(())
+                    } else if meta.path.is_ident("pk") {
+                        pk = true;
+                        Ok(())
+                    } else {
+                        Err(meta.error("unsupported spanner attribute"))
+                    }
+                })?;
+            }
+            Ok(Column {
+                field: ident,
+                name,
+                pk,
+            })
+        })
+        .collect()
+}
+
+fn expand_to_row(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let columns = columns(input)?;
+    let names = columns.iter().map(|column| &column.name);
+    let fields = columns.iter().map(|column| &column.field);
+
+    // Validates the struct's attributes, which are otherwise only used by the TableRow derive.
+    table(input)?;
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::spanner_rs::ToRow for #ident #ty_generics #where_clause {
+            fn to_row(
+                &self,
+            ) -> ::std::result::Result<
+                ::std::vec::Vec<(&'static str, ::spanner_rs::Value)>,
+                ::spanner_rs::Error,
+            > {
+                ::std::result::Result::Ok(::std::vec![
+                    #((#names, ::spanner_rs::ToSpanner::to_spanner(&self.#fields)?),)*
+                ])
+            }
+        }
+    })
+}
+
+fn expand_table_row(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let columns = columns(input)?;
+    let keys = columns
+        .iter()
+        .filter(|column| column.pk)
+        .map(|column| &column.field)
+        .collect::<Vec<_>>();
+    let key_columns = columns
+        .iter()
+        .filter(|column| column.pk)
+        .map(|column| &column.name);
+    if keys.is_empty() {
+        return Err(syn::Error::new_spanned(
+            input,
+            "TableRow requires at least one field marked using #[spanner(pk)]",
+        ));
+    }
+
+    let ident = &input.ident;
+    let table = table(input)?.unwrap_or_else(|| ident.unraw().to_string());
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::spanner_rs::TableRow for #ident #ty_generics #where_clause {
+            const TABLE: &'static str = #table;
+
+            const KEY_COLUMNS: &'static [&'static str] = &[#(#key_columns),*];
+
+            fn key(
+                &self,
+            ) -> ::std::result::Result<::std::vec::Vec<::spanner_rs::Value>, ::spanner_rs::Error> {
+                ::std::result::Result::Ok(::std::vec![
+                    #(::spanner_rs::ToSpanner::to_spanner(&self.#keys)?,)*
+                ])
+            }
+        }
+    })
+}
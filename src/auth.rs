@@ -1,7 +1,13 @@
-use std::{future::Future, pin::Pin, sync::Arc};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, SystemTime},
+};
 
 use gcp_auth::AuthenticationManager;
 use http::HeaderValue;
+use tokio::sync::OnceCell;
 use tower::{filter::AsyncPredicate, BoxError};
 
 const DATABASE_SCOPES: [&str; 2] = [
@@ -31,19 +37,199 @@ impl Scopes {
     }
 }
 
+/// The source of the [`AuthenticationManager`] used to obtain tokens.
+#[derive(Clone)]
+pub(crate) enum AuthProvider {
+    /// A manager owned by this client.
+    Owned(Arc<AuthenticationManager>),
+    /// The process-wide manager using the default credentials, it is only initialized the first time it is used.
+    ///
+    /// Sharing the manager allows clients created later in the same process (e.g.: subsequent invocations of a cloud function)
+    /// to reuse previously obtained tokens.
+    Shared,
+    /// Tokens are obtained by the application, e.g.: when they are minted by an external service.
+    External(TokenProvider),
+    /// Tokens with an expiry, obtained by tests.
+    #[cfg(test)]
+    Fake(Arc<dyn Fn() -> AccessToken + Send + Sync>),
+}
+
+/// Obtains access tokens on behalf of the application, see [`crate::ConfigBuilder::with_access_token_provider`].
+pub(crate) type TokenProvider = Arc<
+    dyn Fn() -> Pin<Box<dyn Future<Output = Result<String, crate::Error>> + Send>> + Send + Sync,
+>;
+
+impl std::fmt::Debug for AuthProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AuthProvider::Owned(_) => f.write_str("Owned"),
+            AuthProvider::Shared => f.write_str("Shared"),
+            AuthProvider::External(_) => f.write_str("External"),
+            #[cfg(test)]
+            AuthProvider::Fake(_) => f.write_str("Fake"),
+        }
+    }
+}
+
+impl AuthProvider {
+    pub(crate) fn new(auth_manager: AuthenticationManager) -> Self {
+        AuthProvider::Owned(Arc::new(auth_manager))
+    }
+
+    /// Obtains an access token for the provided scopes.
+    pub(crate) async fn token(&self, scopes: &[&str]) -> Result<AccessToken, crate::Error> {
+        let manager = match self {
+            AuthProvider::Owned(auth_manager) => auth_manager.as_ref(),
+            AuthProvider::Shared => shared_manager().await?,
+            AuthProvider::External(provider) => {
+                return provider().await.map(|value| AccessToken {
+                    value,
+                    expires_at: None,
+                })
+            }
+            #[cfg(test)]
+            AuthProvider::Fake(provider) => return Ok(provider()),
+        };
+        let token = manager.get_token(scopes).await?;
+        Ok(AccessToken {
+            value: token.as_str().to_string(),
+            expires_at: token.expires_at().map(SystemTime::from),
+        })
+    }
+
+    /// Returns the project of the credentials, which is only known for service accounts.
+    pub(crate) async fn project_id(&self) -> Result<String, crate::Error> {
+        let manager = match self {
+            AuthProvider::Owned(auth_manager) => auth_manager.as_ref(),
+            AuthProvider::Shared => shared_manager().await?,
+            AuthProvider::External(_) => {
+                return Err(crate::Error::Config("missing project id".to_string()))
+            }
+            #[cfg(test)]
+            AuthProvider::Fake(_) => {
+                return Err(crate::Error::Config("missing project id".to_string()))
+            }
+        };
+        Ok(manager.project_id().await?)
+    }
+}
+
+async fn shared_manager() -> Result<&'static AuthenticationManager, gcp_auth::Error> {
+    static SHARED: OnceLock<OnceCell<AuthenticationManager>> = OnceLock::new();
+    SHARED
+        .get_or_init(OnceCell::new)
+        .get_or_try_init(AuthenticationManager::new)
+        .await
+}
+
+/// An access token and its expiry, when known.
+#[derive(Clone)]
+pub(crate) struct AccessToken {
+    value: String,
+    expires_at: Option<SystemTime>,
+}
+
+/// Tokens closer than this to their expiry are never sent.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(10);
+
+/// How long to wait before attempting another refresh when one fails or does not obtain a newer token, doubled after each attempt.
+const REFRESH_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest delay between two refresh attempts.
+const MAX_REFRESH_BACKOFF: Duration = Duration::from_secs(16);
+
+/// The token last obtained by an [`AuthFilter`], shared by its clones.
+#[derive(Default)]
+struct TokenCache {
+    token: Option<AccessToken>,
+    // Set while a token is being refreshed in the background.
+    refreshing: bool,
+}
+
 #[derive(Clone)]
 pub(crate) struct AuthFilter {
-    auth_manager: Arc<AuthenticationManager>,
+    auth: AuthProvider,
     scopes: Scopes,
+    refresh_window: Duration,
+    refresh_backoff: Duration,
+    cache: Arc<Mutex<TokenCache>>,
 }
 
 impl AuthFilter {
-    pub(crate) fn new(auth_manager: AuthenticationManager, scopes: Scopes) -> Self {
+    /// Creates a filter which refreshes tokens in the background once they expire within `refresh_window`.
+    pub(crate) fn new(auth: AuthProvider, scopes: Scopes, refresh_window: Duration) -> Self {
         Self {
-            auth_manager: Arc::new(auth_manager),
+            auth,
             scopes,
+            refresh_window,
+            refresh_backoff: REFRESH_BACKOFF,
+            cache: Arc::new(Mutex::new(TokenCache::default())),
         }
     }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, TokenCache> {
+        self.cache.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the cached token, unless it is about to expire, in which case a new one is obtained on the request path.
+    ///
+    /// Tokens provided by the application are never cached, see [`crate::ConfigBuilder::with_access_token_provider`].
+    async fn token(&self) -> Result<String, crate::Error> {
+        if let AuthProvider::External(_) = self.auth {
+            return Ok(self.auth.token(self.scopes.as_slice()).await?.value);
+        }
+
+        let now = SystemTime::now();
+        {
+            let mut cache = self.lock();
+            if let Some(token) = cache.token.clone() {
+                match token.expires_at {
+                    None => return Ok(token.value),
+                    Some(expires_at) if now + EXPIRY_MARGIN < expires_at => {
+                        if now + self.refresh_window >= expires_at && !cache.refreshing {
+                            cache.refreshing = true;
+                            self.spawn_refresh(expires_at);
+                        }
+                        return Ok(token.value);
+                    }
+                    Some(_) => (),
+                }
+            }
+        }
+
+        let token = self.auth.token(self.scopes.as_slice()).await?;
+        self.lock().token = Some(token.clone());
+        Ok(token.value)
+    }
+
+    /// Obtains a new token off the request path, the current one remains in use until then.
+    ///
+    /// The authentication manager hands out its own cached token until shortly before it expires, which may be well
+    /// within the refresh window: attempts are repeated with an exponential backoff until a newer token is obtained,
+    /// or until the current one is about to expire, at which point requests obtain a new token themselves.
+    fn spawn_refresh(&self, current_expiry: SystemTime) {
+        let filter = self.clone();
+        tokio::spawn(async move {
+            let mut backoff = filter.refresh_backoff;
+            loop {
+                if let Ok(token) = filter.auth.token(filter.scopes.as_slice()).await {
+                    if token
+                        .expires_at
+                        .map_or(true, |expiry| expiry > current_expiry)
+                    {
+                        filter.lock().token = Some(token);
+                        break;
+                    }
+                }
+                if SystemTime::now() + backoff + EXPIRY_MARGIN >= current_expiry {
+                    break;
+                }
+                tokio::time::sleep(backoff).await;
+                backoff = (backoff * 2).min(MAX_REFRESH_BACKOFF);
+            }
+            filter.lock().refreshing = false;
+        });
+    }
 }
 
 impl AsyncPredicate<http::Request<tonic::body::BoxBody>> for AuthFilter {
@@ -54,12 +240,9 @@ impl AsyncPredicate<http::Request<tonic::body::BoxBody>> for AuthFilter {
     fn check(&mut self, request: http::Request<tonic::body::BoxBody>) -> Self::Future {
         let filter = self.clone();
         Box::pin(async move {
-            let token = filter
-                .auth_manager
-                .get_token(filter.scopes.as_slice())
-                .await?;
+            let token = filter.token().await?;
 
-            let header = HeaderValue::try_from(format!("Bearer {}", token.as_str()))
+            let header = HeaderValue::try_from(format!("Bearer {}", token))
                 .map_err(|err| crate::Error::Client(format!("invalid auth token: {}", err)))?;
 
             let (mut parts, body) = request.into_parts();
@@ -68,3 +251,116 @@ impl AsyncPredicate<http::Request<tonic::body::BoxBody>> for AuthFilter {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    /// Returns a provider that hands out `stale` for the first `stale_count` calls and `fresh` afterwards,
+    /// along with the number of calls made so far.
+    fn provider(
+        stale: AccessToken,
+        stale_count: usize,
+        fresh: AccessToken,
+    ) -> (AuthProvider, Arc<AtomicUsize>) {
+        let calls = Arc::new(AtomicUsize::new(0));
+        let provider = AuthProvider::Fake(Arc::new({
+            let calls = calls.clone();
+            move || {
+                if calls.fetch_add(1, Ordering::SeqCst) < stale_count {
+                    stale.clone()
+                } else {
+                    fresh.clone()
+                }
+            }
+        }));
+        (provider, calls)
+    }
+
+    fn token(value: &str, expires_in: Duration) -> AccessToken {
+        AccessToken {
+            value: value.to_string(),
+            expires_at: Some(SystemTime::now() + expires_in),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_cached_token() {
+        let (auth, calls) = provider(
+            token("stale", Duration::from_secs(3600)),
+            usize::MAX,
+            token("fresh", Duration::from_secs(7200)),
+        );
+        let filter = AuthFilter::new(auth, Scopes::Database, Duration::from_secs(60));
+        for _ in 0..3 {
+            assert_eq!(filter.token().await.unwrap(), "stale");
+        }
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_token() {
+        let (auth, calls) = provider(
+            token("stale", EXPIRY_MARGIN / 2),
+            1,
+            token("fresh", Duration::from_secs(3600)),
+        );
+        let filter = AuthFilter::new(auth, Scopes::Database, Duration::from_secs(60));
+        assert_eq!(filter.token().await.unwrap(), "stale");
+        // obtained on the request path, since the cached token is about to expire
+        assert_eq!(filter.token().await.unwrap(), "fresh");
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_backoff() {
+        // the manager keeps handing out its cached token for the first 3 calls, as gcp_auth does within its own refresh margin
+        let (auth, calls) = provider(
+            token("stale", Duration::from_secs(1800)),
+            3,
+            token("fresh", Duration::from_secs(7200)),
+        );
+        let filter = AuthFilter {
+            refresh_backoff: Duration::from_millis(10),
+            ..AuthFilter::new(auth, Scopes::Database, Duration::from_secs(3600))
+        };
+        assert_eq!(filter.token().await.unwrap(), "stale");
+        // the token is within the refresh window: it remains in use while a single refresh runs in the background
+        for _ in 0..5 {
+            assert_eq!(filter.token().await.unwrap(), "stale");
+        }
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while filter.token().await.unwrap() != "fresh" {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        })
+        .await
+        .unwrap();
+        // 1 on the request path, 2 refresh attempts returning the cached token and the successful one
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+        assert!(!filter.lock().refreshing);
+    }
+
+    #[tokio::test]
+    async fn test_refresh_gives_up_before_expiry() {
+        let (auth, calls) = provider(
+            token("stale", EXPIRY_MARGIN + Duration::from_millis(200)),
+            usize::MAX,
+            token("fresh", Duration::from_secs(3600)),
+        );
+        let filter = AuthFilter {
+            refresh_backoff: Duration::from_millis(50),
+            ..AuthFilter::new(auth, Scopes::Database, Duration::from_secs(60))
+        };
+        assert_eq!(filter.token().await.unwrap(), "stale");
+        assert_eq!(filter.token().await.unwrap(), "stale");
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert!(!filter.lock().refreshing);
+        // 1 on the request path, then attempts after 0, 50 and 150ms before giving up
+        assert_eq!(calls.load(Ordering::SeqCst), 4);
+    }
+}
@@ -0,0 +1,331 @@
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use derive_builder::Builder;
+use tokio::sync::Notify;
+
+use crate::{Error, ErrorCode};
+
+/// Adapts the number of concurrent commits made by bulk writes to how fast Cloud Spanner applies them,
+/// see [`crate::ConfigBuilder::write_backpressure`].
+///
+/// The limit starts at [`initial`](AdaptiveConcurrencyBuilder::initial) concurrent commits and grows by about one
+/// for every window of commits that complete within the [`target latency`](AdaptiveConcurrencyBuilder::target_latency).
+/// It is halved when a commit is slower than the target or fails with an error indicating that Cloud Spanner is overloaded,
+/// i.e.: `RESOURCE_EXHAUSTED`, `UNAVAILABLE` or `DEADLINE_EXCEEDED`, so that ingestion slows down instead of adding to the load.
+///
+/// This applies to [`crate::Client::write_chunked`] and [`crate::Client::batch_write`].
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use spanner_rs::{AdaptiveConcurrency, Config};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// Config::builder().write_backpressure(
+///     AdaptiveConcurrency::builder()
+///         .max(16)
+///         .target_latency(Duration::from_millis(500))
+///         .build()?,
+/// );
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[builder(
+    pattern = "owned",
+    build_fn(error = "crate::Error", validate = "Self::validate")
+)]
+pub struct AdaptiveConcurrency {
+    /// Specify the number of concurrent commits to start with. Defaults to 4.
+    #[builder(default = "4")]
+    initial: usize,
+
+    /// Specify the number of concurrent commits the limit never goes below. Defaults to 1.
+    #[builder(default = "1")]
+    min: usize,
+
+    /// Specify the number of concurrent commits the limit never goes above. Defaults to 32.
+    #[builder(default = "32")]
+    max: usize,
+
+    /// Specify the commit latency above which the limit is decreased. Defaults to 1 second.
+    ///
+    /// The latency of a batch write is the time until all the outcomes of the batch have been received,
+    /// which grows with the size of the batches.
+    #[builder(default = "Duration::from_secs(1)")]
+    target_latency: Duration,
+}
+
+impl Default for AdaptiveConcurrency {
+    fn default() -> Self {
+        Self {
+            initial: 4,
+            min: 1,
+            max: 32,
+            target_latency: Duration::from_secs(1),
+        }
+    }
+}
+
+impl AdaptiveConcurrencyBuilder {
+    fn validate(&self) -> Result<(), Error> {
+        let defaults = AdaptiveConcurrency::default();
+        let initial = self.initial.unwrap_or(defaults.initial);
+        let min = self.min.unwrap_or(defaults.min);
+        let max = self.max.unwrap_or(defaults.max);
+        if min == 0 || min > initial || initial > max {
+            Err(Error::Config(format!(
+                "invalid adaptive concurrency: expected 1 <= min <= initial <= max, got min={}, initial={}, max={}",
+                min, initial, max
+            )))
+        } else if self.target_latency == Some(Duration::ZERO) {
+            Err(Error::Config(
+                "invalid adaptive concurrency: target latency must be positive".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl AdaptiveConcurrency {
+    /// Returns a new [`AdaptiveConcurrencyBuilder`] for configuring the limit.
+    pub fn builder() -> AdaptiveConcurrencyBuilder {
+        AdaptiveConcurrencyBuilder::default()
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    limit: f64,
+    in_flight: usize,
+    last_decrease: Option<Instant>,
+}
+
+/// Enforces an [`AdaptiveConcurrency`], its state is shared by all clones of a client.
+#[derive(Clone, Debug)]
+pub(crate) struct AdaptiveLimiter {
+    config: AdaptiveConcurrency,
+    state: Arc<Mutex<State>>,
+    released: Arc<Notify>,
+}
+
+/// A commit in flight, its latency and outcome adjust the limit when dropped.
+pub(crate) struct AdaptivePermit {
+    limiter: AdaptiveLimiter,
+    started: Instant,
+    overloaded: bool,
+}
+
+impl AdaptiveLimiter {
+    pub(crate) fn new(config: AdaptiveConcurrency) -> Self {
+        let state = State {
+            limit: config.initial as f64,
+            in_flight: 0,
+            last_decrease: None,
+        };
+        Self {
+            config,
+            state: Arc::new(Mutex::new(state)),
+            released: Arc::new(Notify::new()),
+        }
+    }
+
+    /// Returns the largest number of commits that may ever be in flight.
+    pub(crate) fn max(&self) -> usize {
+        self.config.max
+    }
+
+    /// Returns the number of commits currently allowed in flight.
+    #[cfg(test)]
+    fn limit(&self) -> usize {
+        self.lock().limit as usize
+    }
+
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Waits until another commit may be sent.
+    pub(crate) async fn acquire(&self) -> AdaptivePermit {
+        loop {
+            // Registered before checking the state, so that a release in between isn't missed.
+            let released = self.released.notified();
+            {
+                let mut state = self.lock();
+                if state.in_flight < state.limit as usize {
+                    state.in_flight += 1;
+                    return AdaptivePermit {
+                        limiter: self.clone(),
+                        started: Instant::now(),
+                        overloaded: false,
+                    };
+                }
+            }
+            released.await;
+        }
+    }
+
+    fn release(&self, latency: Duration, overloaded: bool) {
+        let mut state = self.lock();
+        state.in_flight -= 1;
+        if overloaded || latency > self.config.target_latency {
+            // Commits in flight when the limit is decreased are likely to be slow too, so the limit is decreased
+            // at most once per target latency rather than once per slow commit.
+            let now = Instant::now();
+            let decrease = state.last_decrease.map_or(true, |at| {
+                now.duration_since(at) >= self.config.target_latency
+            });
+            if decrease {
+                state.limit = (state.limit / 2.0).max(self.config.min as f64);
+                state.last_decrease = Some(now);
+            }
+        } else {
+            state.limit = (state.limit + 1.0 / state.limit).min(self.config.max as f64);
+        }
+        drop(state);
+        self.released.notify_waiters();
+    }
+}
+
+impl AdaptivePermit {
+    /// Records an error returned by the commit, errors indicating that Cloud Spanner is overloaded decrease the limit.
+    pub(crate) fn observe(&mut self, error: &Error) {
+        if matches!(
+            error.code(),
+            Some(
+                ErrorCode::ResourceExhausted | ErrorCode::Unavailable | ErrorCode::DeadlineExceeded
+            )
+        ) {
+            self.overloaded = true;
+        }
+    }
+}
+
+impl Drop for AdaptivePermit {
+    fn drop(&mut self) {
+        self.limiter
+            .release(self.started.elapsed(), self.overloaded);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn limiter(initial: usize, min: usize, max: usize) -> AdaptiveLimiter {
+        AdaptiveLimiter::new(
+            AdaptiveConcurrency::builder()
+                .initial(initial)
+                .min(min)
+                .max(max)
+                .build()
+                .unwrap(),
+        )
+    }
+
+    async fn is_blocked(limiter: &AdaptiveLimiter) -> bool {
+        tokio::time::timeout(Duration::from_millis(10), limiter.acquire())
+            .await
+            .is_err()
+    }
+
+    #[test]
+    fn test_builder() {
+        assert_eq!(
+            AdaptiveConcurrency::builder().build().unwrap(),
+            AdaptiveConcurrency::default()
+        );
+        for builder in [
+            AdaptiveConcurrency::builder().min(0),
+            AdaptiveConcurrency::builder().initial(0),
+            AdaptiveConcurrency::builder().min(8).initial(4),
+            AdaptiveConcurrency::builder().initial(64),
+            AdaptiveConcurrency::builder().target_latency(Duration::ZERO),
+        ] {
+            assert!(matches!(builder.build(), Err(Error::Config(_))));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_acquire() {
+        let limiter = limiter(2, 1, 4);
+        let first = limiter.acquire().await;
+        let _second = limiter.acquire().await;
+        assert!(is_blocked(&limiter).await);
+
+        drop(first);
+        let _third = limiter.acquire().await;
+    }
+
+    #[tokio::test]
+    async fn test_acquire_wakes_waiters() {
+        let limiter = limiter(1, 1, 1);
+        let permit = limiter.acquire().await;
+        let waiter = tokio::spawn({
+            let limiter = limiter.clone();
+            async move {
+                limiter.acquire().await;
+            }
+        });
+        tokio::time::sleep(Duration::from_millis(10)).await;
+        drop(permit);
+        tokio::time::timeout(Duration::from_secs(1), waiter)
+            .await
+            .unwrap()
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_increase() {
+        let limiter = limiter(2, 1, 3);
+        for _ in 0..2 {
+            drop(limiter.acquire().await);
+        }
+        // 2 + 1/2 + 1/2.5
+        assert_eq!(limiter.limit(), 2);
+        drop(limiter.acquire().await);
+        assert_eq!(limiter.limit(), 3);
+        for _ in 0..10 {
+            drop(limiter.acquire().await);
+        }
+        assert_eq!(limiter.limit(), 3);
+    }
+
+    async fn complete_after(limiter: &AdaptiveLimiter, latency: Duration) {
+        let mut permit = limiter.acquire().await;
+        permit.started -= latency;
+    }
+
+    #[tokio::test]
+    async fn test_decrease_on_latency() {
+        let limiter = limiter(8, 3, 8);
+        complete_after(&limiter, Duration::from_secs(2)).await;
+        assert_eq!(limiter.limit(), 4);
+
+        // a single decrease per target latency
+        complete_after(&limiter, Duration::from_secs(2)).await;
+        assert_eq!(limiter.limit(), 4);
+
+        limiter.lock().last_decrease = None;
+        complete_after(&limiter, Duration::from_secs(2)).await;
+        assert_eq!(limiter.limit(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_decrease_on_overload() {
+        let limiter = limiter(8, 1, 8);
+        let mut permit = limiter.acquire().await;
+        permit.observe(&Error::Status(tonic::Status::already_exists("row exists")));
+        drop(permit);
+        assert_eq!(limiter.limit(), 8);
+
+        let mut permit = limiter.acquire().await;
+        permit.observe(&Error::Status(tonic::Status::resource_exhausted(
+            "overloaded",
+        )));
+        drop(permit);
+        assert_eq!(limiter.limit(), 4);
+    }
+}
@@ -0,0 +1,317 @@
+use std::time::SystemTime;
+
+use google_api_proto::google::{rpc, spanner::v1 as spanner};
+use prost::Message;
+
+use crate::mutation::{check_keys, MAX_COMMIT_BYTES};
+#[cfg(doc)]
+use crate::Client;
+use crate::{Error, Mutation, MAX_COMMIT_MUTATIONS};
+
+/// The `BatchWrite` messages, which are not part of the generated protos this crate depends on.
+pub(crate) mod proto {
+    use google_api_proto::google::{rpc, spanner::v1 as spanner};
+
+    pub(crate) const BATCH_WRITE_PATH: &str = "/google.spanner.v1.Spanner/BatchWrite";
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(crate) struct BatchWriteRequest {
+        #[prost(string, tag = "1")]
+        pub(crate) session: String,
+        #[prost(message, optional, tag = "3")]
+        pub(crate) request_options: Option<spanner::RequestOptions>,
+        #[prost(message, repeated, tag = "4")]
+        pub(crate) mutation_groups: Vec<MutationGroup>,
+        #[prost(bool, tag = "5")]
+        pub(crate) exclude_txn_from_change_streams: bool,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(crate) struct MutationGroup {
+        #[prost(message, repeated, tag = "1")]
+        pub(crate) mutations: Vec<spanner::Mutation>,
+    }
+
+    #[derive(Clone, PartialEq, prost::Message)]
+    pub(crate) struct BatchWriteResponse {
+        #[prost(int32, repeated, tag = "1")]
+        pub(crate) indexes: Vec<i32>,
+        #[prost(message, optional, tag = "2")]
+        pub(crate) status: Option<rpc::Status>,
+        #[prost(message, optional, tag = "3")]
+        pub(crate) commit_timestamp: Option<prost_types::Timestamp>,
+    }
+}
+
+/// Mutations that are applied atomically by [`Client::batch_write`], e.g.: a parent row and its interleaved children.
+///
+/// Groups are validated when built, such that a batch is not rejected as a whole because of a single invalid group.
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{Mutation, MutationGroup};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// let group = MutationGroup::builder()
+///     .mutation(Mutation::insert("singer").set("id", 1).set("name", "ferris"))
+///     .mutation(Mutation::insert("album").set("singer_id", 1).set("id", 1))
+///     .build()?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct MutationGroup {
+    mutations: Vec<Mutation>,
+}
+
+impl MutationGroup {
+    /// Returns a new [`MutationGroupBuilder`] for grouping mutations.
+    pub fn builder() -> MutationGroupBuilder {
+        MutationGroupBuilder::default()
+    }
+
+    /// Returns the mutations of this group, in the order they were added.
+    pub fn mutations(&self) -> &[Mutation] {
+        &self.mutations
+    }
+}
+
+impl TryFrom<MutationGroup> for proto::MutationGroup {
+    type Error = Error;
+
+    fn try_from(group: MutationGroup) -> Result<Self, Self::Error> {
+        Ok(proto::MutationGroup {
+            mutations: group
+                .mutations
+                .into_iter()
+                .map(spanner::Mutation::try_from)
+                .collect::<Result<_, _>>()?,
+        })
+    }
+}
+
+/// Builds a [`MutationGroup`], see [`MutationGroup::builder`].
+#[derive(Clone, Debug, Default)]
+pub struct MutationGroupBuilder {
+    mutations: Vec<Mutation>,
+}
+
+impl MutationGroupBuilder {
+    /// Adds a mutation to the group.
+    #[must_use]
+    pub fn mutation(mut self, mutation: Mutation) -> Self {
+        self.mutations.push(mutation);
+        self
+    }
+
+    /// Adds several mutations to the group.
+    #[must_use]
+    pub fn mutations(mut self, mutations: impl IntoIterator<Item = Mutation>) -> Self {
+        self.mutations.extend(mutations);
+        self
+    }
+
+    /// Builds the group, failing when it is empty, when one of its values cannot be converted
+    /// or when it exceeds the 100 MB or the [`MAX_COMMIT_MUTATIONS`] Cloud Spanner allows per commit.
+    /// It also fails when a key exceeds 8 KiB, for the mutations whose [key columns](Mutation::with_key_columns) are known.
+    pub fn build(self) -> Result<MutationGroup, Error> {
+        self.build_within(MAX_COMMIT_BYTES, MAX_COMMIT_MUTATIONS)
+    }
+
+    fn build_within(self, max_bytes: usize, max_mutations: usize) -> Result<MutationGroup, Error> {
+        if self.mutations.is_empty() {
+            return Err(Error::Client(
+                "a mutation group must contain at least one mutation".to_string(),
+            ));
+        }
+        check_keys(&self.mutations)?;
+        let count = self
+            .mutations
+            .iter()
+            .map(Mutation::mutation_count)
+            .sum::<usize>();
+        if count > max_mutations {
+            return Err(Error::Client(format!(
+                "mutation group has {} mutations, which exceeds the limit of {} mutations",
+                count, max_mutations
+            )));
+        }
+        let group = MutationGroup {
+            mutations: self.mutations,
+        };
+        let size = proto::MutationGroup::try_from(group.clone())?.encoded_len();
+        if size > max_bytes {
+            return Err(Error::Client(format!(
+                "mutation group is {} bytes, which exceeds the limit of {} bytes",
+                size, max_bytes
+            )));
+        }
+        Ok(group)
+    }
+}
+
+/// The outcome of applying a [`MutationGroup`] submitted using [`Client::batch_write`].
+///
+/// Each group is applied atomically, but independently of the others and in no particular order.
+#[derive(Debug)]
+pub struct MutationGroupOutcome {
+    index: usize,
+    result: Result<SystemTime, Error>,
+}
+
+impl MutationGroupOutcome {
+    /// Returns the index of the group in the submitted batch.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the timestamp at which the group was committed, or `None` if it was not applied.
+    pub fn commit_timestamp(&self) -> Option<SystemTime> {
+        self.result.as_ref().ok().copied()
+    }
+
+    /// Returns the error that prevented applying the group, if any.
+    pub fn error(&self) -> Option<&Error> {
+        self.result.as_ref().err()
+    }
+
+    /// Returns the group's commit timestamp, or the error that prevented applying it.
+    pub fn into_result(self) -> Result<SystemTime, Error> {
+        self.result
+    }
+}
+
+/// Returns the outcome of each group a `BatchWrite` response applies to.
+pub(crate) fn outcomes(
+    response: proto::BatchWriteResponse,
+) -> Result<Vec<MutationGroupOutcome>, Error> {
+    let result = match response
+        .status
+        .filter(|status| status.code != tonic::Code::Ok as i32)
+    {
+        Some(status) => Err(status),
+        None => Ok(response
+            .commit_timestamp
+            .ok_or_else(|| Error::Codec("missing commit timestamp".to_string()))
+            .and_then(|timestamp| {
+                SystemTime::try_from(timestamp)
+                    .map_err(|_| Error::Codec("invalid commit timestamp".to_string()))
+            })?),
+    };
+    response
+        .indexes
+        .into_iter()
+        .map(|index| {
+            Ok(MutationGroupOutcome {
+                index: usize::try_from(index)?,
+                // The error is recreated for every group, since it cannot be cloned.
+                result: result
+                    .as_ref()
+                    .copied()
+                    .map_err(|status| Error::from(status_of(status))),
+            })
+        })
+        .collect()
+}
+
+fn status_of(status: &rpc::Status) -> tonic::Status {
+    tonic::Status::with_details(
+        tonic::Code::from(status.code),
+        status.message.clone(),
+        status.encode_to_vec().into(),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_mutation_group_builder() {
+        let group = MutationGroup::builder()
+            .mutation(Mutation::insert("singer").set("id", 1))
+            .mutations(vec![
+                Mutation::insert("album").set("singer_id", 1).set("id", 1),
+                Mutation::insert("album").set("singer_id", 1).set("id", 2),
+            ])
+            .build()
+            .unwrap();
+        assert_eq!(group.mutations().len(), 3);
+        assert_eq!(group.mutations()[0].table(), "singer");
+
+        assert!(matches!(
+            MutationGroup::builder().build(),
+            Err(Error::Client(_))
+        ));
+    }
+
+    #[test]
+    fn test_mutation_group_builder_too_large() {
+        let builder = MutationGroup::builder()
+            .mutation(Mutation::insert("singer").set("name", "x".repeat(64)));
+        assert!(builder.clone().build_within(1024, 10).is_ok());
+        assert!(matches!(
+            builder.build_within(32, 10),
+            Err(Error::Client(_))
+        ));
+    }
+
+    #[test]
+    fn test_mutation_group_builder_too_many_mutations() {
+        let builder = MutationGroup::builder()
+            .mutation(
+                Mutation::insert("singer")
+                    .set("id", 1)
+                    .set("name", "ferris"),
+            )
+            .mutation(Mutation::delete(
+                "album",
+                crate::KeySet::new().key(&[&1]).key(&[&2]),
+            ));
+        assert!(builder.clone().build_within(MAX_COMMIT_BYTES, 4).is_ok());
+        assert!(matches!(
+            builder.build_within(MAX_COMMIT_BYTES, 3),
+            Err(Error::Client(_))
+        ));
+    }
+
+    #[test]
+    fn test_outcomes() {
+        let now = SystemTime::now();
+        let outcomes = outcomes(proto::BatchWriteResponse {
+            indexes: vec![0, 2],
+            status: Some(rpc::Status::default()),
+            commit_timestamp: Some(now.into()),
+        })
+        .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].index(), 2);
+        assert_eq!(outcomes[1].commit_timestamp(), Some(now));
+        assert!(outcomes[1].error().is_none());
+    }
+
+    #[test]
+    fn test_outcomes_error() {
+        let mut outcomes = outcomes(proto::BatchWriteResponse {
+            indexes: vec![1, 3],
+            status: Some(rpc::Status {
+                code: tonic::Code::FailedPrecondition as i32,
+                message: "parent row is missing".to_string(),
+                details: Vec::new(),
+            }),
+            commit_timestamp: None,
+        })
+        .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        let outcome = outcomes.pop().unwrap();
+        assert_eq!(outcome.index(), 3);
+        assert!(outcome.commit_timestamp().is_none());
+        match outcome.into_result() {
+            Err(Error::Status(status)) => {
+                assert_eq!(status.code(), tonic::Code::FailedPrecondition);
+                assert_eq!(status.message(), "parent row is missing");
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
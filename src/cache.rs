@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::{Fingerprint, ResultSet, Statement, TimestampBound};
+
+/// A read-through cache for the results of read-only queries.
+///
+/// When configured using [`crate::ConfigBuilder::query_cache`], the result sets of queries executed using
+/// [`crate::Client::read_only_with_bound`] are cached, keyed by the statement's SQL, its parameters and the bound.
+/// Reads using [`TimestampBound::Strong`] or [`TimestampBound::ExactStaleness`] (including [`crate::Client::read_only`])
+/// are never cached, since a cached result could not be exactly as stale as requested.
+///
+/// Entries expire after the cache's TTL, but the bound is also taken into account:
+///
+/// * `ReadTimestamp` and `MinReadTimestamp` reads are repeatable, so they are kept for the whole TTL;
+/// * `MaxStaleness` reads are only kept until their data becomes staler than the bound, based on the timestamp
+///   Cloud Spanner read at, so a cached result is never older than the requested staleness.
+///
+/// The cache is not aware of writes. Applications should invalidate entries when they know some data has changed,
+/// using either [`QueryCache::invalidate`], [`QueryCache::invalidate_if`] or [`QueryCache::clear`].
+///
+/// A `QueryCache` is cheap to clone and clones share the same entries, which allows sharing a cache between clients.
+///
+/// # Example
+///
+/// ```no_run
+/// # use std::time::Duration;
+/// # use spanner_rs::{Client, Error, QueryCache, ReadContext, Statement, TimestampBound};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// let cache = QueryCache::new(Duration::from_secs(30), 1_000);
+/// let client = Client::configure().query_cache(cache.clone()).connect().await?;
+///
+/// let statement = Statement::new("SELECT name FROM person WHERE id = @id").bind("id", 42);
+/// let rs = client
+///     .read_only_with_bound(TimestampBound::MaxStaleness(Duration::from_secs(15)))
+///     .execute_statement(&statement)
+///     .await?;
+///
+/// // after updating person 42
+/// cache.invalidate(&statement);
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct QueryCache {
+    ttl: Duration,
+    capacity: usize,
+    entries: Arc<Mutex<HashMap<Key, Entry>>>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct Key {
+    sql: String,
+    params: String,
+    bound: String,
+}
+
+struct Entry {
+    fingerprint: Fingerprint,
+    expires_at: Instant,
+    result_set: ResultSet,
+}
+
+impl QueryCache {
+    /// Creates a new cache holding up to `capacity` result sets, each for at most `ttl`.
+    ///
+    /// When the cache is full, expired entries are evicted first, then the entry closest to expiring.
+    pub fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            ttl,
+            capacity,
+            entries: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Removes the cached results of the provided statement, regardless of its parameters or timestamp bound.
+    ///
+    /// Statements are matched using their [`Fingerprint`].
+    pub fn invalidate(&self, statement: &Statement) {
+        let fingerprint = statement.fingerprint();
+        self.invalidate_if(|entry| entry == fingerprint);
+    }
+
+    /// Removes the cached results of all statements whose [`Fingerprint`] matches the provided predicate.
+    pub fn invalidate_if<F>(&self, mut predicate: F)
+    where
+        F: FnMut(Fingerprint) -> bool,
+    {
+        self.lock().retain(|_, entry| !predicate(entry.fingerprint));
+    }
+
+    /// Removes all entries from this cache.
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    /// Returns the number of entries in this cache, including the ones that have expired but not yet been evicted.
+    pub fn len(&self) -> usize {
+        self.lock().len()
+    }
+
+    /// Returns true when this cache holds no entries.
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Returns the cached result set for the statement read at the provided bound, if any.
+    pub(crate) fn get(&self, statement: &Statement, bound: &TimestampBound) -> Option<ResultSet> {
+        let key = Key::of(statement, bound)?;
+        let mut entries = self.lock();
+        match entries.get(&key) {
+            Some(entry) if entry.expires_at > Instant::now() => Some(entry.result_set.clone()),
+            Some(_) => {
+                entries.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Caches the result set of the statement read at the provided bound, if the bound allows it.
+    pub(crate) fn put(
+        &self,
+        statement: &Statement,
+        bound: &TimestampBound,
+        result_set: &ResultSet,
+    ) {
+        let ttl = match self.ttl_for(bound, result_set) {
+            Some(ttl) if !ttl.is_zero() && self.capacity > 0 => ttl,
+            _ => return,
+        };
+        let key = match Key::of(statement, bound) {
+            Some(key) => key,
+            None => return,
+        };
+
+        let now = Instant::now();
+        let mut entries = self.lock();
+        if !entries.contains_key(&key) && entries.len() >= self.capacity {
+            entries.retain(|_, entry| entry.expires_at > now);
+            if entries.len() >= self.capacity {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires_at)
+                    .map(|(key, _)| key.clone());
+                if let Some(oldest) = oldest {
+                    entries.remove(&oldest);
+                }
+            }
+        }
+        entries.insert(
+            key,
+            Entry {
+                fingerprint: statement.fingerprint(),
+                expires_at: now + ttl,
+                result_set: result_set.clone(),
+            },
+        );
+    }
+
+    fn ttl_for(&self, bound: &TimestampBound, result_set: &ResultSet) -> Option<Duration> {
+        match bound {
+            TimestampBound::Strong | TimestampBound::ExactStaleness(_) => None,
+            TimestampBound::ReadTimestamp(_) | TimestampBound::MinReadTimestamp(_) => {
+                Some(self.ttl)
+            }
+            TimestampBound::MaxStaleness(staleness) => {
+                // results without a read timestamp have an unknown staleness, so they are not cached
+                let read_timestamp = result_set.transaction.as_ref()?.read_timestamp()?;
+                let observed = SystemTime::now()
+                    .duration_since(read_timestamp)
+                    .unwrap_or_default();
+                Some(self.ttl.min(staleness.saturating_sub(observed)))
+            }
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<Key, Entry>> {
+        // the lock is never held across operations that may panic, so poisoning can safely be ignored.
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Key {
+    fn of(statement: &Statement, bound: &TimestampBound) -> Option<Self> {
+        // statements with invalid parameters are never cached
+        let values = statement.values().ok()?;
+        let params = values
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value.to_sql_literal()))
+            .collect::<Vec<_>>()
+            .join(",");
+        Some(Self {
+            sql: statement.sql().to_string(),
+            params,
+            bound: format!("{:?}", bound),
+        })
+    }
+}
+
+impl std::fmt::Debug for QueryCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QueryCache")
+            .field("ttl", &self.ttl)
+            .field("capacity", &self.capacity)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use google_api_proto::google::spanner::v1 as proto;
+
+    fn read_at(read_timestamp: SystemTime) -> ResultSet {
+        ResultSet::try_from(proto::ResultSet {
+            metadata: Some(proto::ResultSetMetadata {
+                transaction: Some(proto::Transaction {
+                    read_timestamp: Some(read_timestamp.into()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        })
+        .unwrap()
+    }
+
+    fn result_set() -> ResultSet {
+        read_at(SystemTime::now())
+    }
+
+    fn stale() -> TimestampBound {
+        TimestampBound::MaxStaleness(Duration::from_secs(60))
+    }
+
+    #[test]
+    fn test_get_put() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let statement = Statement::new("SELECT * FROM person WHERE id = @id").bind("id", 42);
+
+        assert!(cache.get(&statement, &stale()).is_none());
+        cache.put(&statement, &stale(), &result_set());
+        assert!(cache.get(&statement, &stale()).is_some());
+
+        // parameters and bounds are part of the key
+        assert!(cache
+            .get(
+                &Statement::new("SELECT * FROM person WHERE id = @id").bind("id", 7),
+                &stale()
+            )
+            .is_none());
+        assert!(cache
+            .get(
+                &statement,
+                &TimestampBound::ExactStaleness(Duration::from_secs(60))
+            )
+            .is_none());
+
+        // literals are part of the key, even though they share the same fingerprint
+        cache.put(
+            &Statement::new("SELECT * FROM person WHERE id = 1"),
+            &stale(),
+            &result_set(),
+        );
+        assert!(cache
+            .get(
+                &Statement::new("SELECT * FROM person WHERE id = 2"),
+                &stale()
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_strong_reads_not_cached() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let statement = Statement::new("SELECT 1");
+
+        cache.put(&statement, &TimestampBound::Strong, &result_set());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_exact_staleness_not_cached() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let bound = TimestampBound::ExactStaleness(Duration::from_secs(60));
+
+        cache.put(&Statement::new("SELECT 1"), &bound, &result_set());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_max_staleness_ttl() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let bound = TimestampBound::MaxStaleness(Duration::from_secs(15));
+
+        // the data is already 10 seconds old, it may only be served for the remaining 5 seconds
+        let read_timestamp = SystemTime::now() - Duration::from_secs(10);
+        let ttl = cache.ttl_for(&bound, &read_at(read_timestamp)).unwrap();
+        assert!(ttl <= Duration::from_secs(5));
+        assert!(ttl > Duration::from_secs(4));
+
+        // the data is already staler than the bound
+        let read_timestamp = SystemTime::now() - Duration::from_secs(20);
+        let statement = Statement::new("SELECT 1");
+        cache.put(&statement, &bound, &read_at(read_timestamp));
+        assert!(cache.is_empty());
+
+        // the staleness is unknown without a read timestamp
+        let unknown = ResultSet::try_from(proto::ResultSet::default()).unwrap();
+        cache.put(&statement, &bound, &unknown);
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_expiry() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let statement = Statement::new("SELECT 1");
+        let bound = TimestampBound::MaxStaleness(Duration::ZERO);
+
+        // entries of staleness bounded reads never outlive the bound itself
+        cache.put(&statement, &bound, &result_set());
+        assert!(cache.get(&statement, &bound).is_none());
+
+        let cache = QueryCache::new(Duration::from_millis(1), 10);
+        cache.put(&statement, &stale(), &result_set());
+        std::thread::sleep(Duration::from_millis(5));
+        assert!(cache.get(&statement, &stale()).is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_capacity() {
+        let cache = QueryCache::new(Duration::from_secs(60), 2);
+        for id in 0..5 {
+            cache.put(
+                &Statement::new("SELECT @id").bind("id", id),
+                &stale(),
+                &result_set(),
+            );
+        }
+        assert_eq!(cache.len(), 2);
+        assert!(cache
+            .get(&Statement::new("SELECT @id").bind("id", 4), &stale())
+            .is_some());
+    }
+
+    #[test]
+    fn test_invalidate() {
+        let cache = QueryCache::new(Duration::from_secs(60), 10);
+        let person = Statement::new("SELECT * FROM person WHERE id = @id");
+        let versions = Statement::new("SELECT * FROM versions");
+
+        cache.put(&person.clone().bind("id", 1), &stale(), &result_set());
+        cache.put(&person.clone().bind("id", 2), &stale(), &result_set());
+        cache.put(&versions, &stale(), &result_set());
+        assert_eq!(cache.len(), 3);
+
+        cache.invalidate(&person);
+        assert_eq!(cache.len(), 1);
+
+        let clone = cache.clone();
+        clone.invalidate_if(|fingerprint| fingerprint == versions.fingerprint());
+        assert!(cache.is_empty());
+
+        cache.put(&versions, &stale(), &result_set());
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+}
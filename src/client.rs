@@ -1,19 +1,39 @@
 use std::future::Future;
 use std::pin::Pin;
+use std::time::{Duration, Instant, SystemTime};
 
-use bb8::{Pool, PooledConnection};
-use tonic::Code;
+use futures_util::stream::{self, Stream};
+use futures_util::{StreamExt, TryStreamExt};
 
-use crate::result_set::ResultSet;
-use crate::statement::Statement;
+use crate::backpressure::AdaptiveLimiter;
+use crate::health::{HealthReport, Step, HEALTH_CHECK_QUERY};
+use crate::mutation::check_commit;
+use crate::partition::QueryPartition;
+use crate::result_set::{OwnedRow, ResultSet};
+use crate::schema::Schema;
+use crate::statement::{IntoParameters, Statement, StatementKind};
 use crate::TimestampBound;
-use crate::ToSpanner;
-use crate::{session::SessionManager, ConfigBuilder, Connection, Error, TransactionSelector};
+use crate::{
+    chunk_mutations, AdaptiveConcurrency, CommitResponse, ConfigBuilder, Connection, Error,
+    Mutation, MutationGroup, MutationGroupOutcome, QueryCache, QueryPlan, RetryObserver,
+    RetryPolicy, SessionGuard, SessionPool, SharedConnection, TransactionSelector, TxOptions,
+};
+use crate::{FromSpanner, ToRow, ToSpanner};
 
 /// An asynchronous Cloud Spanner client.
+///
+/// Clients are `Send` and `Sync` and all their methods take `&self`, so a single client can be shared between tasks,
+/// e.g.: in an `Arc` or as the state of a web service, and start reads and transactions concurrently.
+/// Clients can also be cloned cheaply, clones share the same session pool.
+#[derive(Clone)]
 pub struct Client {
-    connection: Box<dyn Connection>,
-    session_pool: Pool<SessionManager>,
+    connection: SharedConnection,
+    session_pool: SessionPool,
+    read_only: bool,
+    retry_policy: RetryPolicy,
+    retry_observer: Option<RetryObserver>,
+    query_cache: Option<QueryCache>,
+    write_limiter: Option<AdaptiveLimiter>,
 }
 
 impl Client {
@@ -24,13 +44,50 @@ impl Client {
 }
 
 impl Client {
-    pub(crate) fn connect(
-        connection: Box<dyn Connection>,
-        session_pool: Pool<SessionManager>,
-    ) -> Self {
+    pub(crate) fn connect(connection: Box<dyn Connection>, session_pool: SessionPool) -> Self {
         Self {
-            connection,
+            connection: SharedConnection::new(connection),
             session_pool,
+            read_only: false,
+            retry_policy: RetryPolicy::default(),
+            retry_observer: None,
+            query_cache: None,
+            write_limiter: None,
+        }
+    }
+
+    pub(crate) fn with_read_only(self, read_only: bool) -> Self {
+        Self { read_only, ..self }
+    }
+
+    pub(crate) fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_retry_observer(self, retry_observer: Option<RetryObserver>) -> Self {
+        Self {
+            retry_observer,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_query_cache(self, query_cache: Option<QueryCache>) -> Self {
+        Self {
+            query_cache,
+            ..self
+        }
+    }
+
+    pub(crate) fn with_write_backpressure(
+        self,
+        write_backpressure: Option<AdaptiveConcurrency>,
+    ) -> Self {
+        Self {
+            write_limiter: write_backpressure.map(AdaptiveLimiter::new),
+            ..self
         }
     }
 
@@ -38,35 +95,555 @@ impl Client {
     /// The returned context uses [`TimestampBound::Strong`] consistency for each individual read.
     pub fn read_only(&self) -> impl ReadContext {
         ReadOnly {
-            connection: self.connection.clone(),
+            connection: self.connection.get(),
             bound: None,
             session_pool: self.session_pool.clone(),
+            read_only: self.read_only,
+            query_cache: None,
         }
     }
 
     /// Returns a [`ReadContext`] that can be used to read data out of Cloud Spanner.
     /// The returned context uses the specified bounded consistency for each individual read.
+    ///
+    /// When a [`QueryCache`] is configured, results may be served from the cache, see [`QueryCache`] for details.
     pub fn read_only_with_bound(&self, bound: TimestampBound) -> impl ReadContext {
         ReadOnly {
-            connection: self.connection.clone(),
+            connection: self.connection.get(),
             bound: Some(bound),
             session_pool: self.session_pool.clone(),
+            read_only: self.read_only,
+            query_cache: self.query_cache.clone(),
+        }
+    }
+
+    /// Returns a [`ReadContext`] that reads a consistent snapshot of the data as of the provided timestamp.
+    ///
+    /// This is a shortcut for [`Client::read_only_with_bound`] using [`TimestampBound::ReadTimestamp`].
+    pub fn read_only_at(&self, timestamp: SystemTime) -> impl ReadContext {
+        self.read_only_with_bound(TimestampBound::ReadTimestamp(timestamp))
+    }
+
+    /// Returns a [`ReadContext`] whose reads observe data that is at most `max_staleness` old.
+    ///
+    /// This is a shortcut for [`Client::read_only_with_bound`] using [`TimestampBound::MaxStaleness`].
+    pub fn read_only_stale(&self, max_staleness: Duration) -> impl ReadContext {
+        self.read_only_with_bound(TimestampBound::MaxStaleness(max_staleness))
+    }
+
+    /// Returns the [`QueryPlan`] of a query, as determined by Cloud Spanner, without executing it.
+    ///
+    /// This can be used to flag statements that would scan entire tables before they ship, e.g.: in CI checks.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, Statement};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let plan = client
+    ///     .estimate(&Statement::new("SELECT * FROM person WHERE name = @name").bind("name", "ferris"))
+    ///     .await?;
+    /// assert!(!plan.has_full_scan(), "statement scans entire tables: {:?}", plan.scans());
+    /// # Ok(()) }
+    /// ```
+    pub async fn estimate(&self, statement: &Statement) -> Result<QueryPlan, Error> {
+        if self.read_only {
+            check_read_only(statement.sql())?;
+        }
+        let session = self.session_pool.get().await?;
+        self.connection.get().plan_sql(&session, statement).await
+    }
+
+    /// Returns the [`Schema`] of the database, i.e.: its tables, columns, primary keys and indexes, read from `INFORMATION_SCHEMA`.
+    ///
+    /// This can be used by code generation or migration tools, but note that only GoogleSQL-dialect databases are supported.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let schema = client.schema().await?;
+    /// for table in schema.tables() {
+    ///     let columns: Vec<&str> = table.columns().iter().map(|column| column.name()).collect();
+    ///     println!("{}({})", table.name(), columns.join(", "));
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn schema(&self) -> Result<Schema, Error> {
+        Schema::read(&mut self.read_only()).await
+    }
+
+    /// Divides a query into partitions that can be executed in parallel, e.g.: by export or scan jobs.
+    ///
+    /// The partitions read from a read-only transaction using the provided bound, which must be one of
+    /// [`TimestampBound::Strong`], [`TimestampBound::ReadTimestamp`] or [`TimestampBound::ExactStaleness`].
+    /// Only queries whose first operator is a distributed union can be partitioned, see
+    /// [the Spanner Documentation](https://cloud.google.com/spanner/docs/reads#read_data_in_parallel).
+    ///
+    /// The transaction uses a dedicated session which is not part of the session pool,
+    /// Cloud Spanner deletes it once it has been idle for an hour.
+    /// See [`Client::batch_read_only`] to partition several queries using the same transaction and to control the session's lifetime.
+    ///
+    /// See [`Client::execute_partitions`] to execute all partitions as a single stream of rows.
+    pub async fn partition_query(
+        &self,
+        statement: impl Into<Statement>,
+        bound: TimestampBound,
+    ) -> Result<Vec<QueryPartition>, Error> {
+        self.batch_read_only(bound)
+            .await?
+            .partition_query(statement)
+            .await
+    }
+
+    /// Begins a read-only transaction pinned to a single timestamp, which is used to export a consistent snapshot of
+    /// one or more tables by executing their partitions in parallel.
+    ///
+    /// The bound must be one of [`TimestampBound::Strong`], [`TimestampBound::ReadTimestamp`] or [`TimestampBound::ExactStaleness`],
+    /// [single-use only](TimestampBound::is_single_use_only) bounds are rejected before any request is sent.
+    /// See [`BatchClient`] for details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, TimestampBound};
+    /// use futures_util::TryStreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let batch = client.batch_read_only(TimestampBound::Strong).await?;
+    /// for table in ["person", "address"] {
+    ///     let mut rows = Box::pin(batch.export(format!("SELECT * FROM {}", table), 4));
+    ///     while let Some(row) = rows.try_next().await? {
+    ///         // write the row to a file
+    ///     }
+    /// }
+    /// // both tables were read as of the same timestamp
+    /// println!("exported as of {:?}", batch.read_timestamp());
+    /// batch.close().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn batch_read_only(&self, bound: TimestampBound) -> Result<BatchClient<'_>, Error> {
+        let bound = bound.multi_use()?;
+        let mut connection = self.connection.get();
+        let session = connection.create_session().await?;
+        let transaction = connection.begin_read_only(&session, bound).await?;
+        Ok(BatchClient {
+            client: self,
+            session,
+            transaction,
+        })
+    }
+
+    /// Executes a single partition of a query, see [`Client::partition_query`].
+    pub async fn execute_partition(&self, partition: &QueryPartition) -> Result<ResultSet, Error> {
+        self.connection
+            .get()
+            .execute_partition(
+                &partition.session,
+                &partition.transaction,
+                &partition.statement,
+                &partition.token,
+            )
+            .await
+    }
+
+    /// Executes the partitions of a query, at most `concurrency` at a time, and returns their rows as a single stream.
+    ///
+    /// Rows are returned in the order of the partitions, see [`Client::execute_partitions_unordered`] to return them as soon
+    /// as their partition completes instead. Partitions are only executed as the stream is consumed, which provides backpressure.
+    /// Each partition's result set is held in memory until its rows are consumed.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, TimestampBound};
+    /// use futures_util::TryStreamExt;
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let partitions = client
+    ///     .partition_query("SELECT id, name FROM person", TimestampBound::Strong)
+    ///     .await?;
+    /// let mut rows = Box::pin(client.execute_partitions(partitions, 4));
+    /// while let Some(row) = rows.try_next().await? {
+    ///     let name: &str = row.get("name")?;
+    ///     println!("{}", name);
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn execute_partitions(
+        &self,
+        partitions: Vec<QueryPartition>,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<OwnedRow, Error>> + '_ {
+        stream::iter(partitions)
+            .map(move |partition| async move { self.execute_partition(&partition).await })
+            .buffered(concurrency.max(1))
+            .map_ok(partition_rows)
+            .try_flatten()
+    }
+
+    /// Executes the partitions of a query, at most `concurrency` at a time, and returns their rows as a single stream.
+    ///
+    /// Unlike [`Client::execute_partitions`], the rows of a partition are returned as soon as it completes,
+    /// regardless of the order of the partitions.
+    pub fn execute_partitions_unordered(
+        &self,
+        partitions: Vec<QueryPartition>,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<OwnedRow, Error>> + '_ {
+        stream::iter(partitions)
+            .map(move |partition| async move { self.execute_partition(&partition).await })
+            .buffer_unordered(concurrency.max(1))
+            .map_ok(partition_rows)
+            .try_flatten()
+    }
+
+    /// Fills the session pool up to its [minimum size](crate::SessionPoolConfigBuilder::min_idle), e.g.: before serving traffic.
+    ///
+    /// This is mostly useful when [`ConfigBuilder::lazy_connect`] is enabled, since the pool is otherwise filled when connecting.
+    /// Missing sessions are created using a single request, then checked out of the pool concurrently,
+    /// which takes at most the pool's [connection timeout](crate::SessionPoolConfigBuilder::connection_timeout).
+    /// In single-session mode, this creates the session if necessary.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// let client = Client::configure().lazy_connect(true).connect().await?;
+    /// client.warmup().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn warmup(&self) -> Result<(), Error> {
+        self.session_pool.warmup(self.connection.get()).await
+    }
+
+    /// Gracefully shuts down this client, e.g.: during a rolling restart.
+    ///
+    /// This stops handing out sessions, which makes any subsequent read or transaction fail with an [`Error::Client`].
+    /// It then waits up to `timeout` for in-flight reads and transactions to complete before deleting all sessions.
+    ///
+    /// An error is returned if some sessions were still in use when the timeout elapsed; these are deleted
+    /// in the background once released.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::time::Duration;
+    /// # use spanner_rs::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// client.drain(Duration::from_secs(10)).await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn drain(&self, timeout: Duration) -> Result<(), Error> {
+        self.session_pool
+            .drain(self.connection.get(), timeout)
+            .await
+    }
+
+    /// Checks that this client is able to reach Cloud Spanner, e.g.: for readiness endpoints or synthetic monitoring.
+    ///
+    /// This obtains a session from the pool and executes a trivial query using it. The returned [`HealthReport`]
+    /// determines which step failed, if any, as well as the query's round-trip latency.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let report = client.health_check().await;
+    /// if !report.is_healthy() {
+    ///     eprintln!("unhealthy: {:?}", report.error());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn health_check(&self) -> HealthReport {
+        let session = match self.session_pool.get().await {
+            Ok(session) => session,
+            Err(err) => return HealthReport::failed(Step::Session, err),
+        };
+        let start = Instant::now();
+        let result = self
+            .connection
+            .get()
+            .execute_sql(
+                &session,
+                &TransactionSelector::SingleUse(None),
+                &Statement::new(HEALTH_CHECK_QUERY),
+                None,
+                None,
+            )
+            .await;
+        match result {
+            Ok(_) => HealthReport::healthy(start.elapsed()),
+            Err(err) => HealthReport::failed(Step::Query, err),
         }
     }
 
     /// Returns a [`TxRunner`] that can be used to execute transactions using a [`TransactionContext`]
     /// to read and write data from/into Cloud Spanner.
+    ///
+    /// Note that transactions cannot be run when the client is configured to be [read-only](crate::ConfigBuilder::read_only).
     pub fn read_write(&self) -> TxRunner {
         TxRunner {
             connection: self.connection.clone(),
             session_pool: self.session_pool.clone(),
+            read_only: self.read_only,
+            retry_policy: self.retry_policy.clone(),
+            retry_observer: self.retry_observer.clone(),
+        }
+    }
+
+    /// Applies groups of mutations using the `BatchWrite` RPC, which is the recommended way to ingest large amounts of data.
+    ///
+    /// Each group is applied atomically, but **not** the batch as a whole: groups are applied independently, in no particular order,
+    /// and some of them may fail while others succeed. The outcome of every group is streamed back as a [`MutationGroupOutcome`].
+    /// Mutations that must be applied together, e.g.: a parent row and its interleaved children, must be part of the same [`MutationGroup`].
+    ///
+    /// When [write backpressure](crate::ConfigBuilder::write_backpressure) is configured, batches wait for the limit before
+    /// being sent and count as a single commit until all their outcomes have been received.
+    ///
+    /// Unlike transactions, failed groups are not retried. Note that batch writes cannot be used when the client is
+    /// [read-only](crate::ConfigBuilder::read_only).
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use futures_util::StreamExt;
+    /// # use spanner_rs::{Client, Error, Mutation, MutationGroup};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let groups = (0..100)
+    ///     .map(|id| {
+    ///         MutationGroup::builder()
+    ///             .mutation(Mutation::insert("person").set("id", id).set("name", format!("person-{}", id)))
+    ///             .build()
+    ///     })
+    ///     .collect::<Result<Vec<_>, Error>>()?;
+    /// let mut outcomes = client.batch_write(groups).await?;
+    /// while let Some(outcome) = outcomes.next().await {
+    ///     let outcome = outcome?;
+    ///     if let Some(err) = outcome.error() {
+    ///         println!("group {} failed: {}", outcome.index(), err);
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn batch_write(
+        &self,
+        groups: Vec<MutationGroup>,
+    ) -> Result<impl Stream<Item = Result<MutationGroupOutcome, Error>> + '_, Error> {
+        if self.read_only {
+            return Err(Error::Client(
+                "batch writes are not allowed on a read-only client".to_string(),
+            ));
+        }
+        // The permit is obtained first, so that batches waiting on the limit don't hold sessions.
+        let mut permit = match &self.write_limiter {
+            Some(limiter) => Some(limiter.acquire().await),
+            None => None,
+        };
+        let session = self.session_pool.get().await?;
+        let responses = match self.connection.get().batch_write(&session, groups).await {
+            Ok(responses) => responses,
+            Err(err) => {
+                if let Some(permit) = permit.as_mut() {
+                    permit.observe(&err);
+                }
+                return Err(err);
+            }
+        };
+        // The session and permit are held until all responses have been received.
+        Ok(responses.map(move |response| {
+            let _session = &session;
+            if let Some(permit) = permit.as_mut() {
+                if let Some(err) = response
+                    .as_ref()
+                    .map_or_else(Some, MutationGroupOutcome::error)
+                {
+                    permit.observe(err);
+                }
+            }
+            response
+        }))
+    }
+
+    /// Applies a large number of mutations using as many commits as necessary for each to stay within
+    /// Cloud Spanner's limit on the number of mutations per commit, see [`chunk_mutations`].
+    ///
+    /// **NOTE:** the mutations are **not** applied atomically. Chunks are committed one after the other, in order,
+    /// each in its own transaction: when an error is returned, the chunks committed before the failing one remain applied.
+    /// Use a single transaction, see [`TxRunner::run`], when the mutations must be applied all together or not at all.
+    ///
+    /// When [write backpressure](crate::ConfigBuilder::write_backpressure) is configured, chunks are committed concurrently
+    /// instead, as many at a time as the commit latency allows. Chunks are then committed in no particular order:
+    /// when an error is returned, any other chunk may have been applied.
+    ///
+    /// Since mutation counts are estimated, `max_mutations` should leave room for the secondary index entries
+    /// affected by the mutations, see [`Mutation::mutation_count`].
+    ///
+    /// Returns the commit timestamp of each chunk.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, Mutation, MAX_COMMIT_MUTATIONS};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let mutations = (0..100_000)
+    ///     .map(|id| Mutation::insert("person").set("id", id).set("name", format!("person-{}", id)))
+    ///     .collect();
+    /// let commit_timestamps = client
+    ///     .write_chunked(mutations, MAX_COMMIT_MUTATIONS / 2)
+    ///     .await?;
+    /// println!("applied using {} commits", commit_timestamps.len());
+    /// # Ok(()) }
+    /// ```
+    pub async fn write_chunked(
+        &self,
+        mutations: Vec<Mutation>,
+        max_mutations: usize,
+    ) -> Result<Vec<SystemTime>, Error> {
+        let limiter = self.write_limiter.as_ref();
+        stream::iter(chunk_mutations(mutations, max_mutations))
+            .map(|chunk| async move {
+                let mut permit = match limiter {
+                    Some(limiter) => Some(limiter.acquire().await),
+                    None => None,
+                };
+                let outcome = self
+                    .read_write()
+                    .run_with_outcome(|tx| {
+                        tx.buffer_write(chunk.clone());
+                        Box::pin(async { Ok(()) })
+                    })
+                    .await;
+                if let (Some(permit), Err(err)) = (permit.as_mut(), &outcome) {
+                    permit.observe(err);
+                }
+                outcome
+            })
+            // Without a limiter, chunks are committed one at a time.
+            .buffered(limiter.map_or(1, AdaptiveLimiter::max))
+            .try_filter_map(|outcome| async move { Ok(outcome.commit_timestamp()) })
+            .try_collect()
+            .await
+    }
+}
+
+fn check_read_only(statement: &str) -> Result<(), Error> {
+    let kind = StatementKind::of(statement);
+    if kind.is_query() {
+        Ok(())
+    } else {
+        Err(Error::Client(format!(
+            "statement of kind {:?} rejected by read-only client",
+            kind
+        )))
+    }
+}
+
+fn partition_rows(result_set: ResultSet) -> impl Stream<Item = Result<OwnedRow, Error>> {
+    stream::iter(result_set.into_iter().map(Ok))
+}
+
+/// A read-only transaction pinned to a single timestamp, used to export consistent snapshots of tables,
+/// see [`Client::batch_read_only`].
+///
+/// All the queries partitioned using the same batch client read the data as of its [read timestamp](BatchClient::read_timestamp),
+/// regardless of when their partitions are executed.
+///
+/// The transaction uses a dedicated session which is not part of the session pool.
+/// Partitions remain valid until the session is deleted, either using [`BatchClient::close`] or by Cloud Spanner once
+/// it has been idle for an hour. Note that reads older than the database's version retention period (one hour by default)
+/// also fail, which bounds how long an export can take.
+pub struct BatchClient<'a> {
+    client: &'a Client,
+    session: crate::Session,
+    transaction: crate::Transaction,
+}
+
+impl<'a> BatchClient<'a> {
+    /// Returns the timestamp at which all reads are made.
+    pub fn read_timestamp(&self) -> Option<SystemTime> {
+        self.transaction.read_timestamp()
+    }
+
+    /// Divides a query into partitions that read as of this batch's timestamp, see [`Client::partition_query`].
+    pub async fn partition_query(
+        &self,
+        statement: impl Into<Statement>,
+    ) -> Result<Vec<QueryPartition>, Error> {
+        let statement = statement.into();
+        if self.client.read_only {
+            check_read_only(statement.sql())?;
         }
+        let tokens = self
+            .client
+            .connection
+            .get()
+            .partition_query(&self.session, &self.transaction, &statement)
+            .await?;
+        Ok(tokens
+            .into_iter()
+            .map(|token| QueryPartition {
+                session: self.session.clone(),
+                transaction: self.transaction.clone(),
+                statement: statement.clone(),
+                token,
+            })
+            .collect())
+    }
+
+    /// Partitions a query and executes its partitions, at most `concurrency` at a time, as a single stream of rows.
+    ///
+    /// Rows are returned as soon as their partition completes, in no particular order.
+    /// Nothing is sent to Cloud Spanner until the stream is polled, see [`Client::execute_partitions_unordered`].
+    pub fn export(
+        &self,
+        statement: impl Into<Statement>,
+        concurrency: usize,
+    ) -> impl Stream<Item = Result<OwnedRow, Error>> + '_ {
+        let statement = statement.into();
+        stream::once(async move { self.partition_query(statement).await })
+            .map_ok(move |partitions| {
+                self.client
+                    .execute_partitions_unordered(partitions, concurrency)
+            })
+            .try_flatten()
+    }
+
+    /// Deletes the batch's session, which invalidates its partitions.
+    pub async fn close(self) -> Result<(), Error> {
+        self.client
+            .connection
+            .get()
+            .delete_session(self.session)
+            .await
     }
 }
 
 /// Defines the interface to read data out of Cloud Spanner.
+///
+/// Contexts are [`Send`], as are the futures returned by their functions, such that they can be moved into spawned tasks.
 #[async_trait::async_trait]
-pub trait ReadContext {
+pub trait ReadContext: Send {
     /// Execute a read-only SQL statement and returns a [ResultSet].
     ///
     /// # Parameters
@@ -78,6 +655,8 @@ pub trait ReadContext {
     ///
     /// If the parameter values do not line up with parameters in the statement, an [Error] is returned.
     ///
+    /// Parameters collected in a map can be provided using [`ReadContextExt::execute_query_with_parameters`].
+    ///
     /// # Example
     ///
     ///  ```no_run
@@ -101,12 +680,180 @@ pub trait ReadContext {
         statement: &str,
         parameters: &[(&str, &(dyn ToSpanner + Sync))],
     ) -> Result<ResultSet, Error>;
+
+    /// Execute a read-only [`Statement`] and returns a [ResultSet].
+    ///
+    /// This is equivalent to [`ReadContext::execute_query`], but allows reusing statements and their parameters.
+    ///
+    /// # Example
+    ///
+    ///  ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext, Statement};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let statement = Statement::new("SELECT id FROM person WHERE id > @my_id").bind("my_id", 42);
+    /// let rs = client.read_only().execute_statement(&statement).await?;
+    /// for row in rs.iter() {
+    ///     let id: u32 = row.get("id")?;
+    ///     println!("id: {}", id);
+    /// }
+    /// # Ok(()) }
+    ///  ```
+    async fn execute_statement(&mut self, statement: &Statement) -> Result<ResultSet, Error>;
+}
+
+/// Convenience methods to read a single value, implemented for every [`ReadContext`] (including transactions).
+pub trait ReadContextExt: ReadContext {
+    /// Executes a query that returns exactly one row of exactly one column and decodes its value.
+    ///
+    /// An error is returned if the query returns no row, more than one row or more than one column.
+    ///
+    /// # Example
+    ///
+    ///  ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContextExt};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let people: i64 = client
+    ///     .read_only()
+    ///     .execute_query_one("SELECT COUNT(*) FROM person", &[])
+    ///     .await?;
+    /// # Ok(()) }
+    ///  ```
+    fn execute_query_one<'a, T>(
+        &'a mut self,
+        statement: &'a str,
+        parameters: &'a [(&'a str, &'a (dyn ToSpanner + Sync))],
+    ) -> Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>
+    where
+        T: for<'r> FromSpanner<'r> + Send + 'a,
+    {
+        let query = self.execute_query(statement, parameters);
+        Box::pin(async move {
+            single_value(query.await?)?
+                .ok_or_else(|| Error::Client("expected exactly one row, got none".to_string()))
+        })
+    }
+
+    /// Executes a query that returns at most one row of exactly one column and decodes its value, e.g.: to look up a row by key.
+    ///
+    /// `None` is returned when the query returns no row.
+    /// An error is returned if the query returns more than one row or more than one column.
+    ///
+    /// # Example
+    ///
+    ///  ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContextExt};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let name: Option<String> = client
+    ///     .read_only()
+    ///     .execute_query_opt("SELECT name FROM person WHERE id = @id", &[("id", &42)])
+    ///     .await?;
+    /// # Ok(()) }
+    ///  ```
+    fn execute_query_opt<'a, T>(
+        &'a mut self,
+        statement: &'a str,
+        parameters: &'a [(&'a str, &'a (dyn ToSpanner + Sync))],
+    ) -> Pin<Box<dyn Future<Output = Result<Option<T>, Error>> + Send + 'a>>
+    where
+        T: for<'r> FromSpanner<'r> + Send + 'a,
+    {
+        let query = self.execute_query(statement, parameters);
+        Box::pin(async move { single_value(query.await?) })
+    }
+
+    /// Executes a read-only SQL statement whose parameters are provided as a map (or slice), see [`IntoParameters`],
+    /// and returns a [`ResultSet`].
+    ///
+    /// # Example
+    ///
+    ///  ```no_run
+    /// # use std::collections::HashMap;
+    /// # use spanner_rs::{Client, Error, ReadContextExt, Value};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let mut params = HashMap::new();
+    /// params.insert("name".to_string(), Value::String("ferris".to_string()));
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query_with_parameters("SELECT id FROM person WHERE name = @name", params)
+    ///     .await?;
+    /// # Ok(()) }
+    ///  ```
+    fn execute_query_with_parameters<'a, P>(
+        &'a mut self,
+        statement: &str,
+        parameters: P,
+    ) -> Pin<Box<dyn Future<Output = Result<ResultSet, Error>> + Send + 'a>>
+    where
+        P: IntoParameters,
+    {
+        let statement = Statement::with_parameters(statement, parameters);
+        Box::pin(async move { self.execute_statement(&statement).await })
+    }
+
+    /// Executes a [`Statement`] that returns exactly one row of exactly one column and decodes its value,
+    /// see [`ReadContextExt::execute_query_one`].
+    fn execute_statement_one<'a, T>(
+        &'a mut self,
+        statement: &'a Statement,
+    ) -> Pin<Box<dyn Future<Output = Result<T, Error>> + Send + 'a>>
+    where
+        T: for<'r> FromSpanner<'r> + Send + 'a,
+    {
+        let query = self.execute_statement(statement);
+        Box::pin(async move {
+            single_value(query.await?)?
+                .ok_or_else(|| Error::Client("expected exactly one row, got none".to_string()))
+        })
+    }
+
+    /// Executes a [`Statement`] that returns at most one row of exactly one column and decodes its value,
+    /// see [`ReadContextExt::execute_query_opt`].
+    fn execute_statement_opt<'a, T>(
+        &'a mut self,
+        statement: &'a Statement,
+    ) -> Pin<Box<dyn Future<Output = Result<Option<T>, Error>> + Send + 'a>>
+    where
+        T: for<'r> FromSpanner<'r> + Send + 'a,
+    {
+        let query = self.execute_statement(statement);
+        Box::pin(async move { single_value(query.await?) })
+    }
+}
+
+impl<C> ReadContextExt for C where C: ReadContext + ?Sized {}
+
+// Decodes the only column of the only row of a result set, if any.
+fn single_value<T>(result_set: ResultSet) -> Result<Option<T>, Error>
+where
+    T: for<'r> FromSpanner<'r>,
+{
+    let columns = result_set.row_type().fields().len();
+    if columns != 1 {
+        return Err(Error::Client(format!(
+            "expected exactly one column, got {}",
+            columns
+        )));
+    }
+    match result_set.opt()? {
+        Some(row) => row.get(0).map(Some),
+        None => Ok(None),
+    }
 }
 
 struct ReadOnly {
     connection: Box<dyn Connection>,
     bound: Option<TimestampBound>,
-    session_pool: Pool<SessionManager>,
+    session_pool: SessionPool,
+    read_only: bool,
+    query_cache: Option<QueryCache>,
 }
 
 #[async_trait::async_trait]
@@ -116,19 +863,52 @@ impl ReadContext for ReadOnly {
         statement: &str,
         parameters: &[(&str, &(dyn ToSpanner + Sync))],
     ) -> Result<ResultSet, Error> {
-        let session = self.session_pool.get().await?;
-        let result = self
-            .connection
-            .execute_sql(
-                &session,
-                &TransactionSelector::SingleUse(self.bound.clone()),
-                statement,
-                parameters,
-                None,
-            )
-            .await?;
+        self.execute_statement(&Statement::from_parameters(statement, parameters))
+            .await
+    }
 
-        Ok(result)
+    async fn execute_statement(&mut self, statement: &Statement) -> Result<ResultSet, Error> {
+        if self.read_only {
+            check_read_only(statement.sql())?;
+        }
+        if let (Some(cache), Some(bound)) = (&self.query_cache, &self.bound) {
+            if let Some(result_set) = cache.get(statement, bound) {
+                return Ok(result_set);
+            }
+        }
+        let mut session = self.session_pool.get().await?;
+        let selector = TransactionSelector::SingleUse(self.bound.clone());
+        let mut backed_off = false;
+        let mut renewed = false;
+        loop {
+            let result = self
+                .connection
+                .execute_sql(&session, &selector, statement, None, None)
+                .await;
+
+            match result {
+                // Single-use reads are idempotent, so we honour the server's hint and try again once.
+                Err(Error::QuotaExceeded {
+                    retry_after: Some(delay),
+                    ..
+                }) if !backed_off => {
+                    backed_off = true;
+                    tokio::time::sleep(delay).await;
+                }
+                // The session was deleted by Cloud Spanner, replace it and try again once.
+                Err(Error::SessionNotFound(_)) if !renewed => {
+                    renewed = true;
+                    session.replace(self.connection.create_session().await?);
+                }
+                Ok(result_set) => {
+                    if let (Some(cache), Some(bound)) = (&self.query_cache, &self.bound) {
+                        cache.put(statement, bound, &result_set);
+                    }
+                    return Ok(result_set);
+                }
+                result => return result,
+            }
+        }
     }
 }
 
@@ -142,6 +922,7 @@ pub trait TransactionContext: ReadContext {
     /// # Parameters
     ///
     /// Like its [`ReadContext::execute_sql`] counterpart, this function also supports query parameters.
+    /// Parameters collected in a map can be provided using [`TransactionContextExt::execute_update_with_parameters`].
     ///
     /// # Example
     ///
@@ -174,54 +955,234 @@ pub trait TransactionContext: ReadContext {
         parameters: &[(&str, &(dyn ToSpanner + Sync))],
     ) -> Result<i64, Error>;
 
-    /// Execute a batch of DML SQL statements and returns the number of affected rows for each statement.
-    ///
-    /// # Statements
+    /// Execute a DML [`Statement`] and returns the number of affected rows.
+    ///
+    /// This is equivalent to [`TransactionContext::execute_update`], but allows reusing statements and their parameters.
+    async fn execute_update_statement(&mut self, statement: &Statement) -> Result<i64, Error>;
+
+    /// Execute a batch of DML SQL statements and returns the number of affected rows for each statement.
+    ///
+    /// # Statements
+    ///
+    /// Each DML statement has its own SQL statement and parameters. See [`Statement`] for more details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, Statement, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let id = 42;
+    /// let name = "ferris";
+    /// let new_name = "ferris";
+    /// let rows = client
+    ///     .read_write()
+    ///     .run(|tx| {
+    ///         Box::pin(async move {
+    ///             tx.execute_updates(&[
+    ///                 &Statement::new("INSERT INTO person(id, name) VALUES (@id, @name)")
+    ///                     .bind("id", id)
+    ///                     .bind("name", name),
+    ///                 &Statement::new("UPDATE person SET name = @name WHERE id = 42")
+    ///                     .bind("name", new_name),
+    ///             ])
+    ///             .await
+    ///         })
+    ///     })
+    ///     .await?;
+    ///
+    /// // each statement modified a single row
+    /// assert_eq!(rows, vec![1, 1]);
+    ///
+    /// # Ok(()) }
+    /// ```
+    async fn execute_updates(&mut self, statements: &[&Statement]) -> Result<Vec<i64>, Error>;
+
+    /// Buffers mutations that are sent to Cloud Spanner when the transaction commits.
+    ///
+    /// Mutations are applied atomically with the transaction's DML statements, after them.
+    /// As such, they are not visible to the transaction's reads.
+    /// A transaction that only buffers mutations is committed in a single round trip.
+    ///
+    /// The buffer is discarded when the transaction rolls back or is retried.
+    ///
+    /// The commit fails with an [`Error::Client`] naming the largest table and column, without reaching Cloud Spanner,
+    /// when the [estimated](Mutation::mutation_count) number of mutations exceeds [`crate::MAX_COMMIT_MUTATIONS`]
+    /// or when the mutations exceed the 100 MB Cloud Spanner allows per commit.
+    /// It also fails when a key exceeds 8 KiB, for the mutations whose [key columns](Mutation::with_key_columns) are known.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, KeySet, Mutation, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// client
+    ///     .read_write()
+    ///     .run(|tx| {
+    ///         Box::pin(async move {
+    ///             tx.execute_update("UPDATE person SET name = 'ferris' WHERE id = 42", &[])
+    ///                 .await?;
+    ///             tx.buffer_write(vec![
+    ///                 Mutation::insert("person").set("id", 43).set("name", "corro"),
+    ///                 Mutation::delete("person", KeySet::new().key(&[&44])),
+    ///             ]);
+    ///             Ok(())
+    ///         })
+    ///     })
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    fn buffer_write(&mut self, mutations: Vec<Mutation>);
+
+    /// Buffers the insertion of a row made of the columns of a [`ToRow`], e.g.: a struct deriving it.
+    ///
+    /// This is equivalent to buffering a [`Mutation::insert`] whose columns are set using [`Mutation::set_row`],
+    /// see [`TransactionContext::buffer_write`]. Any conversion error is returned when the transaction commits.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ToRow, ToSpanner, TransactionContext, Value};
+    /// # struct Person { id: i64, name: String }
+    /// # impl ToRow for Person {
+    /// #     fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error> {
+    /// #         Ok(vec![("id", self.id.to_spanner()?), ("name", self.name.to_spanner()?)])
+    /// #     }
+    /// # }
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let person = Person { id: 42, name: "ferris".to_string() };
+    /// client
+    ///     .read_write()
+    ///     .run(|tx| {
+    ///         tx.insert("person", &person);
+    ///         Box::pin(async { Ok(()) })
+    ///     })
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    fn insert(&mut self, table: &str, row: &dyn ToRow) {
+        self.buffer_write(vec![Mutation::insert(table).set_row(row)]);
+    }
+
+    /// Buffers the update of a row using the columns of a [`ToRow`], e.g.: a struct deriving it.
     ///
-    /// Each DML statement has its own SQL statement and parameters. See [`Statement`] for more details.
+    /// This is equivalent to buffering a [`Mutation::update`], see [`TransactionContext::insert`].
+    fn update(&mut self, table: &str, row: &dyn ToRow) {
+        self.buffer_write(vec![Mutation::update(table).set_row(row)]);
+    }
+}
+
+/// Convenience methods to write using parameters collected in maps, implemented for every [`TransactionContext`].
+pub trait TransactionContextExt: TransactionContext {
+    /// Executes a DML SQL statement whose parameters are provided as a map (or slice), see [`IntoParameters`],
+    /// and returns the number of affected rows.
     ///
     /// # Example
     ///
     /// ```no_run
-    /// # use spanner_rs::{Client, Error, Statement, TransactionContext};
+    /// # use std::collections::BTreeMap;
+    /// # use spanner_rs::{Client, Error, ToSpanner, TransactionContextExt};
     /// # #[tokio::main]
     /// # async fn main() -> Result<(), Error> {
     /// # let mut client = Client::configure().connect().await?;
-    /// let id = 42;
-    /// let name = "ferris";
-    /// let new_name = "ferris";
-    /// let rows = client
+    /// let mut params = BTreeMap::<&str, &dyn ToSpanner>::new();
+    /// params.insert("id", &42);
+    /// params.insert("name", &"ferris");
+    /// client
     ///     .read_write()
     ///     .run(|tx| {
-    ///         Box::pin(async move {
-    ///             tx.execute_updates(&[
-    ///                 &Statement {
-    ///                     sql: "INSERT INTO person(id, name) VALUES (@id, @name)",
-    ///                     params: &[("id", &id), ("name", &name)],
-    ///                 },
-    ///                 &Statement {
-    ///                     sql: "UPDATE person SET name = @name WHERE id = 42",
-    ///                     params: &[("name", &new_name)],
-    ///                 },
-    ///             ])
-    ///             .await
-    ///         })
+    ///         tx.execute_update_with_parameters(
+    ///             "INSERT INTO person(id, name) VALUES (@id, @name)",
+    ///             &params,
+    ///         )
     ///     })
     ///     .await?;
-    ///
-    /// // each statement modified a single row
-    /// assert_eq!(rows, vec![1, 1]);
-    ///
     /// # Ok(()) }
     /// ```
-    async fn execute_updates(&mut self, statements: &[&Statement]) -> Result<Vec<i64>, Error>;
+    fn execute_update_with_parameters<'a, P>(
+        &'a mut self,
+        statement: &str,
+        parameters: P,
+    ) -> Pin<Box<dyn Future<Output = Result<i64, Error>> + Send + 'a>>
+    where
+        P: IntoParameters,
+    {
+        let statement = Statement::with_parameters(statement, parameters);
+        Box::pin(async move { self.execute_update_statement(&statement).await })
+    }
 }
 
+impl<C> TransactionContextExt for C where C: TransactionContext + ?Sized {}
+
 struct Tx<'a> {
     connection: Box<dyn Connection>,
-    session: PooledConnection<'a, SessionManager>,
+    session: SessionGuard<'a>,
     selector: TransactionSelector,
     seqno: i64,
+    mutations: Vec<Mutation>,
+    options: TxOptions,
+    #[cfg(feature = "replay-log")]
+    log: crate::ReplayLog,
+}
+
+impl<'a> Tx<'a> {
+    /// Prepares this context for a new attempt.
+    fn reset(&mut self) {
+        self.selector = TransactionSelector::Begin;
+        self.seqno = 0;
+        self.mutations.clear();
+        #[cfg(feature = "replay-log")]
+        self.log.next_attempt();
+    }
+
+    /// Commits the transaction, or rolls it back, and resets this context such that it can be used for another attempt.
+    async fn end(&mut self, commit: bool) -> Result<Option<CommitResponse>, Error> {
+        let selector = std::mem::replace(&mut self.selector, TransactionSelector::Begin);
+        let mutations = std::mem::take(&mut self.mutations);
+        if commit {
+            if let Err(err) = check_commit(&mutations) {
+                // Rejected before committing, the transaction is released rather than left for Cloud Spanner to expire.
+                if let TransactionSelector::Id(tx) = selector {
+                    let _ = self.connection.rollback(&self.session, tx).await;
+                }
+                return Err(err);
+            }
+        }
+        match selector {
+            TransactionSelector::Id(tx) if commit => self
+                .connection
+                .commit(&self.session, Some(tx), &mutations, &self.options)
+                .await
+                .map(Some),
+            TransactionSelector::Id(tx) => self
+                .connection
+                .rollback(&self.session, tx)
+                .await
+                .map(|_| None),
+            // Only mutations were buffered: apply them without beginning a transaction.
+            _ if commit && !mutations.is_empty() => self
+                .connection
+                .commit(&self.session, None, &mutations, &self.options)
+                .await
+                .map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    #[cfg(feature = "replay-log")]
+    fn attach_log(&mut self, error: Error) -> Error {
+        std::mem::take(&mut self.log).attach(error)
+    }
+
+    #[cfg(not(feature = "replay-log"))]
+    fn attach_log(&mut self, error: Error) -> Error {
+        error
+    }
 }
 
 #[async_trait::async_trait]
@@ -231,16 +1192,23 @@ impl<'a> ReadContext for Tx<'a> {
         statement: &str,
         parameters: &[(&str, &(dyn ToSpanner + Sync))],
     ) -> Result<ResultSet, Error> {
+        self.execute_statement(&Statement::from_parameters(statement, parameters))
+            .await
+    }
+
+    async fn execute_statement(&mut self, statement: &Statement) -> Result<ResultSet, Error> {
         // seqno is required on DML queries and ignored otherwise. Specifying it on every query is fine.
         self.seqno += 1;
+        #[cfg(feature = "replay-log")]
+        self.log.record(statement);
         let result_set = self
             .connection
             .execute_sql(
                 &self.session,
                 &self.selector,
                 statement,
-                parameters,
                 Some(self.seqno),
+                Some(&self.options),
             )
             .await?;
 
@@ -262,17 +1230,33 @@ impl<'a> TransactionContext for Tx<'a> {
         statement: &str,
         parameters: &[(&str, &(dyn ToSpanner + Sync))],
     ) -> Result<i64, Error> {
-        self.execute_query(statement, parameters).await?
+        self.execute_update_statement(&Statement::from_parameters(statement, parameters))
+            .await
+    }
+
+    async fn execute_update_statement(&mut self, statement: &Statement) -> Result<i64, Error> {
+        self.execute_statement(statement).await?
             .stats
             .row_count
+            .map(|row_count| row_count.value())
             .ok_or_else(|| Error::Client("no row count available. This may be the result of using execute_update on a statement that did not contain DML.".to_string()))
     }
 
     async fn execute_updates(&mut self, statements: &[&Statement]) -> Result<Vec<i64>, Error> {
         self.seqno += 1;
+        #[cfg(feature = "replay-log")]
+        for statement in statements {
+            self.log.record(statement);
+        }
         let result_sets = self
             .connection
-            .execute_batch_dml(&self.session, &self.selector, statements, self.seqno)
+            .execute_batch_dml(
+                &self.session,
+                &self.selector,
+                statements,
+                self.seqno,
+                &self.options,
+            )
             .await?;
 
         // TODO: this is brittle, if we forget to do this in some other method, then we risk not committing.
@@ -286,19 +1270,53 @@ impl<'a> TransactionContext for Tx<'a> {
             .map(|rs| {
                 rs.stats
                 .row_count
+                .map(|row_count| row_count.value())
                 .ok_or_else(|| Error::Client("no row count available. This may be the result of using execute_update on a statement that did not contain DML.".to_string()))
             })
             .collect()
     }
+
+    fn buffer_write(&mut self, mutations: Vec<Mutation>) {
+        self.mutations.extend(mutations);
+    }
 }
 
 /// Allows running read/write transactions against Cloud Spanner.
+///
+/// A `TxRunner` is [`Send`] and [`Sync`], see [`TxRunner::run`] for the requirements on the futures it runs.
 pub struct TxRunner {
-    connection: Box<dyn Connection>,
-    session_pool: Pool<SessionManager>,
+    connection: SharedConnection,
+    session_pool: SessionPool,
+    read_only: bool,
+    retry_policy: RetryPolicy,
+    retry_observer: Option<RetryObserver>,
 }
 
 impl TxRunner {
+    /// Overrides the client's [`RetryPolicy`] for transactions run using this runner.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, RetryPolicy, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// client
+    ///     .read_write()
+    ///     .with_retry_policy(RetryPolicy::builder().max_attempts(1).build()?)
+    ///     .run(|tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn with_retry_policy(self, retry_policy: RetryPolicy) -> Self {
+        Self {
+            retry_policy,
+            ..self
+        }
+    }
+
     /// Runs abitrary read / write operations against Cloud Spanner.
     ///
     /// This function encapsulates the read/write transaction management concerns, allowing the application to minimize boilerplate.
@@ -306,7 +1324,7 @@ impl TxRunner {
     /// # Begin
     ///
     /// The underlying transaction is only lazily created. If the provided closure does no work against Cloud Spanner,
-    /// then no transaction is created.
+    /// then no transaction is created. When it only buffers mutations, they are committed without beginning a transaction.
     ///
     /// # Commit / Rollback
     ///
@@ -320,9 +1338,32 @@ impl TxRunner {
     /// When committing, Cloud Spanner may reject the transaction due to conflicts with another transaction.
     /// In these situations, Cloud Spanner allows retrying the transaction which will have a higher priority and potentially successfully commit.
     ///
+    /// Retries are governed by the [`RetryPolicy`] which determines the delay between attempts and how many attempts are made.
+    /// When Cloud Spanner recommends a delay before retrying, it is used instead of the policy's backoff.
+    /// When no more attempts are allowed, the `ABORTED` status is returned.
+    /// Each retry is reported to the callback registered using [`ConfigBuilder::on_retry`], if any.
+    ///
+    /// Similarly, when the session used by the transaction no longer exists (e.g.: it was deleted by Cloud Spanner after being idle for too long),
+    /// it is replaced by a new session and the transaction is retried once.
+    ///
     /// **NOTE:** the consequence of retyring is that the provided closure may be invoked multiple times.
     /// It is important to avoid doing any additional side effects within this closure as they will also potentially occur more than once.
     ///
+    /// # Replay log
+    ///
+    /// When the `replay-log` feature is enabled, returned errors are wrapped in an `Error::WithReplayLog`
+    /// which records the statements (but not the parameter values) executed by each attempt.
+    ///
+    /// # Read-only clients
+    ///
+    /// This function returns an [`Error::Client`] without invoking the closure when the client is [read-only](crate::ConfigBuilder::read_only).
+    ///
+    /// # Send
+    ///
+    /// The future returned by the closure must be [`Send`], such that the future returned by this function is also [`Send`]
+    /// (provided the closure and its output are) and can be used within `tokio::spawn`.
+    /// Use [`TxRunner::run_local`] when the closure's future cannot be [`Send`].
+    ///
     /// # Example
     ///
     /// ```no_run
@@ -339,7 +1380,7 @@ impl TxRunner {
     ///                         &[("id", &id)],
     ///                     )
     ///                     .await?;
-    ///                 let latest_version: u32 = rs.iter().next().unwrap().get(0)?;
+    ///                 let latest_version: u32 = rs.one()?.get(0)?;
     ///                 let next_version = latest_version + 1;
     ///                 tx.execute_update(
     ///                     "INSERT INTO versions(id, version) VALUES(@id, @next_version)",
@@ -356,40 +1397,410 @@ impl TxRunner {
     /// # bump_version(42).await?;
     /// # Ok(()) }
     /// ```
-    pub async fn run<'b, O, F>(&'b mut self, mut work: F) -> Result<O, Error>
+    pub async fn run<'b, O, F>(&'b mut self, work: F) -> Result<O, Error>
+    where
+        F: for<'a> FnMut(
+            &'a mut dyn TransactionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + Send + 'a>>,
+    {
+        self.run_with_outcome(work).await.map(TxOutcome::into_value)
+    }
+
+    /// Runs abitrary read / write operations against Cloud Spanner and returns the transaction's [`TxOutcome`].
+    ///
+    /// This behaves exactly like [`TxRunner::run`], but the outcome also provides the transaction's commit timestamp,
+    /// which allows reading the data exactly as it was written, e.g.: to run follow-up verification queries.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let outcome = client
+    ///     .read_write()
+    ///     .run_with_outcome(|tx| tx.execute_update("UPDATE person SET name = 'ferris' WHERE id = 42", &[]))
+    ///     .await?;
+    ///
+    /// let rs = outcome
+    ///     .snapshot_reader()
+    ///     .execute_query("SELECT name FROM person WHERE id = 42", &[])
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn run_with_outcome<'b, O, F>(&'b mut self, work: F) -> Result<TxOutcome<O>, Error>
+    where
+        F: for<'a> FnMut(
+            &'a mut dyn TransactionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + Send + 'a>>,
+    {
+        self.run_attempts(TxOptions::default(), work).await
+    }
+
+    /// Runs abitrary read / write operations against Cloud Spanner using the provided transaction settings.
+    ///
+    /// This behaves exactly like [`TxRunner::run_with_outcome`], but every request of the transaction carries the provided [`TxOptions`],
+    /// e.g.: its tag and priority.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, Priority, TransactionContext, TxOptions};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let options = TxOptions::builder()
+    ///     .tag("cleanup")
+    ///     .priority(Priority::Low)
+    ///     .return_commit_stats(true)
+    ///     .build()?;
+    /// let outcome = client
+    ///     .read_write()
+    ///     .run_with_options(options, |tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+    ///     .await?;
+    ///
+    /// if let Some(stats) = outcome.commit_response().and_then(|response| response.commit_stats()) {
+    ///     println!("deleted {} rows using {} mutations", outcome.value(), stats.mutation_count());
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub async fn run_with_options<'b, O, F>(
+        &'b mut self,
+        options: TxOptions,
+        work: F,
+    ) -> Result<TxOutcome<O>, Error>
+    where
+        F: for<'a> FnMut(
+            &'a mut dyn TransactionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + Send + 'a>>,
+    {
+        self.run_attempts(options, work).await
+    }
+
+    /// Runs abitrary read / write operations against Cloud Spanner, like [`TxRunner::run`], but does not require the closure's future to be [`Send`].
+    ///
+    /// This is an escape hatch for futures that hold on to values that cannot be sent between threads across `.await` points, e.g.: an `Rc`.
+    /// The returned future is then not [`Send`] either, so it cannot be used within `tokio::spawn`, but it can be within e.g.: a [`tokio::task::LocalSet`].
+    /// Prefer [`TxRunner::run`] otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use std::rc::Rc;
+    /// # use spanner_rs::{Client, Error, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let name = Rc::new("ferris".to_string());
+    /// client
+    ///     .read_write()
+    ///     .run_local(|tx| {
+    ///         let name = name.clone();
+    ///         Box::pin(async move {
+    ///             tx.execute_update("UPDATE person SET name = @name WHERE id = 42", &[("name", &name.as_str())])
+    ///                 .await?;
+    ///             tx.execute_update("UPDATE person SET name = @name WHERE id = 43", &[("name", &name.as_str())])
+    ///                 .await
+    ///         })
+    ///     })
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn run_local<'b, O, F>(&'b mut self, work: F) -> Result<O, Error>
     where
         F: for<'a> FnMut(
             &'a mut dyn TransactionContext,
         ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + 'a>>,
     {
-        let session = self.session_pool.get().await?;
-        let mut ctx = Tx {
-            connection: self.connection.clone(),
-            session,
-            selector: TransactionSelector::Begin,
-            seqno: 0,
-        };
+        self.run_local_with_options(TxOptions::default(), work)
+            .await
+            .map(TxOutcome::into_value)
+    }
+
+    /// Runs abitrary read / write operations against Cloud Spanner using the provided transaction settings,
+    /// without requiring the closure's future to be [`Send`].
+    ///
+    /// See [`TxRunner::run_local`] and [`TxRunner::run_with_options`].
+    pub async fn run_local_with_options<'b, O, F>(
+        &'b mut self,
+        options: TxOptions,
+        mut work: F,
+    ) -> Result<TxOutcome<O>, Error>
+    where
+        F: for<'a> FnMut(
+            &'a mut dyn TransactionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + 'a>>,
+    {
+        let mut ctx = self.tx(options).await?;
+
+        let mut attempts = 0;
+        let mut renewed = false;
+        loop {
+            attempts += 1;
+            ctx.reset();
+            let result = work(&mut ctx).await;
+            if let Some(outcome) = self
+                .end_attempt(&mut ctx, result, attempts, &mut renewed)
+                .await
+            {
+                return outcome;
+            }
+        }
+    }
 
+    // Identical to `run_local_with_options`, except that the closure's future is `Send`, and so is the one returned by this function.
+    async fn run_attempts<'b, O, F>(
+        &'b mut self,
+        options: TxOptions,
+        mut work: F,
+    ) -> Result<TxOutcome<O>, Error>
+    where
+        F: for<'a> FnMut(
+            &'a mut dyn TransactionContext,
+        ) -> Pin<Box<dyn Future<Output = Result<O, Error>> + Send + 'a>>,
+    {
+        let mut ctx = self.tx(options).await?;
+
+        let mut attempts = 0;
+        let mut renewed = false;
         loop {
-            ctx.selector = TransactionSelector::Begin;
-            ctx.seqno = 0;
+            attempts += 1;
+            ctx.reset();
             let result = work(&mut ctx).await;
+            if let Some(outcome) = self
+                .end_attempt(&mut ctx, result, attempts, &mut renewed)
+                .await
+            {
+                return outcome;
+            }
+        }
+    }
 
-            let commit_result = if let TransactionSelector::Id(tx) = ctx.selector {
-                if result.is_ok() {
-                    self.connection.commit(&ctx.session, tx).await
-                } else {
-                    self.connection.rollback(&ctx.session, tx).await
+    /// Commits or rolls back an attempt's transaction, returns `None` when the transaction should be attempted again.
+    async fn end_attempt<O>(
+        &self,
+        ctx: &mut Tx<'_>,
+        result: Result<O, Error>,
+        attempts: u32,
+        renewed: &mut bool,
+    ) -> Option<Result<TxOutcome<O>, Error>> {
+        let commit_result = ctx.end(result.is_ok()).await;
+
+        // The session was deleted by Cloud Spanner, so the transaction did not commit: replace the session and try again once.
+        let session_not_found = matches!(result, Err(Error::SessionNotFound(_)))
+            || matches!(commit_result, Err(Error::SessionNotFound(_)));
+        if session_not_found && !*renewed {
+            *renewed = true;
+            return match ctx.connection.create_session().await {
+                Ok(session) => {
+                    ctx.session.replace(session);
+                    None
                 }
-            } else {
-                Ok(())
+                Err(err) => Some(Err(err)),
             };
+        }
 
-            match commit_result {
-                Err(Error::Status(status)) if status.code() == Code::Aborted => continue,
-                Err(err) => break Err(err),
-                _ => break result,
+        let result = match commit_result {
+            Err(Error::Aborted(status)) if self.retry_policy.should_retry(attempts) => {
+                let delay = self.retry_policy.delay(&status, attempts);
+                if let Some(observer) = &self.retry_observer {
+                    observer.notify(attempts, delay, &status);
+                }
+                tokio::time::sleep(delay).await;
+                return None;
             }
+            Err(err) => Err(err),
+            Ok(commit_response) => result.map(|value| TxOutcome {
+                value,
+                commit_response,
+                connection: self.connection.get(),
+                session_pool: self.session_pool.clone(),
+            }),
+        };
+        Some(result.map_err(|err| ctx.attach_log(err)))
+    }
+
+    async fn tx(&self, options: TxOptions) -> Result<Tx<'_>, Error> {
+        if self.read_only {
+            return Err(Error::Client(
+                "read/write transactions are not allowed on a read-only client".to_string(),
+            ));
+        }
+        Ok(Tx {
+            connection: self.connection.get(),
+            session: self.session_pool.get().await?,
+            selector: TransactionSelector::Begin,
+            seqno: 0,
+            mutations: Vec::new(),
+            options,
+            #[cfg(feature = "replay-log")]
+            log: crate::ReplayLog::default(),
+        })
+    }
+
+    /// Begins a read/write transaction that is committed or rolled back explicitly, using [`ReadWriteTransaction::commit`]
+    /// or [`ReadWriteTransaction::rollback`].
+    ///
+    /// This is an escape hatch for applications that cannot express their transaction as a closure,
+    /// e.g.: because they interleave it with other asynchronous resources. Prefer [`TxRunner::run`] otherwise.
+    ///
+    /// Unlike [`TxRunner::run`], aborted transactions are **not** retried: [`Error::Aborted`] is returned instead,
+    /// in which case the application should begin a new transaction and redo its work.
+    /// Similarly, the session is not replaced when Cloud Spanner no longer knows about it.
+    ///
+    /// Like with [`TxRunner::run`], the underlying transaction is only lazily created by its first statement.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let mut runner = client.read_write();
+    /// let mut tx = runner.begin().await?;
+    /// tx.execute_update("UPDATE person SET name = 'ferris' WHERE id = 42", &[])
+    ///     .await?;
+    /// // ... interleave other work ...
+    /// tx.commit().await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn begin(&mut self) -> Result<ReadWriteTransaction<'_>, Error> {
+        self.begin_with_options(TxOptions::default()).await
+    }
+
+    /// Begins a read/write transaction that is committed or rolled back explicitly, using the provided transaction settings.
+    ///
+    /// See [`TxRunner::begin`] and [`TxRunner::run_with_options`].
+    pub async fn begin_with_options(
+        &mut self,
+        options: TxOptions,
+    ) -> Result<ReadWriteTransaction<'_>, Error> {
+        Ok(ReadWriteTransaction {
+            tx: self.tx(options).await?,
+        })
+    }
+}
+
+/// A read/write transaction that is committed or rolled back explicitly, see [`TxRunner::begin`].
+///
+/// Dropping it without committing is equivalent to rolling it back, except that Cloud Spanner only releases
+/// the transaction's locks once it times out.
+pub struct ReadWriteTransaction<'a> {
+    tx: Tx<'a>,
+}
+
+impl<'a> ReadWriteTransaction<'a> {
+    /// Commits the transaction and returns Cloud Spanner's response.
+    ///
+    /// This is `None` when no transaction was committed, i.e.: no work was done against Cloud Spanner and no mutations were buffered.
+    /// An [`Error::Aborted`] is returned when Cloud Spanner aborted the transaction, which is not retried.
+    pub async fn commit(mut self) -> Result<Option<CommitResponse>, Error> {
+        self.tx.end(true).await
+    }
+
+    /// Rolls back the transaction, discarding its writes and buffered mutations.
+    pub async fn rollback(mut self) -> Result<(), Error> {
+        self.tx.end(false).await.map(|_| ())
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> ReadContext for ReadWriteTransaction<'a> {
+    async fn execute_query(
+        &mut self,
+        statement: &str,
+        parameters: &[(&str, &(dyn ToSpanner + Sync))],
+    ) -> Result<ResultSet, Error> {
+        self.tx.execute_query(statement, parameters).await
+    }
+
+    async fn execute_statement(&mut self, statement: &Statement) -> Result<ResultSet, Error> {
+        self.tx.execute_statement(statement).await
+    }
+}
+
+#[async_trait::async_trait]
+impl<'a> TransactionContext for ReadWriteTransaction<'a> {
+    async fn execute_update(
+        &mut self,
+        statement: &str,
+        parameters: &[(&str, &(dyn ToSpanner + Sync))],
+    ) -> Result<i64, Error> {
+        self.tx.execute_update(statement, parameters).await
+    }
+
+    async fn execute_update_statement(&mut self, statement: &Statement) -> Result<i64, Error> {
+        self.tx.execute_update_statement(statement).await
+    }
+
+    async fn execute_updates(&mut self, statements: &[&Statement]) -> Result<Vec<i64>, Error> {
+        self.tx.execute_updates(statements).await
+    }
+
+    fn buffer_write(&mut self, mutations: Vec<Mutation>) {
+        self.tx.buffer_write(mutations)
+    }
+}
+
+/// The outcome of a successful read/write transaction, see [`TxRunner::run_with_outcome`] and [`TxRunner::run_with_options`].
+pub struct TxOutcome<O> {
+    value: O,
+    commit_response: Option<CommitResponse>,
+    connection: Box<dyn Connection>,
+    session_pool: SessionPool,
+}
+
+impl<O> TxOutcome<O> {
+    /// Returns the value returned by the transaction's closure.
+    pub fn value(&self) -> &O {
+        &self.value
+    }
+
+    /// Consumes this outcome and returns the value returned by the transaction's closure.
+    pub fn into_value(self) -> O {
+        self.value
+    }
+
+    /// Returns the timestamp at which the transaction committed.
+    ///
+    /// This is `None` when no transaction was committed, i.e.: the closure did no work against Cloud Spanner
+    /// and buffered no mutations.
+    pub fn commit_timestamp(&self) -> Option<SystemTime> {
+        self.commit_response
+            .as_ref()
+            .map(CommitResponse::commit_timestamp)
+    }
+
+    /// Returns Cloud Spanner's response to the transaction's commit, including its commit statistics when they
+    /// were requested using [`crate::TxOptionsBuilder::return_commit_stats`].
+    ///
+    /// This is `None` when no transaction was committed, see [`TxOutcome::commit_timestamp`].
+    pub fn commit_response(&self) -> Option<&CommitResponse> {
+        self.commit_response.as_ref()
+    }
+
+    /// Returns a [`ReadContext`] that reads data as of the transaction's commit timestamp,
+    /// i.e.: its reads observe exactly what was written by the transaction, but no subsequent write.
+    ///
+    /// When no transaction was committed, the returned context uses [`TimestampBound::Strong`] consistency.
+    pub fn snapshot_reader(&self) -> impl ReadContext {
+        ReadOnly {
+            connection: self.connection.clone(),
+            bound: self.commit_timestamp().map(TimestampBound::ReadTimestamp),
+            session_pool: self.session_pool.clone(),
+            read_only: false,
+            query_cache: None,
         }
     }
 }
+
+impl<O: std::fmt::Debug> std::fmt::Debug for TxOutcome<O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TxOutcome")
+            .field("value", &self.value)
+            .field("commit_response", &self.commit_response)
+            .finish()
+    }
+}
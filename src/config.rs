@@ -1,9 +1,34 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+
 use bb8::{Builder as PoolBuilder, Pool};
-use tonic::transport::ClientTlsConfig;
+use gcp_auth::AuthenticationManager;
+use tonic::service::Interceptor;
+use tonic::transport::{Certificate, Channel, ClientTlsConfig};
+use tower::{BoxError, Layer, Service};
 
-use crate::{Client, DatabaseId, Error, InstanceId, ProjectId, SessionManager};
+use crate::auth::{AuthProvider, TokenProvider};
+use crate::connection::grpc::ChannelOptions;
+use crate::layer::ServiceLayer;
+use crate::{
+    AdaptiveConcurrency, Client, Connection, DatabaseId, Dialect, Error, GrpcRequest, GrpcResponse,
+    GrpcService, InstanceId, ProjectId, QueryCache, RetryEvent, RetryObserver, RetryPolicy,
+    SessionManager, SessionPool, DEFAULT_MAX_SESSION_LIFETIME,
+};
 use derive_builder::Builder;
 
+#[cfg(doc)]
+use crate::StatementKind;
+
+fn parse_option<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, Error> {
+    value
+        .parse()
+        .map_err(|_| Error::Config(format!("invalid value for URI option {}: {}", name, value)))
+}
+
+const DEFAULT_TOKEN_REFRESH_WINDOW: Duration = Duration::from_secs(60);
+
 /// Configuration for building a [`Client`].
 ///
 /// # Example
@@ -28,9 +53,22 @@ pub struct Config {
     endpoint: Option<String>,
 
     /// Set custom client-side TLS settings.
+    ///
+    /// See also [`ConfigBuilder::ca_certificate`] and [`ConfigBuilder::domain_name`] for the most common settings.
     #[builder(setter(strip_option), default = "Some(ClientTlsConfig::default())")]
     tls_config: Option<ClientTlsConfig>,
 
+    /// Trust a PEM-encoded CA certificate bundle, see [`ConfigBuilder::ca_certificate`].
+    #[builder(setter(custom), default)]
+    ca_certificate: Option<Vec<u8>>,
+
+    /// Set the domain name used to verify the server's certificate, e.g.: when reaching the Spanner API through a proxy
+    /// whose certificate is issued for another domain.
+    ///
+    /// This requires TLS and an `https` endpoint, an [`Error::Config`] is returned when connecting otherwise.
+    #[builder(setter(strip_option, into), default)]
+    domain_name: Option<String>,
+
     /// Specify the GCP project where the Cloud Spanner instance exists.
     ///
     /// This may be left unspecified, in which case, the project will be extracted
@@ -52,9 +90,137 @@ pub struct Config {
     #[builder(setter(strip_option, into), default)]
     credentials_file: Option<String>,
 
+    /// Obtain a new access token in the background once the current one expires within this duration, 1 minute by default.
+    ///
+    /// This keeps token rotation off the request path, which would otherwise add the latency of obtaining a token to
+    /// the requests made when the token expires. This has no effect on tokens obtained by the application,
+    /// see [`ConfigBuilder::with_access_token_provider`].
+    #[builder(setter(strip_option), default)]
+    token_refresh_window: Option<Duration>,
+
+    /// Use an existing authentication manager or externally obtained tokens,
+    /// see [`ConfigBuilder::with_auth_manager`] and [`ConfigBuilder::with_access_token`].
+    #[builder(setter(custom), default)]
+    auth: Option<AuthProvider>,
+
     /// Configuration for the embedded session pool.
     #[builder(setter(strip_option), default)]
     session_pool_config: Option<SessionPoolConfig>,
+
+    /// Defer as much work as possible until the first request is made, which minimizes the time spent connecting.
+    ///
+    /// This is mostly useful in serverless environments (e.g.: Cloud Functions, Cloud Run) where cold-start latency matters:
+    ///
+    /// * the gRPC channel is only established when the first request is sent;
+    /// * the session pool is not filled up to its `min_idle` size when connecting;
+    /// * when using the default credentials, the authentication method is only discovered when the first token is required.
+    ///   These credentials are shared by all clients in the same process, which allows reusing cached tokens
+    ///   when a new client is created for each invocation of a function.
+    ///
+    /// Note that the project should also be specified, otherwise credentials must be obtained to determine it.
+    /// As a consequence, configuration and connectivity errors are only reported when the client is first used.
+    #[builder(default)]
+    lazy_connect: bool,
+
+    /// Specify the SQL [`Dialect`] of the database.
+    ///
+    /// This may be left unspecified, in which case, the dialect is detected when the first session is created.
+    /// When detection fails, it is attempted again with later sessions, waiting longer after each failure,
+    /// and GoogleSQL is assumed in the meantime.
+    #[builder(setter(strip_option), default)]
+    dialect: Option<Dialect>,
+
+    /// Reject any statement that may write to the database, which protects services that should only ever read data,
+    /// e.g.: analytics or reporting services connected to a production database.
+    ///
+    /// When enabled, read/write transactions cannot be run and only statements classified as [`StatementKind::Query`]
+    /// are sent to Cloud Spanner, other statements result in an [`Error::Client`].
+    ///
+    /// Note that statements are classified using a heuristic; this is not a substitute for proper IAM permissions.
+    #[builder(default)]
+    read_only: bool,
+
+    /// Configure how aborted read/write transactions are retried. See [`RetryPolicy`] for the defaults.
+    ///
+    /// The policy can also be overridden for individual transactions using [`crate::TxRunner::with_retry_policy`].
+    #[builder(setter(strip_option), default)]
+    retry_policy: Option<RetryPolicy>,
+
+    /// Observe retries of aborted read/write transactions, see [`ConfigBuilder::on_retry`].
+    #[builder(setter(custom), default)]
+    retry_observer: Option<RetryObserver>,
+
+    /// Send HTTP/2 keep-alive pings at the specified interval, including when the channel is idle.
+    ///
+    /// This prevents idle channels from being silently dropped by NATs or load balancers, which would otherwise
+    /// make the first request after a period of inactivity hang.
+    #[builder(setter(strip_option), default)]
+    http2_keep_alive_interval: Option<Duration>,
+
+    /// Specify how long to wait for a keep-alive ping to be acknowledged before closing the channel.
+    ///
+    /// This only has an effect when [`ConfigBuilder::http2_keep_alive_interval`] is set.
+    #[builder(setter(strip_option), default)]
+    keep_alive_timeout: Option<Duration>,
+
+    /// Set the `TCP_NODELAY` option on the channel's socket.
+    #[builder(setter(strip_option), default)]
+    tcp_nodelay: Option<bool>,
+
+    /// Specify how long to wait for the channel to be established.
+    #[builder(setter(strip_option), default)]
+    connect_timeout: Option<Duration>,
+
+    /// Append a suffix to the `user-agent` sent with every request, e.g.: to identify the application in Cloud Spanner's logs.
+    #[builder(setter(strip_option, into), default)]
+    user_agent_suffix: Option<String>,
+
+    /// Static gRPC metadata sent with every request, see [`ConfigBuilder::metadata`].
+    #[builder(setter(custom), default)]
+    metadata: Vec<(String, String)>,
+
+    /// Configure the deadlines of requests sent to Cloud Spanner. See [`RpcTimeouts`] for the defaults.
+    #[builder(setter(strip_option), default)]
+    rpc_timeouts: Option<RpcTimeouts>,
+
+    /// Route requests made by read/write transactions to the leader region, using the `x-goog-spanner-route-to-leader` header.
+    ///
+    /// In multi-region instances, this avoids an extra round-trip between the nearest replica and the leader,
+    /// which reduces the latency of read/write transactions. It has no effect on single-region instances.
+    #[builder(default)]
+    route_to_leader: bool,
+
+    /// Custom layers wrapping the gRPC channel, see [`ConfigBuilder::layer`] and [`ConfigBuilder::interceptor`].
+    #[builder(setter(custom), default)]
+    layers: Vec<ServiceLayer>,
+
+    /// Use an existing channel instead of connecting to the endpoint, see [`ConfigBuilder::with_channel`].
+    #[builder(setter(custom), default)]
+    channel: Option<Channel>,
+
+    /// Cache the results of queries executed using [`Client::read_only_with_bound`], see [`QueryCache`].
+    #[builder(setter(strip_option), default)]
+    query_cache: Option<QueryCache>,
+
+    /// Limit the number of requests in flight, overall or per [`crate::Priority`], see [`ConcurrencyLimit`].
+    ///
+    /// Requests are unlimited by default.
+    #[builder(setter(strip_option), default)]
+    concurrency_limit: Option<ConcurrencyLimit>,
+
+    /// Adapt the number of concurrent commits made by [`Client::write_chunked`] and [`Client::batch_write`] to the commit latency,
+    /// see [`AdaptiveConcurrency`].
+    ///
+    /// When unspecified, chunks are committed one after the other and batch writes are unlimited.
+    #[builder(setter(strip_option), default)]
+    write_backpressure: Option<AdaptiveConcurrency>,
+
+    /// Wrap the errors returned while executing a statement in an [`Error::WithStatement`] which includes the statement's SQL text
+    /// and the names of its parameters, e.g.: to make errors logged in production actionable.
+    ///
+    /// Parameter values are never included, but literals in the SQL text are. Disabled by default.
+    #[builder(default)]
+    statement_error_context: bool,
 }
 
 impl Config {
@@ -63,6 +229,137 @@ impl Config {
         ConfigBuilder::default()
     }
 
+    /// Returns a new [`ConfigBuilder`] initialized from the following environment variables, when they are set:
+    ///
+    /// * `SPANNER_RS_PROJECT_ID` or `GOOGLE_CLOUD_PROJECT`: the GCP project, see [`ConfigBuilder::project`];
+    /// * `SPANNER_RS_INSTANCE`: the Cloud Spanner instance ID, see [`ConfigBuilder::instance`];
+    /// * `SPANNER_RS_DATABASE`: the Cloud Spanner database name, see [`ConfigBuilder::database`];
+    /// * `SPANNER_RS_ENDPOINT`: the URI of the Spanner API, see [`ConfigBuilder::endpoint`];
+    /// * `SPANNER_EMULATOR_HOST`: the `host:port` of a Spanner emulator, see [`ConfigBuilder::with_emulator_host`].
+    ///   This is ignored when `SPANNER_RS_ENDPOINT` is set;
+    /// * `SPANNER_RS_CREDENTIALS_FILE`: the credentials file, see [`ConfigBuilder::credentials_file`].
+    ///
+    /// The returned builder can be further customized, which also allows providing defaults for unset variables.
+    /// Note that an [`Error::Config`] is returned when building if the instance or database is missing.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use spanner_rs::Config;
+    /// #[tokio::main]
+    /// # async fn main() -> Result<(), spanner_rs::Error> {
+    /// let mut client = Config::from_env().connect().await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn from_env() -> ConfigBuilder {
+        Self::from_vars(|name| std::env::var(name).ok())
+    }
+
+    /// Returns a new [`ConfigBuilder`] initialized from a URI of the following form:
+    ///
+    /// `spanner://projects/<project>/instances/<instance>/databases/<database>?<option>=<value>&...`
+    ///
+    /// The `projects/<project>/` part may be omitted, in which case the project is determined from the credentials.
+    /// The following options are supported:
+    ///
+    /// * `endpoint`: the URI of the Spanner API, see [`ConfigBuilder::endpoint`];
+    /// * `emulator_host`: the `host:port` of a Spanner emulator, see [`ConfigBuilder::with_emulator_host`];
+    /// * `credentials_file`: see [`ConfigBuilder::credentials_file`];
+    /// * `min_sessions` and `max_sessions`: the size of the session pool, see [`SessionPoolConfig`];
+    /// * `lazy_connect`, `read_only` and `route_to_leader`: `true` or `false`, see the corresponding [`ConfigBuilder`] options.
+    ///
+    /// Option values are not percent-decoded. An invalid URI, unknown option or invalid value results in an [`Error::Config`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use spanner_rs::Config;
+    /// #[tokio::main]
+    /// # async fn main() -> Result<(), spanner_rs::Error> {
+    /// let mut client =
+    ///     Config::from_uri("spanner://projects/p/instances/i/databases/d?max_sessions=100")?
+    ///         .connect()
+    ///         .await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn from_uri(uri: &str) -> Result<ConfigBuilder, Error> {
+        let invalid = || Error::Config(format!("invalid URI: {}", uri));
+        let (path, query) = uri
+            .strip_prefix("spanner://")
+            .map(|rest| rest.split_once('?').unwrap_or((rest, "")))
+            .ok_or_else(invalid)?;
+
+        let mut builder = Config::builder();
+        let segments = path.trim_end_matches('/').split('/').collect::<Vec<_>>();
+        let segments = match segments.as_slice() {
+            ["projects", project, rest @ ..] if !project.is_empty() => {
+                builder = builder.project(*project);
+                rest
+            }
+            rest => rest,
+        };
+        match segments {
+            ["instances", instance, "databases", database]
+                if !instance.is_empty() && !database.is_empty() =>
+            {
+                builder = builder.instance(*instance).database(*database);
+            }
+            _ => return Err(invalid()),
+        }
+
+        let mut session_pool = SessionPoolConfig::builder();
+        let mut has_session_pool = false;
+        for param in query.split('&').filter(|param| !param.is_empty()) {
+            let (name, value) = param.split_once('=').ok_or_else(invalid)?;
+            builder = match name {
+                "endpoint" => builder.endpoint(value),
+                "emulator_host" => builder.with_emulator_host(format!("http://{}", value)),
+                "credentials_file" => builder.credentials_file(value),
+                "lazy_connect" => builder.lazy_connect(parse_option(name, value)?),
+                "read_only" => builder.read_only(parse_option(name, value)?),
+                "route_to_leader" => builder.route_to_leader(parse_option(name, value)?),
+                "min_sessions" => {
+                    has_session_pool = true;
+                    session_pool = session_pool.min_idle(parse_option(name, value)?);
+                    builder
+                }
+                "max_sessions" => {
+                    has_session_pool = true;
+                    session_pool = session_pool.max_size(parse_option(name, value)?);
+                    builder
+                }
+                _ => return Err(Error::Config(format!("unknown URI option: {}", name))),
+            };
+        }
+        if has_session_pool {
+            builder = builder.session_pool_config(session_pool.build()?);
+        }
+        Ok(builder)
+    }
+
+    fn from_vars(var: impl Fn(&str) -> Option<String>) -> ConfigBuilder {
+        let mut builder = Config::builder();
+        if let Some(project) = var("SPANNER_RS_PROJECT_ID").or_else(|| var("GOOGLE_CLOUD_PROJECT"))
+        {
+            builder = builder.project(project);
+        }
+        if let Some(instance) = var("SPANNER_RS_INSTANCE") {
+            builder = builder.instance(instance);
+        }
+        if let Some(database) = var("SPANNER_RS_DATABASE") {
+            builder = builder.database(database);
+        }
+        if let Some(endpoint) = var("SPANNER_RS_ENDPOINT") {
+            builder = builder.endpoint(endpoint);
+        } else if let Some(host) = var("SPANNER_EMULATOR_HOST") {
+            builder = builder.with_emulator_host(format!("http://{}", host));
+        }
+        if let Some(file) = var("SPANNER_RS_CREDENTIALS_FILE") {
+            builder = builder.credentials_file(file);
+        }
+        builder
+    }
+
     /// Connect to Cloud Spanner and return a new [`Client`].
     ///
     /// # Example
@@ -87,13 +384,139 @@ impl Config {
     /// credentials are normally obtained from the environment (i.e.: `GOOGLE_APPLICATION_CREDENTIALS`).
     ///
     /// Similarly, for local development, authentication will transparently delegate to the `gcloud` command line tool.
-    pub async fn connect(self) -> Result<Client, Error> {
-        let auth = if self.tls_config.is_none() {
+    pub async fn connect(mut self) -> Result<Client, Error> {
+        let session_pool_config = self.session_pool_config.take().unwrap_or_default();
+        let lazy_connect = self.lazy_connect;
+        let read_only = self.read_only;
+        let retry_policy = self.retry_policy.take().unwrap_or_default();
+        let retry_observer = self.retry_observer.take();
+        let query_cache = self.query_cache.take();
+        let write_backpressure = self.write_backpressure.take();
+        let connection = self.open_connection().await?;
+
+        let min_idle = session_pool_config.min_idle.unwrap_or(0);
+        let manager = SessionManager::new(connection.clone())
+            .with_max_session_lifetime(session_pool_config.max_session_lifetime());
+        let session_pool =
+            SessionPool::pooled(session_pool_config.build(), manager, min_idle, lazy_connect)
+                .await?;
+
+        Ok(Client::connect(connection, session_pool)
+            .with_read_only(read_only)
+            .with_retry_policy(retry_policy)
+            .with_retry_observer(retry_observer)
+            .with_query_cache(query_cache)
+            .with_write_backpressure(write_backpressure))
+    }
+
+    /// Connect to Cloud Spanner and return a new [`Client`] that uses exactly one session.
+    ///
+    /// No session pool is maintained in this mode: a single session is created while connecting
+    /// and it is used for every read and transaction, one at a time. Contexts obtained from the returned client
+    /// will wait for the session to be released before doing any work.
+    ///
+    /// This is useful for command line tools, functions-as-a-service or migration scripts where the overhead
+    /// of maintaining a pool of sessions is undesirable. Any [`SessionPoolConfig`] is ignored.
+    ///
+    /// When [`ConfigBuilder::lazy_connect`] is enabled, the session is only created when it is first used.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use spanner_rs::Config;
+    /// #[tokio::main]
+    /// # async fn main() -> Result<(), spanner_rs::Error> {
+    /// let mut client = Config::builder()
+    ///     .project("my-gcp-project")
+    ///     .instance("my-spanner-instance")
+    ///     .database("my-database")
+    ///     .connect_single_session()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn connect_single_session(mut self) -> Result<Client, Error> {
+        let lazy_connect = self.lazy_connect;
+        let read_only = self.read_only;
+        let retry_policy = self.retry_policy.take().unwrap_or_default();
+        let retry_observer = self.retry_observer.take();
+        let query_cache = self.query_cache.take();
+        let write_backpressure = self.write_backpressure.take();
+        let mut connection = self.open_connection().await?;
+        let session = if lazy_connect {
             None
         } else {
-            match self.credentials_file {
-                Some(file) => Some(gcp_auth::CustomServiceAccount::from_file(file)?.into()),
-                None => Some(gcp_auth::AuthenticationManager::new().await?),
+            Some(connection.create_session().await?)
+        };
+
+        Ok(Client::connect(
+            connection.clone(),
+            SessionPool::single(SessionManager::new(connection), session),
+        )
+        .with_read_only(read_only)
+        .with_retry_policy(retry_policy)
+        .with_retry_observer(retry_observer)
+        .with_query_cache(query_cache)
+        .with_write_backpressure(write_backpressure))
+    }
+
+    /// Returns the TLS settings, including the CA certificate and domain name when specified.
+    fn tls_config(&self) -> Result<Option<ClientTlsConfig>, Error> {
+        if self.ca_certificate.is_none() && self.domain_name.is_none() {
+            return Ok(self.tls_config.clone());
+        }
+        let mut tls_config = match (&self.tls_config, &self.channel, self.endpoint.as_deref()) {
+            (None, _, _) => {
+                return Err(Error::Config(
+                    "a CA certificate or domain name requires TLS, which is disabled".to_string(),
+                ))
+            }
+            (_, Some(_), _) => {
+                return Err(Error::Config(
+                    "a CA certificate or domain name cannot be used with an existing channel, configure TLS on the channel instead".to_string(),
+                ))
+            }
+            (_, _, Some(endpoint)) if !endpoint.starts_with("https://") => {
+                return Err(Error::Config(format!(
+                    "a CA certificate or domain name requires an https endpoint: {}",
+                    endpoint
+                )))
+            }
+            (Some(tls_config), _, _) => tls_config.clone(),
+        };
+        if let Some(pem) = &self.ca_certificate {
+            if !String::from_utf8_lossy(pem).contains("-----BEGIN CERTIFICATE-----") {
+                return Err(Error::Config(
+                    "invalid CA certificate: expected a PEM-encoded certificate".to_string(),
+                ));
+            }
+            tls_config = tls_config.ca_certificate(Certificate::from_pem(pem));
+        }
+        if let Some(domain_name) = &self.domain_name {
+            tls_config = tls_config.domain_name(domain_name.clone());
+        }
+        Ok(Some(tls_config))
+    }
+
+    async fn open_connection(self) -> Result<Box<dyn Connection>, Error> {
+        let tls_config = self.tls_config()?;
+        let auth = if tls_config.is_none() {
+            // Disabling TLS disables authentication, which would silently drop the tokens provided by the application.
+            if let Some(AuthProvider::External(_)) = self.auth {
+                return Err(Error::Config(
+                    "access tokens cannot be used when TLS is disabled".to_string(),
+                ));
+            }
+            None
+        } else {
+            match (self.auth, self.credentials_file) {
+                (Some(auth), _) => Some(auth),
+                (None, Some(file)) => Some(AuthProvider::new(
+                    gcp_auth::CustomServiceAccount::from_file(file)?.into(),
+                )),
+                (None, None) if self.lazy_connect => Some(AuthProvider::Shared),
+                (None, None) => Some(AuthProvider::new(
+                    gcp_auth::AuthenticationManager::new().await?,
+                )),
             }
         };
 
@@ -112,18 +535,32 @@ impl Config {
             &self.database,
         );
 
-        let connection =
-            crate::connection::grpc::connect(self.endpoint, self.tls_config, auth, database_id)
-                .await?;
-
-        let pool = self
-            .session_pool_config
-            .unwrap_or_default()
-            .build()
-            .build(SessionManager::new(connection.clone()))
-            .await?;
-
-        Ok(Client::connect(connection, pool))
+        crate::connection::grpc::connect(
+            self.endpoint,
+            tls_config,
+            auth,
+            database_id,
+            self.lazy_connect,
+            self.dialect,
+            ChannelOptions {
+                http2_keep_alive_interval: self.http2_keep_alive_interval,
+                keep_alive_timeout: self.keep_alive_timeout,
+                tcp_nodelay: self.tcp_nodelay,
+                connect_timeout: self.connect_timeout,
+                timeouts: self.rpc_timeouts.unwrap_or_default(),
+                user_agent_suffix: self.user_agent_suffix,
+                metadata: self.metadata,
+                route_to_leader: self.route_to_leader,
+                layers: self.layers,
+                channel: self.channel,
+                concurrency_limit: self.concurrency_limit,
+                statement_error_context: self.statement_error_context,
+                token_refresh_window: self
+                    .token_refresh_window
+                    .unwrap_or(DEFAULT_TOKEN_REFRESH_WINDOW),
+            },
+        )
+        .await
     }
 }
 
@@ -152,10 +589,324 @@ impl ConfigBuilder {
         self.with_emulator_host(format!("http://localhost:{}", port))
     }
 
+    /// Trust a PEM-encoded CA certificate (or bundle of certificates) in addition to the system's trusted roots,
+    /// e.g.: when requests go through a corporate proxy.
+    ///
+    /// This requires TLS and an `https` endpoint, an [`Error::Config`] is returned when connecting otherwise.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use spanner_rs::Config;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// Config::builder()
+    ///     .endpoint("https://spanner-proxy.example.com")
+    ///     .ca_certificate(std::fs::read("/etc/ssl/corporate-ca.pem")?)
+    ///     .domain_name("spanner.googleapis.com");
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn ca_certificate(self, pem: impl AsRef<[u8]>) -> Self {
+        Self {
+            ca_certificate: Some(Some(pem.as_ref().to_vec())),
+            ..self
+        }
+    }
+
+    /// Use an existing channel to reach the Spanner API instead of connecting to the endpoint,
+    /// e.g.: a channel with custom TLS settings, over a Unix domain socket or to an in-process test server.
+    ///
+    /// The channel is used as-is: the endpoint, TLS, keep-alive, `TCP_NODELAY`, connect timeout and user agent settings
+    /// are ignored. Requests are still authenticated unless [`ConfigBuilder::disable_tls`] is also called.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use spanner_rs::Config;
+    /// use tonic::transport::Channel;
+    ///
+    /// #[tokio::main]
+    /// # async fn main() -> Result<(), spanner_rs::Error> {
+    /// let channel = Channel::from_static("http://localhost:9010").connect().await?;
+    /// let client = Config::builder()
+    ///     .project("my-gcp-project")
+    ///     .instance("my-spanner-instance")
+    ///     .database("my-database")
+    ///     .with_channel(channel)
+    ///     .disable_tls()
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn with_channel(self, channel: Channel) -> Self {
+        Self {
+            channel: Some(Some(channel)),
+            ..self
+        }
+    }
+
+    /// Use an existing [`gcp_auth::AuthenticationManager`] to obtain tokens, e.g.: one that is shared with clients of other
+    /// Google APIs, which avoids discovering credentials and obtaining tokens more than once.
+    ///
+    /// This takes precedence over [`ConfigBuilder::credentials_file`] and the default credentials.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// use std::sync::Arc;
+    /// use spanner_rs::Config;
+    ///
+    /// #[tokio::main]
+    /// # async fn main() -> Result<(), spanner_rs::Error> {
+    /// let manager = Arc::new(gcp_auth::AuthenticationManager::new().await?);
+    /// let client = Config::builder()
+    ///     .instance("my-spanner-instance")
+    ///     .database("my-database")
+    ///     .with_auth_manager(manager.clone())
+    ///     .connect()
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[must_use]
+    pub fn with_auth_manager(self, manager: impl Into<Arc<AuthenticationManager>>) -> Self {
+        Self {
+            auth: Some(Some(AuthProvider::Owned(manager.into()))),
+            ..self
+        }
+    }
+
+    /// Authenticate using an access token obtained by the application, e.g.: one that is minted by an external service.
+    ///
+    /// The token is sent as-is with every request, it is never refreshed. See [`ConfigBuilder::with_access_token_provider`]
+    /// for tokens that expire while the client is in use.
+    ///
+    /// Note that the project must be specified since it cannot be determined from the token.
+    /// Since [`ConfigBuilder::disable_tls`] also disables authentication, an [`Error::Config`] is returned when connecting
+    /// if TLS is disabled.
+    #[must_use]
+    pub fn with_access_token(self, token: impl Into<String>) -> Self {
+        let token: Arc<str> = token.into().into();
+        self.with_access_token_provider(move || {
+            let token = token.clone();
+            async move { Ok(token.to_string()) }
+        })
+    }
+
+    /// Authenticate using access tokens obtained by the application, e.g.: ones that are minted by an external service.
+    ///
+    /// The provider is called for every request, it is expected to cache tokens and only obtain a new one
+    /// when the current one is about to expire. Errors it returns fail the request.
+    ///
+    /// Note that the project must be specified since it cannot be determined from the tokens.
+    /// Since [`ConfigBuilder::disable_tls`] also disables authentication, an [`Error::Config`] is returned when connecting
+    /// if TLS is disabled.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Config, Error};
+    ///
+    /// Config::builder()
+    ///     .project("my-gcp-project")
+    ///     .with_access_token_provider(|| async {
+    ///         std::env::var("SPANNER_TOKEN").map_err(|_| Error::Config("missing token".to_string()))
+    ///     });
+    /// ```
+    #[must_use]
+    pub fn with_access_token_provider<F, Fut>(self, provider: F) -> Self
+    where
+        F: Fn() -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<String, Error>> + Send + 'static,
+    {
+        let provider: TokenProvider = Arc::new(move || Box::pin(provider()));
+        Self {
+            auth: Some(Some(AuthProvider::External(provider))),
+            ..self
+        }
+    }
+
+    /// Add static gRPC metadata to send with every request, e.g.: `x-goog-user-project` to attribute requests to a quota project.
+    ///
+    /// This may be called several times, including with the same key. An invalid key or value results in an [`Error::Config`]
+    /// when connecting.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::Config;
+    ///
+    /// Config::builder().metadata("x-goog-user-project", "my-quota-project");
+    /// ```
+    #[must_use]
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.metadata
+            .get_or_insert_with(Vec::new)
+            .push((key.into(), value.into()));
+        self
+    }
+
+    /// Wrap the gRPC channel with a custom [`tower::Layer`], e.g.: for auditing or fault injection.
+    ///
+    /// Layers wrap the channel's authentication layer, which means that they see requests before credentials are added.
+    /// This may be called several times, the first layer added being the outermost one.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Config, GrpcRequest, GrpcService};
+    /// use tower::ServiceExt;
+    ///
+    /// Config::builder().layer(tower::layer::layer_fn(|service: GrpcService| {
+    ///     service.map_request(|request: GrpcRequest| {
+    ///         println!("sending {}", request.uri());
+    ///         request
+    ///     })
+    /// }));
+    /// ```
+    #[must_use]
+    pub fn layer<L>(mut self, layer: L) -> Self
+    where
+        L: Layer<GrpcService> + Send + Sync + 'static,
+        L::Service: Service<GrpcRequest, Response = GrpcResponse> + Clone + Send + 'static,
+        <L::Service as Service<GrpcRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<GrpcRequest>>::Future: Send + 'static,
+    {
+        self.layers
+            .get_or_insert_with(Vec::new)
+            .push(ServiceLayer::new(layer));
+        self
+    }
+
+    /// Intercept the requests sent to Cloud Spanner using a [`tonic::service::Interceptor`],
+    /// e.g.: to add metadata or reject requests.
+    ///
+    /// Interceptors are layers, see [`ConfigBuilder::layer`] for how they are composed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::Config;
+    ///
+    /// Config::builder().interceptor(|mut request: tonic::Request<()>| {
+    ///     request
+    ///         .metadata_mut()
+    ///         .insert("x-audit-id", "my-service".parse().unwrap());
+    ///     Ok(request)
+    /// });
+    /// ```
+    #[must_use]
+    pub fn interceptor<F>(mut self, interceptor: F) -> Self
+    where
+        F: Interceptor + Clone + Send + Sync + 'static,
+    {
+        self.layers
+            .get_or_insert_with(Vec::new)
+            .push(ServiceLayer::interceptor(interceptor));
+        self
+    }
+
+    /// Invoke a callback every time a read/write transaction is retried because Cloud Spanner aborted it,
+    /// e.g.: to count or report contention.
+    ///
+    /// The callback is invoked before waiting for the [`RetryEvent::delay`], it should not block.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    /// use spanner_rs::{Config, RetryEvent};
+    ///
+    /// let retries = Arc::new(AtomicUsize::new(0));
+    /// let counter = retries.clone();
+    /// Config::builder().on_retry(move |event: &RetryEvent| {
+    ///     counter.fetch_add(1, Ordering::Relaxed);
+    ///     eprintln!("attempt {} aborted, retrying in {:?}", event.attempt(), event.delay());
+    /// });
+    /// ```
+    #[must_use]
+    pub fn on_retry<F>(self, observer: F) -> Self
+    where
+        F: Fn(&RetryEvent) + Send + Sync + 'static,
+    {
+        Self {
+            retry_observer: Some(Some(RetryObserver::new(observer))),
+            ..self
+        }
+    }
+
     /// See [Config::connect]
     pub async fn connect(self) -> Result<Client, Error> {
         self.build()?.connect().await
     }
+
+    /// See [Config::connect_single_session]
+    pub async fn connect_single_session(self) -> Result<Client, Error> {
+        self.build()?.connect_single_session().await
+    }
+}
+
+/// The deadlines of requests sent to Cloud Spanner, by type of request.
+///
+/// A request that does not complete within its deadline fails with a `DEADLINE_EXCEEDED` status.
+/// The defaults are the ones used by the official Cloud Spanner client libraries.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use spanner_rs::{Config, RpcTimeouts};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// Config::builder().rpc_timeouts(RpcTimeouts::builder().read(Duration::from_secs(5)).build()?);
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[builder(pattern = "owned", build_fn(error = "crate::Error"))]
+pub struct RpcTimeouts {
+    /// Specify the deadline of requests executing SQL statements, including batches of DML statements.
+    ///
+    /// Defaults to 30 seconds.
+    #[builder(default = "Duration::from_secs(30)")]
+    pub(crate) read: Duration,
+
+    /// Specify the deadline of requests committing or rolling back transactions.
+    ///
+    /// Defaults to 1 hour.
+    #[builder(default = "Duration::from_secs(3600)")]
+    pub(crate) commit: Duration,
+
+    /// Specify the deadline of requests creating or deleting sessions.
+    ///
+    /// Defaults to 30 seconds.
+    #[builder(default = "Duration::from_secs(30)")]
+    pub(crate) session: Duration,
+
+    /// Specify the deadline of batch writes, which covers streaming the outcome of every group, see [`Client::batch_write`].
+    ///
+    /// Unbounded by default, since large batches may take a long time to be applied.
+    #[builder(setter(strip_option), default)]
+    pub(crate) batch_write: Option<Duration>,
+}
+
+impl Default for RpcTimeouts {
+    fn default() -> Self {
+        Self {
+            read: Duration::from_secs(30),
+            commit: Duration::from_secs(3600),
+            session: Duration::from_secs(30),
+            batch_write: None,
+        }
+    }
+}
+
+impl RpcTimeouts {
+    /// Returns a new [`RpcTimeoutsBuilder`] for configuring request deadlines.
+    pub fn builder() -> RpcTimeoutsBuilder {
+        RpcTimeoutsBuilder::default()
+    }
 }
 
 /// Configuration for the internal Cloud Spanner session pool.
@@ -170,15 +921,89 @@ impl ConfigBuilder {
 /// # Ok(()) }
 /// ```
 #[derive(Builder, Default, Debug)]
-#[builder(pattern = "owned", build_fn(error = "crate::Error"))]
+#[builder(
+    pattern = "owned",
+    build_fn(error = "crate::Error", validate = "Self::validate")
+)]
 pub struct SessionPoolConfig {
     /// Specify the maximum number of sessions that should be maintained in the pool.
     #[builder(setter(strip_option), default)]
     max_size: Option<u32>,
 
     /// Specify the minimum number of sessions that should be maintained in the pool.
+    ///
+    /// These sessions are created using a single request when connecting, or when calling [`Client::warmup`].
     #[builder(setter(strip_option), default)]
     min_idle: Option<u32>,
+
+    /// Specify how long to wait for a session to become available before giving up with an [`Error::Client`].
+    ///
+    /// Defaults to 30 seconds.
+    #[builder(setter(strip_option), default)]
+    connection_timeout: Option<Duration>,
+
+    /// Specify the maximum duration a session is kept in the pool before being replaced by a new one.
+    ///
+    /// Defaults to 30 minutes.
+    #[builder(setter(strip_option), default)]
+    max_lifetime: Option<Duration>,
+
+    /// Specify how long a session may stay idle in the pool (beyond `min_idle` sessions) before it is removed.
+    ///
+    /// Defaults to 10 minutes.
+    #[builder(setter(strip_option), default)]
+    idle_timeout: Option<Duration>,
+
+    /// Specify the order in which idle sessions are handed out. See [`QueueStrategy`] for the default.
+    #[builder(default)]
+    queue_strategy: QueueStrategy,
+
+    /// Specify how often the pool removes the sessions that exceeded their `idle_timeout` or `max_lifetime`,
+    /// trimming the pool back towards `min_idle` sessions.
+    ///
+    /// Defaults to 30 seconds.
+    #[builder(setter(strip_option), default)]
+    reaper_rate: Option<Duration>,
+
+    /// Specify the fraction, at least `0.0` and less than `1.0`, by which `reaper_rate`, `max_lifetime` and `idle_timeout`
+    /// are randomly shortened or extended when connecting, e.g.: `0.1` for ±10%.
+    ///
+    /// This prevents the clients of a fleet started at the same time from replacing their sessions all at once.
+    /// Other values are rejected with an [`Error::Config`]. Defaults to no jitter.
+    #[builder(setter(strip_option), default)]
+    jitter: Option<f64>,
+
+    /// Specify the age at which sessions are replaced by new ones, regardless of how they are used.
+    ///
+    /// Cloud Spanner recommends not using sessions older than 28 days, which is the default. Expired sessions are deleted and replaced
+    /// when released and idle ones are deleted and replaced by the pool's reaper, i.e.: `max_lifetime` never exceeds this lifetime.
+    /// This also applies to the session of a [`Client`] created using [`ConfigBuilder::connect_single_session`],
+    /// which is replaced the next time it is used.
+    #[builder(setter(strip_option), default)]
+    max_session_lifetime: Option<Duration>,
+}
+
+/// Determines which idle session is handed out by the pool.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum QueueStrategy {
+    /// Hand out the session that has been idle the longest, which spreads the load over all sessions.
+    #[default]
+    Fifo,
+    /// Hand out the most recently used session, which allows unused sessions to expire when the load decreases.
+    Lifo,
+}
+
+impl SessionPoolConfigBuilder {
+    fn validate(&self) -> Result<(), Error> {
+        match self.jitter {
+            // jittering by 100% or more could shorten durations to nothing
+            Some(Some(jitter)) if !(0.0..1.0).contains(&jitter) => Err(Error::Config(format!(
+                "jitter must be at least 0.0 and less than 1.0, got {}",
+                jitter
+            ))),
+            _ => Ok(()),
+        }
+    }
 }
 
 impl SessionPoolConfig {
@@ -186,19 +1011,72 @@ impl SessionPoolConfig {
         SessionPoolConfigBuilder::default()
     }
 
+    fn max_session_lifetime(&self) -> Duration {
+        self.max_session_lifetime
+            .unwrap_or(DEFAULT_MAX_SESSION_LIFETIME)
+    }
+
     fn build(self) -> PoolBuilder<SessionManager> {
-        let mut builder = Pool::builder().test_on_check_out(false);
+        let jitter = self.jitter.unwrap_or(0.0);
+        let seed = random_seed();
+        let jittered = |duration| jittered(duration, jitter, seed);
+        let mut builder =
+            Pool::builder()
+                .test_on_check_out(false)
+                .queue_strategy(match self.queue_strategy {
+                    QueueStrategy::Fifo => bb8::QueueStrategy::Fifo,
+                    QueueStrategy::Lifo => bb8::QueueStrategy::Lifo,
+                });
         if let Some(max_size) = self.max_size {
             builder = builder.max_size(max_size);
         }
+        if let Some(connection_timeout) = self.connection_timeout {
+            builder = builder.connection_timeout(connection_timeout);
+        }
+        // Sessions would otherwise only be replaced once released, i.e.: never when idle.
+        let max_lifetime = self
+            .max_lifetime
+            .map_or(DEFAULT_MAX_LIFETIME, jittered)
+            .min(self.max_session_lifetime());
+        builder = builder.max_lifetime(Some(max_lifetime));
+        if let Some(idle_timeout) = self.idle_timeout {
+            builder = builder.idle_timeout(Some(jittered(idle_timeout)));
+        }
+        if let Some(reaper_rate) = self.reaper_rate {
+            builder = builder.reaper_rate(jittered(reaper_rate));
+        } else if jitter > 0.0 {
+            builder = builder.reaper_rate(jittered(DEFAULT_REAPER_RATE));
+        }
         builder.min_idle(self.min_idle)
     }
 }
 
+// bb8's default maximum lifetime.
+const DEFAULT_MAX_LIFETIME: Duration = Duration::from_secs(30 * 60);
+
+// bb8's default reaper rate, jittered when no rate is specified.
+const DEFAULT_REAPER_RATE: Duration = Duration::from_secs(30);
+
+// Returns a value in [0, 1) which differs between processes and pools.
+fn random_seed() -> f64 {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let hash = RandomState::new().build_hasher().finish();
+    (hash >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Scales `duration` by a factor in [1 - jitter, 1 + jitter), as determined by `seed`.
+fn jittered(duration: Duration, jitter: f64, seed: f64) -> Duration {
+    duration.mul_f64(1.0 + jitter * (2.0 * seed - 1.0))
+}
+
 #[cfg(test)]
 mod test {
 
     use super::*;
+    use crate::connection::grpc::test::{FakeServer, FAKE_TOKEN};
+    use crate::ErrorCode;
 
     #[test]
     fn test_config_database() {
@@ -214,6 +1092,205 @@ mod test {
         assert_eq!(cfg.database, "database".to_string());
     }
 
+    #[test]
+    fn test_config_from_vars() {
+        let vars = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(var, _)| *var == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        let cfg = Config::from_vars(vars(&[
+            ("GOOGLE_CLOUD_PROJECT", "fallback"),
+            ("SPANNER_RS_PROJECT_ID", "project"),
+            ("SPANNER_RS_INSTANCE", "instance"),
+            ("SPANNER_RS_DATABASE", "database"),
+            ("SPANNER_EMULATOR_HOST", "localhost:9010"),
+        ]))
+        .build()
+        .unwrap();
+        assert_eq!(cfg.project, Some("project".to_string()));
+        assert_eq!(cfg.instance, "instance".to_string());
+        assert_eq!(cfg.database, "database".to_string());
+        assert_eq!(cfg.endpoint, Some("http://localhost:9010".to_string()));
+        assert!(cfg.tls_config.is_none());
+
+        let cfg = Config::from_vars(vars(&[
+            ("GOOGLE_CLOUD_PROJECT", "fallback"),
+            ("SPANNER_RS_ENDPOINT", "https://spanner.example.com"),
+            ("SPANNER_EMULATOR_HOST", "localhost:9010"),
+        ]))
+        .instance("instance")
+        .database("database")
+        .build()
+        .unwrap();
+        assert_eq!(cfg.project, Some("fallback".to_string()));
+        assert_eq!(
+            cfg.endpoint,
+            Some("https://spanner.example.com".to_string())
+        );
+        assert!(cfg.tls_config.is_some());
+
+        assert!(Config::from_vars(vars(&[])).build().is_err());
+    }
+
+    #[test]
+    fn test_config_from_uri() {
+        let cfg = Config::from_uri(
+            "spanner://projects/p/instances/i/databases/d?max_sessions=100&min_sessions=10&read_only=true",
+        )
+        .unwrap()
+        .build()
+        .unwrap();
+        assert_eq!(cfg.project, Some("p".to_string()));
+        assert_eq!(cfg.instance, "i".to_string());
+        assert_eq!(cfg.database, "d".to_string());
+        assert!(cfg.read_only);
+        let session_pool = cfg.session_pool_config.unwrap();
+        assert_eq!(session_pool.max_size, Some(100));
+        assert_eq!(session_pool.min_idle, Some(10));
+
+        let cfg =
+            Config::from_uri("spanner://instances/i/databases/d?emulator_host=localhost:9010")
+                .unwrap()
+                .build()
+                .unwrap();
+        assert_eq!(cfg.project, None);
+        assert_eq!(cfg.endpoint, Some("http://localhost:9010".to_string()));
+        assert!(cfg.tls_config.is_none());
+        assert!(cfg.session_pool_config.is_none());
+
+        for uri in [
+            "projects/p/instances/i/databases/d",
+            "spanner://projects/p/instances/i",
+            "spanner://projects/p/instances/i/databases/d/tables/t",
+            "spanner://instances/i/databases/d?max_sessions=many",
+            "spanner://instances/i/databases/d?unknown=true",
+            "spanner://instances/i/databases/d?read_only",
+        ] {
+            assert!(
+                matches!(Config::from_uri(uri), Err(Error::Config(_))),
+                "{} should be invalid",
+                uri
+            );
+        }
+    }
+
+    #[test]
+    fn test_config_tls() {
+        const PEM: &str = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+        let builder = || Config::builder().instance("instance").database("database");
+
+        let cfg = builder().build().unwrap();
+        assert!(cfg.tls_config().unwrap().is_some());
+
+        let cfg = builder()
+            .endpoint("https://spanner-proxy.example.com")
+            .ca_certificate(PEM)
+            .domain_name("spanner.googleapis.com")
+            .build()
+            .unwrap();
+        assert!(cfg.tls_config().unwrap().is_some());
+
+        for cfg in [
+            builder().ca_certificate(PEM).disable_tls(),
+            builder()
+                .endpoint("http://spanner-proxy.example.com")
+                .domain_name("spanner.googleapis.com"),
+            builder().ca_certificate("not a certificate"),
+        ] {
+            assert!(matches!(
+                cfg.build().unwrap().tls_config(),
+                Err(Error::Config(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_config_lazy_connect() {
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .build()
+            .unwrap();
+        assert!(!cfg.lazy_connect);
+
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .lazy_connect(true)
+            .build()
+            .unwrap();
+        assert!(cfg.lazy_connect);
+    }
+
+    #[test]
+    fn test_config_metadata() {
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .build()
+            .unwrap();
+        assert!(cfg.metadata.is_empty());
+
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .metadata("x-goog-user-project", "project")
+            .metadata("x-custom", "value")
+            .build()
+            .unwrap();
+        assert_eq!(
+            cfg.metadata,
+            vec![
+                ("x-goog-user-project".to_string(), "project".to_string()),
+                ("x-custom".to_string(), "value".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rpc_timeouts() {
+        assert_eq!(
+            RpcTimeouts::builder().build().unwrap(),
+            RpcTimeouts::default()
+        );
+
+        let timeouts = RpcTimeouts::builder()
+            .read(Duration::from_secs(5))
+            .build()
+            .unwrap();
+        assert_eq!(timeouts.read, Duration::from_secs(5));
+        assert_eq!(timeouts.commit, Duration::from_secs(3600));
+        assert_eq!(timeouts.batch_write, None);
+
+        let timeouts = RpcTimeouts::builder()
+            .batch_write(Duration::from_secs(7200))
+            .build()
+            .unwrap();
+        assert_eq!(timeouts.batch_write, Some(Duration::from_secs(7200)));
+    }
+
+    #[test]
+    fn test_config_read_only() {
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .build()
+            .unwrap();
+        assert!(!cfg.read_only);
+
+        let cfg = Config::builder()
+            .instance("instance")
+            .database("database")
+            .read_only(true)
+            .build()
+            .unwrap();
+        assert!(cfg.read_only);
+    }
+
     #[test]
     fn test_config_endpoint() {
         let cfg = Config::builder().endpoint("endpoint");
@@ -230,5 +1307,180 @@ mod test {
 
         assert_eq!(built.max_size, Some(10));
         assert_eq!(built.min_idle, Some(100));
+        assert_eq!(built.connection_timeout, None);
+        assert_eq!(built.queue_strategy, QueueStrategy::Fifo);
+
+        let built = SessionPoolConfig::builder()
+            .connection_timeout(Duration::from_millis(500))
+            .max_lifetime(Duration::from_secs(3000))
+            .idle_timeout(Duration::from_secs(60))
+            .queue_strategy(QueueStrategy::Lifo)
+            .build()
+            .unwrap();
+
+        assert_eq!(built.connection_timeout, Some(Duration::from_millis(500)));
+        assert_eq!(built.max_lifetime, Some(Duration::from_secs(3000)));
+        assert_eq!(built.idle_timeout, Some(Duration::from_secs(60)));
+        assert_eq!(built.queue_strategy, QueueStrategy::Lifo);
+
+        let built = SessionPoolConfig::builder()
+            .reaper_rate(Duration::from_secs(10))
+            .jitter(0.1)
+            .build()
+            .unwrap();
+
+        assert_eq!(built.reaper_rate, Some(Duration::from_secs(10)));
+        assert_eq!(built.jitter, Some(0.1));
+        assert_eq!(built.max_session_lifetime(), DEFAULT_MAX_SESSION_LIFETIME);
+
+        let built = SessionPoolConfig::builder()
+            .max_session_lifetime(Duration::from_secs(600))
+            .build()
+            .unwrap();
+        assert_eq!(built.max_session_lifetime(), Duration::from_secs(600));
+    }
+
+    #[test]
+    fn test_session_pool_config_invalid_jitter() {
+        for jitter in [f64::NAN, f64::INFINITY, -0.1, 1.0, 2.0] {
+            assert!(matches!(
+                SessionPoolConfig::builder().jitter(jitter).build(),
+                Err(Error::Config(_))
+            ));
+        }
+        assert!(SessionPoolConfig::builder().jitter(0.0).build().is_ok());
+        assert!(SessionPoolConfig::builder().jitter(0.99).build().is_ok());
+    }
+
+    #[test]
+    fn test_jittered() {
+        let duration = Duration::from_secs(100);
+        assert_eq!(jittered(duration, 0.0, 0.3), duration);
+        assert_eq!(jittered(duration, 0.1, 0.0), Duration::from_secs(90));
+        assert_eq!(jittered(duration, 0.1, 0.5), duration);
+        assert!(jittered(duration, 0.1, 0.99) < Duration::from_secs(110));
+        for _ in 0..100 {
+            let seed = random_seed();
+            assert!((0.0..1.0).contains(&seed));
+        }
+    }
+
+    fn fake_database(server: &FakeServer) -> ConfigBuilder {
+        Config::builder()
+            .project("project")
+            .instance("instance")
+            .database("database")
+            .with_channel(server.channel())
+    }
+
+    #[tokio::test]
+    async fn test_with_channel() {
+        let server = FakeServer::start();
+        // nothing listens on the endpoint, which would fail the requests if it were dialed
+        let result = fake_database(&server)
+            .endpoint("http://127.0.0.1:1")
+            .user_agent_suffix("my-app")
+            .with_access_token("my-token")
+            .connect_single_session()
+            .await;
+        assert_eq!(
+            result.err().and_then(|err| err.code()),
+            Some(ErrorCode::Unimplemented)
+        );
+
+        let requests = server.grpc_requests();
+        assert!(!requests.is_empty());
+        for request in requests {
+            assert!(request.path.starts_with("/google.spanner.v1.Spanner/"));
+            // the channel options are not applied to the provided channel
+            let user_agent = request.header("user-agent").unwrap_or_default();
+            assert!(!user_agent.contains("my-app"), "{}", user_agent);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_access_token() {
+        let server = FakeServer::start();
+        let result = fake_database(&server)
+            .with_access_token("my-token")
+            .connect_single_session()
+            .await;
+        assert_eq!(
+            result.err().and_then(|err| err.code()),
+            Some(ErrorCode::Unimplemented)
+        );
+
+        let requests = server.grpc_requests();
+        assert!(!requests.is_empty());
+        for request in requests {
+            assert_eq!(request.header("authorization"), Some("Bearer my-token"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_auth_manager() {
+        let server = FakeServer::start();
+        // a service account whose tokens are obtained from the fake server, the key was generated for this test only
+        let key = std::fs::read_to_string(concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/tests/data/test-service-account-key.pem"
+        ))
+        .unwrap();
+        let credentials = format!(
+            r#"{{
+                "type": "service_account",
+                "project_id": "project",
+                "private_key_id": "test",
+                "private_key": "{key}",
+                "client_email": "test@project.iam.gserviceaccount.com",
+                "client_id": "1",
+                "auth_uri": "{endpoint}/auth",
+                "token_uri": "{endpoint}/token",
+                "auth_provider_x509_cert_url": "{endpoint}/certs",
+                "client_x509_cert_url": "{endpoint}/certs/test"
+            }}"#,
+            key = key.replace('\n', "\\n"),
+            endpoint = server.endpoint()
+        );
+        let path = std::env::temp_dir().join(format!(
+            "spanner-rs-test-credentials-{}.json",
+            std::process::id()
+        ));
+        std::fs::write(&path, credentials).unwrap();
+        let manager =
+            AuthenticationManager::from(gcp_auth::CustomServiceAccount::from_file(&path).unwrap());
+        std::fs::remove_file(&path).unwrap();
+
+        let result = fake_database(&server)
+            .with_auth_manager(manager)
+            .connect_single_session()
+            .await;
+        assert_eq!(
+            result.err().and_then(|err| err.code()),
+            Some(ErrorCode::Unimplemented)
+        );
+
+        assert!(server
+            .requests()
+            .iter()
+            .any(|request| request.path == "/token"));
+        let requests = server.grpc_requests();
+        assert!(!requests.is_empty());
+        let expected = format!("Bearer {}", FAKE_TOKEN);
+        for request in requests {
+            assert_eq!(request.header("authorization"), Some(expected.as_str()));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_with_access_token_without_tls() {
+        let server = FakeServer::start();
+        let result = fake_database(&server)
+            .with_access_token("my-token")
+            .disable_tls()
+            .connect()
+            .await;
+        assert!(matches!(result, Err(Error::Config(_))));
+        assert!(server.requests().is_empty());
     }
 }
@@ -1,6 +1,13 @@
-use crate::{Error, ResultSet, Session, Statement, ToSpanner, Transaction, TransactionSelector};
+use std::sync::Mutex;
+
+use crate::{
+    CommitResponse, Error, Mutation, MutationGroup, MutationGroupOutcome, QueryPlan, ResultSet,
+    Session, Statement, TimestampBound, Transaction, TransactionSelector, TxOptions,
+};
 use async_trait::async_trait;
 use dyn_clone::DynClone;
+use futures_util::stream::BoxStream;
+use prost::bytes::Bytes;
 
 #[async_trait]
 pub(crate) trait Connection
@@ -8,27 +15,99 @@ where
     Self: DynClone + Send,
 {
     async fn create_session(&mut self) -> Result<Session, Error>;
+    /// Creates exactly `count` sessions, using as few requests as possible.
+    async fn batch_create_sessions(&mut self, count: u32) -> Result<Vec<Session>, Error>;
     async fn delete_session(&mut self, session: Session) -> Result<(), Error>;
-    async fn commit(&mut self, session: &Session, transaction: Transaction) -> Result<(), Error>;
+    /// Commits the transaction along with the provided mutations.
+    ///
+    /// When no transaction is provided, the mutations are applied in a single-use read/write transaction.
+    async fn commit(
+        &mut self,
+        session: &Session,
+        transaction: Option<Transaction>,
+        mutations: &[Mutation],
+        options: &TxOptions,
+    ) -> Result<CommitResponse, Error>;
     async fn rollback(&mut self, session: &Session, transaction: Transaction) -> Result<(), Error>;
+
+    /// Applies each group of mutations atomically, but independently of the others, and streams back the outcome of each group.
+    async fn batch_write(
+        &mut self,
+        session: &Session,
+        groups: Vec<MutationGroup>,
+    ) -> Result<BoxStream<'static, Result<MutationGroupOutcome, Error>>, Error>;
     async fn execute_sql(
         &mut self,
         session: &Session,
         selector: &TransactionSelector,
-        statement: &str,
-        parameters: &[(&str, &(dyn ToSpanner + Sync))],
+        statement: &Statement,
         seqno: Option<i64>,
+        options: Option<&TxOptions>,
     ) -> Result<ResultSet, Error>;
 
+    /// Returns the plan of a query without executing it.
+    async fn plan_sql(
+        &mut self,
+        session: &Session,
+        statement: &Statement,
+    ) -> Result<QueryPlan, Error>;
+
     async fn execute_batch_dml(
         &mut self,
         session: &Session,
         selector: &TransactionSelector,
         statements: &[&Statement],
         seqno: i64,
+        options: &TxOptions,
     ) -> Result<Vec<ResultSet>, Error>;
+
+    /// Begins a read-only transaction that can be used for several reads, e.g.: to partition a query.
+    async fn begin_read_only(
+        &mut self,
+        session: &Session,
+        bound: TimestampBound,
+    ) -> Result<Transaction, Error>;
+
+    /// Divides a query into partitions that can be executed in parallel within the read-only transaction and returns their tokens.
+    async fn partition_query(
+        &mut self,
+        session: &Session,
+        transaction: &Transaction,
+        statement: &Statement,
+    ) -> Result<Vec<Bytes>, Error>;
+
+    /// Executes the partition of a query identified by its token, see [`Connection::partition_query`].
+    async fn execute_partition(
+        &mut self,
+        session: &Session,
+        transaction: &Transaction,
+        statement: &Statement,
+        token: &Bytes,
+    ) -> Result<ResultSet, Error>;
 }
 
 dyn_clone::clone_trait_object!(Connection);
 
+/// A connection that can be shared by reference between tasks, e.g.: by a [`crate::Client`] held in an `Arc`.
+///
+/// Connections are `Send` but not `Sync` (neither is the underlying gRPC service), so each user obtains its own clone.
+pub(crate) struct SharedConnection(Mutex<Box<dyn Connection>>);
+
+impl SharedConnection {
+    pub(crate) fn new(connection: Box<dyn Connection>) -> Self {
+        Self(Mutex::new(connection))
+    }
+
+    /// Returns a clone of the shared connection.
+    pub(crate) fn get(&self) -> Box<dyn Connection> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner()).clone()
+    }
+}
+
+impl Clone for SharedConnection {
+    fn clone(&self) -> Self {
+        Self::new(self.get())
+    }
+}
+
 pub(crate) mod grpc;
@@ -1,35 +1,227 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+use std::time::{Duration, Instant};
+
 use super::Connection;
-use crate::auth::AuthFilter;
+use crate::auth::{AuthFilter, AuthProvider};
+use crate::batch_write::proto::{self as batch_write, BATCH_WRITE_PATH};
+use crate::dialect::DIALECT_QUERY;
+use crate::layer::{GrpcService, ServiceLayer};
+use crate::limit::Limiter;
+use crate::status::REQUEST_ID;
 use crate::{
-    DatabaseId, Error, ResultSet, Session, SpannerResource, Statement, ToSpanner, Transaction,
-    TransactionSelector,
+    CommitResponse, ConcurrencyLimit, DatabaseId, Dialect, Error, Mutation, MutationGroup,
+    MutationGroupOutcome, QueryPlan, ResultSet, RpcTimeouts, Session, SpannerResource, Statement,
+    TimestampBound, Transaction, TransactionSelector, TxOptions,
 };
 use async_trait::async_trait;
-use gcp_auth::AuthenticationManager;
+use futures_util::stream::{self, BoxStream, StreamExt};
 use google_api_proto::google::spanner::v1::{self as proto, ExecuteBatchDmlRequest};
+use prost::bytes::Bytes;
 use proto::{
-    execute_sql_request::QueryMode, spanner_client::SpannerClient, CommitRequest,
-    CreateSessionRequest, DeleteSessionRequest, ExecuteSqlRequest, RollbackRequest,
+    execute_sql_request::QueryMode, spanner_client::SpannerClient, BatchCreateSessionsRequest,
+    BeginTransactionRequest, CommitRequest, CreateSessionRequest, DeleteSessionRequest,
+    ExecuteSqlRequest, PartitionQueryRequest, RollbackRequest,
 };
-use tonic::transport::{Channel, ClientTlsConfig};
+use tonic::client::Grpc;
+use tonic::codec::ProstCodec;
+use tonic::metadata::{AsciiMetadataKey, AsciiMetadataValue};
+use tonic::transport::{Channel, ClientTlsConfig, Endpoint};
 use tonic::Request;
-use tower::filter::{AsyncFilter, AsyncFilterLayer};
-use tower::util::Either;
+use tower::filter::AsyncFilterLayer;
 use tower::ServiceBuilder;
 
 #[derive(Clone)]
 struct GrpcConnection {
     database: DatabaseId,
-    // TODO: abstract over Service
-    spanner: SpannerClient<Either<AsyncFilter<Channel, AuthFilter>, Channel>>,
+    // Shared by all clones of this connection, unset until detected.
+    dialect: Arc<DialectCache>,
+    timeouts: RpcTimeouts,
+    // Sent with every request.
+    metadata: Arc<Vec<(AsciiMetadataKey, AsciiMetadataValue)>>,
+    route_to_leader: bool,
+    // Identifies this connection in request ids.
+    client_id: u32,
+    // The number of requests sent by this connection and its clones.
+    requests: Arc<AtomicU64>,
+    // Shared by all clones of this connection.
+    limiter: Limiter,
+    // Whether errors include the statement that caused them.
+    statement_error_context: bool,
+    spanner: SpannerClient<GrpcService>,
+    // Used to call the methods missing from `SpannerClient`, see `crate::batch_write::proto`.
+    grpc: Grpc<GrpcService>,
 }
 
-pub(crate) async fn connect(
+/// Settings of the underlying gRPC channel.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ChannelOptions {
+    pub(crate) http2_keep_alive_interval: Option<Duration>,
+    pub(crate) keep_alive_timeout: Option<Duration>,
+    pub(crate) tcp_nodelay: Option<bool>,
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) timeouts: RpcTimeouts,
+    pub(crate) user_agent_suffix: Option<String>,
+    pub(crate) metadata: Vec<(String, String)>,
+    pub(crate) route_to_leader: bool,
+    pub(crate) layers: Vec<ServiceLayer>,
+    pub(crate) token_refresh_window: Duration,
+    // Provided by the user, in which case it is used as-is: the endpoint is not dialed and `apply` is not called.
+    pub(crate) channel: Option<Channel>,
+    pub(crate) concurrency_limit: Option<ConcurrencyLimit>,
+    pub(crate) statement_error_context: bool,
+}
+
+impl ChannelOptions {
+    fn apply(&self, mut endpoint: Endpoint) -> Result<Endpoint, Error> {
+        let user_agent = match &self.user_agent_suffix {
+            Some(suffix) => format!("{} {}", USER_AGENT, suffix),
+            None => USER_AGENT.to_string(),
+        };
+        endpoint = endpoint.user_agent(user_agent)?;
+        if let Some(interval) = self.http2_keep_alive_interval {
+            // pings are only useful if they are also sent when the channel is idle
+            endpoint = endpoint
+                .http2_keep_alive_interval(interval)
+                .keep_alive_while_idle(true);
+        }
+        if let Some(timeout) = self.keep_alive_timeout {
+            endpoint = endpoint.keep_alive_timeout(timeout);
+        }
+        if let Some(nodelay) = self.tcp_nodelay {
+            endpoint = endpoint.tcp_nodelay(nodelay);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            endpoint = endpoint.connect_timeout(timeout);
+        }
+        Ok(endpoint)
+    }
+
+    fn metadata(&self) -> Result<Vec<(AsciiMetadataKey, AsciiMetadataValue)>, Error> {
+        self.metadata
+            .iter()
+            .map(|(key, value)| {
+                let key = AsciiMetadataKey::from_str(key)
+                    .map_err(|_| Error::Config(format!("invalid metadata key: {}", key)))?;
+                let value = AsciiMetadataValue::from_str(value)
+                    .map_err(|_| Error::Config(format!("invalid metadata value for {}", key)))?;
+                Ok((key, value))
+            })
+            .collect()
+    }
+}
+
+const REQUEST_PARAMS: &str = "x-goog-request-params";
+const ROUTE_TO_LEADER: &str = "x-goog-spanner-route-to-leader";
+
+/// The resource targeted by a request, see [`GrpcConnection::request`].
+enum Routing<'a> {
+    Session(&'a Session),
+    Database,
+}
+
+/// Percent-encodes everything but unreserved characters, as expected in `x-goog-request-params`.
+fn url_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
+
+/// The id sent with a request, see [`GrpcConnection::request`].
+struct RequestId(Option<AsciiMetadataValue>);
+
+impl RequestId {
+    fn of<T>(request: &Request<T>) -> Self {
+        Self(request.metadata().get(REQUEST_ID).cloned())
+    }
+
+    /// Attaches this id to the status returned for the request, see [`Error::request_id`].
+    fn attach(&self, mut status: tonic::Status) -> tonic::Status {
+        if let Some(id) = &self.0 {
+            status.metadata_mut().insert(REQUEST_ID, id.clone());
+        }
+        status
+    }
+}
+
+/// A random number identifying this process in request ids.
+fn process_id() -> u64 {
+    static PROCESS_ID: OnceLock<u64> = OnceLock::new();
+    *PROCESS_ID.get_or_init(|| RandomState::new().build_hasher().finish())
+}
+
+static CLIENT_IDS: AtomicU32 = AtomicU32::new(0);
+
+const USER_AGENT: &str = concat!("spanner-rs/", env!("CARGO_PKG_VERSION"));
+
+/// How long to wait before detecting the dialect again after a failure, doubled after each consecutive failure.
+const DIALECT_RETRY_BACKOFF: Duration = Duration::from_secs(1);
+
+/// The longest delay between two attempts at detecting the dialect.
+const MAX_DIALECT_RETRY_BACKOFF: Duration = Duration::from_secs(300);
+
+/// The dialect of the database, either configured or detected using the first session.
+#[derive(Debug)]
+struct DialectCache {
+    dialect: OnceLock<Dialect>,
+    // The number of consecutive detection failures and when detection may be attempted again.
+    backoff: Mutex<Option<(u32, Instant)>>,
+}
+
+impl DialectCache {
+    fn new(dialect: Option<Dialect>) -> Self {
+        Self {
+            dialect: dialect.map(OnceLock::from).unwrap_or_default(),
+            backoff: Mutex::default(),
+        }
+    }
+
+    fn get(&self) -> Option<Dialect> {
+        self.dialect.get().copied()
+    }
+
+    fn backoff(&self) -> MutexGuard<'_, Option<(u32, Instant)>> {
+        self.backoff.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns true when the dialect is unknown and its detection did not fail recently.
+    fn should_detect(&self, now: Instant) -> bool {
+        self.get().is_none() && self.backoff().map_or(true, |(_, retry_at)| now >= retry_at)
+    }
+
+    fn detected(&self, dialect: Dialect) {
+        let _ = self.dialect.set(dialect);
+    }
+
+    fn failed(&self, now: Instant) {
+        let mut backoff = self.backoff();
+        let failures = backoff
+            .map_or(0, |(failures, _)| failures)
+            .saturating_add(1);
+        let delay = DIALECT_RETRY_BACKOFF
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(MAX_DIALECT_RETRY_BACKOFF);
+        *backoff = Some((failures, now + delay));
+    }
+}
+
+/// Creates a channel to the endpoint, or Cloud Spanner when unspecified.
+async fn dial(
     endpoint: Option<String>,
     tls_config: Option<ClientTlsConfig>,
-    auth: Option<AuthenticationManager>,
-    database: DatabaseId,
-) -> Result<Box<dyn Connection>, Error> {
+    options: &ChannelOptions,
+    lazy: bool,
+) -> Result<Channel, Error> {
     let channel = match endpoint {
         None => Channel::from_static("https://spanner.googleapis.com")
             .tls_config(tls_config.ok_or_else(|| Error::Config("TLS is required".into()))?)?,
@@ -45,101 +237,475 @@ pub(crate) async fn connect(
         }
     };
 
-    let channel = channel.connect().await?;
+    let channel = options.apply(channel)?;
 
-    let auth_layer = auth
-        .map(|auth| AsyncFilterLayer::new(AuthFilter::new(auth, crate::auth::Scopes::Database)));
+    if lazy {
+        Ok(channel.connect_lazy())
+    } else {
+        Ok(channel.connect().await?)
+    }
+}
+
+pub(crate) async fn connect(
+    endpoint: Option<String>,
+    tls_config: Option<ClientTlsConfig>,
+    auth: Option<AuthProvider>,
+    database: DatabaseId,
+    lazy: bool,
+    dialect: Option<Dialect>,
+    options: ChannelOptions,
+) -> Result<Box<dyn Connection>, Error> {
+    let timeouts = options.timeouts.clone();
+    let metadata = Arc::new(options.metadata()?);
+    let route_to_leader = options.route_to_leader;
+
+    let channel = match &options.channel {
+        Some(channel) => channel.clone(),
+        None => dial(endpoint, tls_config, &options, lazy).await?,
+    };
+
+    let auth_layer = auth.map(|auth| {
+        AsyncFilterLayer::new(AuthFilter::new(
+            auth,
+            crate::auth::Scopes::Database,
+            options.token_refresh_window,
+        ))
+    });
 
     let channel = ServiceBuilder::new()
         .option_layer(auth_layer)
         .service(channel);
+    let channel = ServiceLayer::apply_all(&options.layers, GrpcService::new(channel));
 
+    let grpc = Grpc::new(channel.clone());
     let spanner = SpannerClient::new(channel);
 
-    Ok(Box::new(GrpcConnection { database, spanner }))
+    let dialect = Arc::new(DialectCache::new(dialect));
+
+    Ok(Box::new(GrpcConnection {
+        database,
+        dialect,
+        timeouts,
+        metadata,
+        route_to_leader,
+        client_id: CLIENT_IDS.fetch_add(1, Ordering::Relaxed) + 1,
+        requests: Arc::new(AtomicU64::new(0)),
+        limiter: options
+            .concurrency_limit
+            .as_ref()
+            .map(Limiter::new)
+            .unwrap_or_default(),
+        statement_error_context: options.statement_error_context,
+        spanner,
+        grpc,
+    }))
+}
+
+impl GrpcConnection {
+    /// Creates a request which Cloud Spanner will abandon if it has not completed within the specified timeout, if any.
+    ///
+    /// The request's `x-goog-request-params` header identifies the resource it targets, which Cloud Spanner uses for routing.
+    ///
+    /// The request is also given a unique id, in the same format as the official client libraries:
+    /// `<version>.<process>.<client>.<channel>.<request>.<attempt>`. Since connections use a single channel, the channel is always 1.
+    /// The attempt is also always 1: when the client retries (e.g.: an aborted transaction or an exceeded quota),
+    /// the request is created anew and is therefore given a new request number instead.
+    fn request<T>(
+        &self,
+        timeout: impl Into<Option<Duration>>,
+        routing: Routing<'_>,
+        message: T,
+    ) -> Request<T> {
+        let mut request = Request::new(message);
+        if let Some(timeout) = timeout.into() {
+            request.set_timeout(timeout);
+        }
+        let id = format!(
+            "1.{:x}.{}.1.{}.1",
+            process_id(),
+            self.client_id,
+            self.requests.fetch_add(1, Ordering::Relaxed) + 1
+        );
+        if let Ok(id) = AsciiMetadataValue::try_from(id) {
+            request.metadata_mut().insert(REQUEST_ID, id);
+        }
+        let params = match routing {
+            Routing::Session(session) => format!("session={}", url_encode(session.name())),
+            Routing::Database => format!("database={}", url_encode(&self.database.id())),
+        };
+        if let Ok(params) = AsciiMetadataValue::try_from(params) {
+            request.metadata_mut().insert(REQUEST_PARAMS, params);
+        }
+        for (key, value) in self.metadata.iter() {
+            request.metadata_mut().append(key.clone(), value.clone());
+        }
+        request
+    }
+
+    /// Creates a request made on behalf of a read/write transaction, see [`GrpcConnection::request`].
+    ///
+    /// When enabled, the request is routed to the leader region.
+    fn leader_request<T>(
+        &self,
+        timeout: impl Into<Option<Duration>>,
+        routing: Routing<'_>,
+        message: T,
+    ) -> Request<T> {
+        let mut request = self.request(timeout, routing, message);
+        if self.route_to_leader {
+            request
+                .metadata_mut()
+                .insert(ROUTE_TO_LEADER, AsciiMetadataValue::from_static("true"));
+        }
+        request
+    }
+
+    fn dialect(&self) -> Dialect {
+        self.dialect.get().unwrap_or_default()
+    }
+
+    fn execute_sql_request(
+        &self,
+        session: &Session,
+        selector: &TransactionSelector,
+        statement: &Statement,
+    ) -> Result<ExecuteSqlRequest, Error> {
+        let (params, param_types) = self.dialect().encode_parameters(statement)?;
+        Ok(ExecuteSqlRequest {
+            session: session.name().to_string(),
+            transaction: Some(selector.clone().try_into()?),
+            sql: statement.sql().to_string(),
+            params: Some(prost_types::Struct { fields: params }),
+            param_types,
+            resume_token: Bytes::default(),
+            query_mode: QueryMode::Normal as i32,
+            partition_token: Bytes::default(),
+            seqno: 0,
+            query_options: None,
+            request_options: None,
+        })
+    }
+
+    async fn execute(
+        &mut self,
+        session: &Session,
+        selector: &TransactionSelector,
+        statement: &Statement,
+        seqno: Option<i64>,
+        options: Option<&TxOptions>,
+        query_mode: QueryMode,
+    ) -> Result<proto::ResultSet, Error> {
+        let message = ExecuteSqlRequest {
+            query_mode: query_mode as i32,
+            seqno: seqno.unwrap_or(0), // ignored for queries, required for DML
+            request_options: options.map(TxOptions::request_options),
+            ..self.execute_sql_request(session, selector, statement)?
+        };
+        let timeout = statement.deadline().unwrap_or(self.timeouts.read);
+        let request = match selector {
+            TransactionSelector::SingleUse(_) => {
+                self.request(timeout, Routing::Session(session), message)
+            }
+            TransactionSelector::Id(_) | TransactionSelector::Begin => {
+                self.leader_request(timeout, Routing::Session(session), message)
+            }
+        };
+
+        let id = RequestId::of(&request);
+        let _permits = self
+            .limiter
+            .acquire(options.and_then(TxOptions::priority))
+            .await?;
+        Ok(self
+            .spanner
+            .execute_sql(request)
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner())
+    }
+
+    /// Adds the statement to the error when enabled, see [`crate::ConfigBuilder::statement_error_context`].
+    fn with_statement(&self, err: Error, statement: &Statement) -> Error {
+        if self.statement_error_context {
+            err.with_statement(statement)
+        } else {
+            err
+        }
+    }
+
+    // The dialect is detected using the first session, before it is used for any transaction.
+    // Failing to detect it is not fatal: it is attempted again with a later session, after a backoff so that
+    // repeated failures don't add a round trip to every session creation.
+    async fn init_dialect(&mut self, session: &Session) {
+        if self.dialect.should_detect(Instant::now()) {
+            match self.detect_dialect(session).await {
+                Ok(dialect) => self.dialect.detected(dialect),
+                Err(_) => self.dialect.failed(Instant::now()),
+            }
+        }
+    }
+
+    async fn detect_dialect(&mut self, session: &Session) -> Result<Dialect, Error> {
+        let result_set = self
+            .execute_sql(
+                session,
+                &TransactionSelector::SingleUse(None),
+                &Statement::new(DIALECT_QUERY),
+                None,
+                None,
+            )
+            .await?;
+
+        let dialect = match result_set.iter().next() {
+            Some(row) => Dialect::from_option_value(&row.get::<String, _>(0)?),
+            None => Dialect::GoogleStandardSql,
+        };
+        Ok(dialect)
+    }
 }
 
 #[async_trait]
 impl Connection for GrpcConnection {
     async fn create_session(&mut self) -> Result<Session, Error> {
-        let response = self
-            .spanner
-            .create_session(Request::new(CreateSessionRequest {
+        let request = self.request(
+            self.timeouts.session,
+            Routing::Database,
+            CreateSessionRequest {
                 database: self.database.id(),
                 session: None,
-            }))
-            .await?;
-        Ok(response.into_inner().into())
+            },
+        );
+        let id = RequestId::of(&request);
+        let permits = self.limiter.acquire(None).await?;
+        let response = self
+            .spanner
+            .create_session(request)
+            .await
+            .map_err(|status| id.attach(status))?;
+        // detecting the dialect sends another request
+        drop(permits);
+        let session: Session = response.into_inner().into();
+        self.init_dialect(&session).await;
+        Ok(session)
+    }
+
+    async fn batch_create_sessions(&mut self, count: u32) -> Result<Vec<Session>, Error> {
+        let mut sessions: Vec<Session> = Vec::with_capacity(count as usize);
+        // Cloud Spanner may return fewer sessions than requested.
+        while sessions.len() < count as usize {
+            let request = self.request(
+                self.timeouts.session,
+                Routing::Database,
+                BatchCreateSessionsRequest {
+                    database: self.database.id(),
+                    session_template: None,
+                    session_count: (count as usize - sessions.len()) as i32,
+                },
+            );
+            let id = RequestId::of(&request);
+            let _permits = self.limiter.acquire(None).await?;
+            let response = self
+                .spanner
+                .batch_create_sessions(request)
+                .await
+                .map_err(|status| id.attach(status))?;
+            let created = response.into_inner().session;
+            if created.is_empty() {
+                return Err(Error::Client("no session was created".to_string()));
+            }
+            sessions.extend(created.into_iter().map(Session::from));
+        }
+        if let Some(session) = sessions.first() {
+            self.init_dialect(session).await;
+        }
+        Ok(sessions)
     }
     async fn delete_session(&mut self, session: Session) -> Result<(), Error> {
-        self.spanner
-            .delete_session(Request::new(DeleteSessionRequest {
+        let request = self.request(
+            self.timeouts.session,
+            Routing::Session(&session),
+            DeleteSessionRequest {
                 name: session.name().to_string(),
-            }))
-            .await?;
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(None).await?;
+        self.spanner
+            .delete_session(request)
+            .await
+            .map_err(|status| id.attach(status))?;
         Ok(())
     }
 
-    async fn commit(&mut self, session: &Session, tx: Transaction) -> Result<(), Error> {
-        self.spanner
-            .commit(Request::new(CommitRequest {
+    async fn commit(
+        &mut self,
+        session: &Session,
+        tx: Option<Transaction>,
+        mutations: &[Mutation],
+        options: &TxOptions,
+    ) -> Result<CommitResponse, Error> {
+        let mutations = match mutations
+            .iter()
+            .cloned()
+            .map(proto::Mutation::try_from)
+            .collect::<Result<Vec<_>, Error>>()
+        {
+            Ok(mutations) => mutations,
+            Err(err) => {
+                // The transaction was begun by its first statement, it would otherwise hold its locks until it times out.
+                if let Some(tx) = tx {
+                    let _ = self.rollback(session, tx).await;
+                }
+                return Err(err);
+            }
+        };
+        let transaction = match tx {
+            Some(tx) => proto::commit_request::Transaction::TransactionId(tx.id().clone()),
+            None => proto::commit_request::Transaction::SingleUseTransaction(
+                proto::TransactionOptions {
+                    mode: Some(proto::transaction_options::Mode::ReadWrite(
+                        proto::transaction_options::ReadWrite {
+                            read_lock_mode:
+                                proto::transaction_options::read_write::ReadLockMode::Unspecified
+                                    .into(),
+                        },
+                    )),
+                },
+            ),
+        };
+        let request = self.leader_request(
+            options.commit_deadline().unwrap_or(self.timeouts.commit),
+            Routing::Session(session),
+            CommitRequest {
                 session: session.name().to_string(),
-                mutations: vec![],
-                return_commit_stats: false,
-                transaction: Some(proto::commit_request::Transaction::TransactionId(
-                    tx.id().clone(),
-                )),
-                request_options: None,
-            }))
-            .await?;
-        Ok(())
+                mutations,
+                return_commit_stats: options.return_commit_stats(),
+                transaction: Some(transaction),
+                request_options: Some(options.request_options()),
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(options.priority()).await?;
+        self.spanner
+            .commit(request)
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner()
+            .try_into()
     }
 
     async fn rollback(&mut self, session: &Session, tx: Transaction) -> Result<(), Error> {
-        self.spanner
-            .rollback(Request::new(RollbackRequest {
+        let request = self.leader_request(
+            self.timeouts.commit,
+            Routing::Session(session),
+            RollbackRequest {
                 session: session.name().to_string(),
                 transaction_id: tx.id().clone(),
-            }))
-            .await?;
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(None).await?;
+        self.spanner
+            .rollback(request)
+            .await
+            .map_err(|status| id.attach(status))?;
 
         Ok(())
     }
 
+    async fn batch_write(
+        &mut self,
+        session: &Session,
+        groups: Vec<MutationGroup>,
+    ) -> Result<BoxStream<'static, Result<MutationGroupOutcome, Error>>, Error> {
+        let mutation_groups = groups
+            .into_iter()
+            .map(batch_write::MutationGroup::try_from)
+            .collect::<Result<Vec<_>, Error>>()?;
+        let request = self.leader_request(
+            self.timeouts.batch_write,
+            Routing::Session(session),
+            batch_write::BatchWriteRequest {
+                session: session.name().to_string(),
+                mutation_groups,
+                ..Default::default()
+            },
+        );
+        let id = RequestId::of(&request);
+        let permits = self.limiter.acquire(None).await?;
+        self.grpc
+            .ready()
+            .await
+            .map_err(|err| tonic::Status::unknown(format!("service was not ready: {}", err)))?;
+        let responses = self
+            .grpc
+            .server_streaming::<_, batch_write::BatchWriteResponse, _>(
+                request,
+                http::uri::PathAndQuery::from_static(BATCH_WRITE_PATH),
+                ProstCodec::default(),
+            )
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner();
+        // Each response applies to one or more groups.
+        Ok(responses
+            .flat_map(move |response| {
+                // The request is in flight until the stream is consumed or dropped.
+                let _permits = &permits;
+                let outcomes = match response
+                    .map_err(|status| Error::from(id.attach(status)))
+                    .and_then(crate::batch_write::outcomes)
+                {
+                    Ok(outcomes) => outcomes.into_iter().map(Ok).collect(),
+                    Err(err) => vec![Err(err)],
+                };
+                stream::iter(outcomes)
+            })
+            .boxed())
+    }
+
     async fn execute_sql(
         &mut self,
         session: &Session,
         selector: &TransactionSelector,
-        statement: &str,
-        parameters: &[(&str, &(dyn ToSpanner + Sync))],
+        statement: &Statement,
         seqno: Option<i64>,
+        options: Option<&TxOptions>,
     ) -> Result<ResultSet, Error> {
-        let mut params = std::collections::BTreeMap::new();
-        let mut param_types = std::collections::BTreeMap::new();
-
-        for (name, value) in parameters {
-            let value = value.to_spanner()?;
-            param_types.insert(name.to_string(), value.spanner_type().into());
-            params.insert(name.to_string(), value.try_into()?);
-        }
+        self.execute(
+            session,
+            selector,
+            statement,
+            seqno,
+            options,
+            QueryMode::Normal,
+        )
+        .await
+        .map_err(|err| self.with_statement(err, statement))?
+        .try_into()
+    }
 
-        self.spanner
-            .execute_sql(Request::new(ExecuteSqlRequest {
-                session: session.name().to_string(),
-                transaction: Some(selector.clone().try_into()?),
-                sql: statement.to_string(),
-                params: Some(prost_types::Struct { fields: params }),
-                param_types,
-                resume_token: prost::bytes::Bytes::default(),
-                query_mode: QueryMode::Normal as i32,
-                partition_token: prost::bytes::Bytes::default(),
-                seqno: seqno.unwrap_or(0), // ignored for queries, required for DML
-                query_options: None,
-                request_options: None,
-            }))
-            .await?
-            .into_inner()
-            .try_into()
+    async fn plan_sql(
+        &mut self,
+        session: &Session,
+        statement: &Statement,
+    ) -> Result<QueryPlan, Error> {
+        let result_set = self
+            .execute(
+                session,
+                &TransactionSelector::SingleUse(None),
+                statement,
+                None,
+                None,
+                QueryMode::Plan,
+            )
+            .await
+            .map_err(|err| self.with_statement(err, statement))?;
+        result_set
+            .stats
+            .and_then(|stats| stats.query_plan)
+            .map(QueryPlan::from)
+            .ok_or_else(|| Error::Codec("missing query plan".to_string()))
     }
 
     async fn execute_batch_dml(
@@ -148,22 +714,46 @@ impl Connection for GrpcConnection {
         selector: &TransactionSelector,
         statements: &[&Statement],
         seqno: i64,
+        options: &TxOptions,
     ) -> Result<Vec<ResultSet>, Error> {
-        let statements = statements
+        let dialect = self.dialect();
+        let timeout = statements
+            .iter()
+            .filter_map(|statement| statement.deadline())
+            .max()
+            .unwrap_or(self.timeouts.read);
+        let encoded = statements
             .iter()
-            .map(|&statement| statement.try_into())
+            .map(|statement| {
+                let (params, param_types) = dialect
+                    .encode_parameters(statement)
+                    .map_err(|err| self.with_statement(err, statement))?;
+                Ok(proto::execute_batch_dml_request::Statement {
+                    sql: statement.sql().to_string(),
+                    params: Some(prost_types::Struct { fields: params }),
+                    param_types,
+                })
+            })
             .collect::<Result<Vec<proto::execute_batch_dml_request::Statement>, crate::Error>>()?;
 
-        let response = self
-            .spanner
-            .execute_batch_dml(Request::new(ExecuteBatchDmlRequest {
+        let request = self.leader_request(
+            timeout,
+            Routing::Session(session),
+            ExecuteBatchDmlRequest {
                 session: session.name().to_string(),
                 transaction: Some(selector.clone().try_into()?),
-                statements,
+                statements: encoded,
                 seqno,
-                request_options: None,
-            }))
-            .await?
+                request_options: Some(options.request_options()),
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(options.priority()).await?;
+        let response = self
+            .spanner
+            .execute_batch_dml(request)
+            .await
+            .map_err(|status| id.attach(status))?
             .into_inner();
 
         let status = response
@@ -171,10 +761,16 @@ impl Connection for GrpcConnection {
             .ok_or_else(|| crate::Error::Codec("missing status".to_string()))?;
 
         if status.code != 0 {
-            return Err(crate::Error::Status(tonic::Status::new(
+            let err = Error::from(id.attach(tonic::Status::new(
                 tonic::Code::from_i32(status.code),
                 status.message,
             )));
+            // Statements are executed in order and the first failure stops the batch,
+            // so the failed statement is the one following the last result set.
+            return Err(match statements.get(response.result_sets.len()) {
+                Some(statement) => self.with_statement(err, statement),
+                None => err,
+            });
         };
 
         response
@@ -183,4 +779,247 @@ impl Connection for GrpcConnection {
             .map(|rs| rs.try_into())
             .collect()
     }
+
+    async fn begin_read_only(
+        &mut self,
+        session: &Session,
+        bound: TimestampBound,
+    ) -> Result<Transaction, Error> {
+        let request = self.request(
+            self.timeouts.read,
+            Routing::Session(session),
+            BeginTransactionRequest {
+                session: session.name().to_string(),
+                options: Some(proto::TransactionOptions {
+                    mode: Some(proto::transaction_options::Mode::ReadOnly(
+                        proto::transaction_options::ReadOnly {
+                            return_read_timestamp: true,
+                            timestamp_bound: Some(bound.try_into()?),
+                        },
+                    )),
+                }),
+                request_options: None,
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(None).await?;
+        Ok(self
+            .spanner
+            .begin_transaction(request)
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner()
+            .into())
+    }
+
+    async fn partition_query(
+        &mut self,
+        session: &Session,
+        transaction: &Transaction,
+        statement: &Statement,
+    ) -> Result<Vec<Bytes>, Error> {
+        let (params, param_types) = self.dialect().encode_parameters(statement)?;
+        let request = self.request(
+            self.timeouts.read,
+            Routing::Session(session),
+            PartitionQueryRequest {
+                session: session.name().to_string(),
+                transaction: Some(TransactionSelector::Id(transaction.clone()).try_into()?),
+                sql: statement.sql().to_string(),
+                params: Some(prost_types::Struct { fields: params }),
+                param_types,
+                partition_options: None,
+            },
+        );
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(None).await?;
+        let response = self
+            .spanner
+            .partition_query(request)
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner();
+        Ok(response
+            .partitions
+            .into_iter()
+            .map(|partition| partition.partition_token)
+            .collect())
+    }
+
+    async fn execute_partition(
+        &mut self,
+        session: &Session,
+        transaction: &Transaction,
+        statement: &Statement,
+        token: &Bytes,
+    ) -> Result<ResultSet, Error> {
+        let message = ExecuteSqlRequest {
+            partition_token: token.clone(),
+            ..self.execute_sql_request(
+                session,
+                &TransactionSelector::Id(transaction.clone()),
+                statement,
+            )?
+        };
+        let timeout = statement.deadline().unwrap_or(self.timeouts.read);
+        let request = self.request(timeout, Routing::Session(session), message);
+        let id = RequestId::of(&request);
+        let _permits = self.limiter.acquire(None).await?;
+        self.spanner
+            .execute_sql(request)
+            .await
+            .map_err(|status| id.attach(status))?
+            .into_inner()
+            .try_into()
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use std::convert::Infallible;
+
+    use hyper::service::{make_service_fn, service_fn};
+
+    use super::*;
+
+    /// The access token returned by [`FakeServer`]'s token endpoint.
+    pub(crate) const FAKE_TOKEN: &str = "fake-token";
+
+    /// A local server recording the requests it receives.
+    ///
+    /// gRPC requests fail with `UNIMPLEMENTED`, while `POST /token` returns [`FAKE_TOKEN`] as Google's token endpoint would.
+    pub(crate) struct FakeServer {
+        endpoint: String,
+        requests: Arc<Mutex<Vec<FakeRequest>>>,
+    }
+
+    /// A request received by a [`FakeServer`], without its body.
+    #[derive(Clone, Debug)]
+    pub(crate) struct FakeRequest {
+        pub(crate) path: String,
+        pub(crate) headers: http::HeaderMap,
+    }
+
+    impl FakeRequest {
+        /// Returns the value of the provided header, if present and valid UTF-8.
+        pub(crate) fn header(&self, name: &str) -> Option<&str> {
+            self.headers.get(name)?.to_str().ok()
+        }
+    }
+
+    impl FakeServer {
+        pub(crate) fn start() -> Self {
+            let requests = Arc::new(Mutex::new(Vec::new()));
+            let recorded = requests.clone();
+            let make_service = make_service_fn(move |_| {
+                let recorded = recorded.clone();
+                async move {
+                    Ok::<_, Infallible>(service_fn(move |request: http::Request<hyper::Body>| {
+                        let request = FakeRequest {
+                            path: request.uri().path().to_string(),
+                            headers: request.headers().clone(),
+                        };
+                        let response = if request.path == "/token" {
+                            http::Response::builder()
+                                .header(http::header::CONTENT_TYPE, "application/json")
+                                .body(hyper::Body::from(format!(
+                                    r#"{{"access_token":"{}","token_type":"Bearer","expires_in":3600}}"#,
+                                    FAKE_TOKEN
+                                )))
+                        } else {
+                            http::Response::builder()
+                                .header(http::header::CONTENT_TYPE, "application/grpc")
+                                .header("grpc-status", "12")
+                                .body(hyper::Body::empty())
+                        };
+                        recorded.lock().unwrap().push(request);
+                        async move { Ok::<_, Infallible>(response.unwrap()) }
+                    }))
+                }
+            });
+            let server = hyper::Server::bind(&([127, 0, 0, 1], 0).into()).serve(make_service);
+            let endpoint = format!("http://{}", server.local_addr());
+            tokio::spawn(server);
+            Self { endpoint, requests }
+        }
+
+        /// Returns the URL of this server, e.g.: `http://127.0.0.1:1234`.
+        pub(crate) fn endpoint(&self) -> &str {
+            &self.endpoint
+        }
+
+        /// Returns a channel to this server, which connects when it is first used.
+        pub(crate) fn channel(&self) -> Channel {
+            Endpoint::from_shared(self.endpoint.clone())
+                .unwrap()
+                .connect_lazy()
+        }
+
+        /// Returns the requests received so far.
+        pub(crate) fn requests(&self) -> Vec<FakeRequest> {
+            self.requests.lock().unwrap().clone()
+        }
+
+        /// Returns the gRPC requests received so far, i.e.: excluding the ones made to the token endpoint.
+        pub(crate) fn grpc_requests(&self) -> Vec<FakeRequest> {
+            self.requests()
+                .into_iter()
+                .filter(|request| request.path != "/token")
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_request_id_attach() {
+        let mut request = Request::new(());
+        request
+            .metadata_mut()
+            .insert(REQUEST_ID, AsciiMetadataValue::from_static("1.2.3.1.4.1"));
+
+        let status = RequestId::of(&request).attach(tonic::Status::internal("oops"));
+        assert_eq!(crate::status::request_id(&status), Some("1.2.3.1.4.1"));
+
+        let status = RequestId::of(&Request::new(())).attach(tonic::Status::internal("oops"));
+        assert_eq!(crate::status::request_id(&status), None);
+    }
+
+    #[test]
+    fn test_dialect_cache_backoff() {
+        let now = Instant::now();
+        let cache = DialectCache::new(None);
+        assert!(cache.should_detect(now));
+
+        cache.failed(now);
+        assert!(!cache.should_detect(now));
+        assert!(cache.should_detect(now + Duration::from_secs(1)));
+
+        cache.failed(now);
+        assert!(!cache.should_detect(now + Duration::from_secs(1)));
+        assert!(cache.should_detect(now + Duration::from_secs(2)));
+
+        for _ in 0..100 {
+            cache.failed(now);
+        }
+        assert!(cache.should_detect(now + MAX_DIALECT_RETRY_BACKOFF));
+
+        cache.detected(Dialect::PostgreSql);
+        assert_eq!(cache.get(), Some(Dialect::PostgreSql));
+        assert!(!cache.should_detect(now + MAX_DIALECT_RETRY_BACKOFF));
+    }
+
+    #[test]
+    fn test_dialect_cache_configured() {
+        let cache = DialectCache::new(Some(Dialect::PostgreSql));
+        assert_eq!(cache.get(), Some(Dialect::PostgreSql));
+        assert!(!cache.should_detect(Instant::now()));
+    }
+
+    #[test]
+    fn test_url_encode() {
+        assert_eq!(
+            url_encode("projects/my-project/instances/i_1/databases/db.1~"),
+            "projects%2Fmy-project%2Finstances%2Fi_1%2Fdatabases%2Fdb.1~"
+        );
+        assert_eq!(url_encode("a b=c&é"), "a%20b%3Dc%26%C3%A9");
+    }
 }
@@ -0,0 +1,248 @@
+use std::io::Write;
+
+#[cfg(feature = "temporal")]
+use chrono::SecondsFormat;
+use derive_builder::Builder;
+
+use crate::{Error, ResultSet, Value};
+
+/// Specifies how a [`ResultSet`] is written as CSV, see [`ResultSet::write_csv`].
+///
+/// Values are quoted as per [RFC 4180](https://www.rfc-editor.org/rfc/rfc4180) when they contain the delimiter,
+/// a double quote or a line break. A value that would otherwise be mistaken for `NULL` is also quoted, e.g.: an empty string
+/// when `NULL`s are written as empty cells.
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::CsvOptions;
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// let options = CsvOptions::builder()
+///     .delimiter('\t')
+///     .null("\\N")
+///     .build()?;
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, PartialEq, Eq)]
+#[builder(
+    pattern = "owned",
+    build_fn(error = "crate::Error", validate = "Self::validate")
+)]
+pub struct CsvOptions {
+    /// Specify the character separating values. Defaults to `,`.
+    ///
+    /// Double quotes and line breaks are rejected with an [`Error::Config`], since they could not be told apart from quoted values
+    /// and lines.
+    #[builder(default = "','")]
+    delimiter: char,
+
+    /// Specify whether the first line contains the names of the columns. Defaults to `true`.
+    ///
+    /// Unnamed columns, e.g.: `SELECT 1`, are named after their position, e.g.: `_0`.
+    #[builder(default = "true")]
+    header: bool,
+
+    /// Specify how `NULL` values are written. Defaults to an empty cell.
+    #[builder(setter(into), default)]
+    null: String,
+}
+
+impl CsvOptionsBuilder {
+    fn validate(&self) -> Result<(), Error> {
+        match self.delimiter {
+            Some(delimiter @ ('"' | '\n' | '\r')) => Err(Error::Config(format!(
+                "invalid CSV delimiter: {:?}",
+                delimiter
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl CsvOptions {
+    /// Returns a new [`CsvOptionsBuilder`] for configuring how a [`ResultSet`] is written as CSV.
+    pub fn builder() -> CsvOptionsBuilder {
+        CsvOptionsBuilder::default()
+    }
+
+    fn write_line<'a, W>(
+        &self,
+        writer: &mut W,
+        cells: impl Iterator<Item = Cell<'a>>,
+    ) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        let mut delimiter = [0; 4];
+        let delimiter = self.delimiter.encode_utf8(&mut delimiter);
+        for (index, cell) in cells.enumerate() {
+            if index > 0 {
+                writer.write_all(delimiter.as_bytes())?;
+            }
+            match cell {
+                Cell::Null => writer.write_all(self.null.as_bytes())?,
+                Cell::Text(text) if self.needs_quotes(&text) => {
+                    write!(writer, "\"{}\"", text.replace('"', "\"\""))?
+                }
+                Cell::Text(text) => writer.write_all(text.as_bytes())?,
+            }
+        }
+        writer.write_all(b"\n")
+    }
+
+    fn needs_quotes(&self, text: &str) -> bool {
+        text == self.null
+            || text.contains(|c| c == self.delimiter || c == '"' || c == '\n' || c == '\r')
+    }
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: ',',
+            header: true,
+            null: String::new(),
+        }
+    }
+}
+
+enum Cell<'a> {
+    Null,
+    Text(std::borrow::Cow<'a, str>),
+}
+
+impl<'a> From<&'a Value> for Cell<'a> {
+    fn from(value: &'a Value) -> Self {
+        let text = match value {
+            Value::Null(_) => return Cell::Null,
+            Value::String(s) => return Cell::Text(s.into()),
+            Value::Bool(b) => b.to_string(),
+            Value::Int64(i) => i.to_string(),
+            Value::Float64(f) if f.is_nan() => "NaN".to_string(),
+            Value::Float64(f) if f.is_infinite() && f.is_sign_positive() => "Infinity".to_string(),
+            Value::Float64(f) if f.is_infinite() => "-Infinity".to_string(),
+            Value::Float64(f) => f.to_string(),
+            Value::Bytes(b) => base64::encode(b),
+            #[cfg(feature = "json")]
+            Value::Json(json) => json.to_string(),
+            #[cfg(feature = "numeric")]
+            Value::Numeric(n) => n.to_string(),
+            #[cfg(feature = "temporal")]
+            Value::Timestamp(dt) => dt.to_rfc3339_opts(SecondsFormat::AutoSi, true),
+            #[cfg(feature = "temporal")]
+            Value::Date(d) => d.to_string(),
+            Value::Array(_, _) | Value::Struct(_) => value.to_sql_literal(),
+        };
+        Cell::Text(text.into())
+    }
+}
+
+impl ResultSet {
+    /// Writes the rows of this result set as CSV, e.g.: in export jobs.
+    ///
+    /// Values are written in the same format Cloud Spanner uses to encode them as strings, e.g.: `BYTES` are base64-encoded
+    /// and `TIMESTAMP`s use RFC 3339. Arrays and structs are written as GoogleSQL literals, see [`Value::to_sql_literal`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, CsvOptions, Error, ReadContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query("SELECT id, name FROM person", &[])
+    ///     .await?;
+    /// let file = std::fs::File::create("person.csv").expect("cannot create file");
+    /// rs.write_csv(std::io::BufWriter::new(file), &CsvOptions::default())
+    ///     .expect("cannot write file");
+    /// # Ok(()) }
+    /// ```
+    pub fn write_csv<W>(&self, mut writer: W, options: &CsvOptions) -> std::io::Result<()>
+    where
+        W: Write,
+    {
+        if options.header {
+            let names = self
+                .row_type()
+                .field_names()
+                .enumerate()
+                .map(|(index, name)| match name {
+                    Some(name) => Cell::Text(name.as_str().into()),
+                    None => Cell::Text(format!("_{}", index).into()),
+                });
+            options.write_line(&mut writer, names)?;
+        }
+        for row in self.iter() {
+            let values = (0..self.row_type().fields().len())
+                .map(|index| row.get_unchecked::<&Value, _>(index))
+                .map(Cell::from);
+            options.write_line(&mut writer, values)?;
+        }
+        writer.flush()
+    }
+
+    /// Returns the rows of this result set as CSV, see [`ResultSet::write_csv`].
+    pub fn to_csv(&self, options: &CsvOptions) -> String {
+        let mut csv = Vec::new();
+        self.write_csv(&mut csv, options)
+            .expect("writing to a Vec cannot fail");
+        String::from_utf8(csv).expect("CSV is written as UTF-8")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{StructType, Type};
+
+    fn result_set() -> ResultSet {
+        ResultSet::new(
+            StructType::new(vec![("id", Type::Int64), ("", Type::String)]),
+            vec![
+                vec![Value::Int64(1), Value::String("ferris".to_string())],
+                vec![
+                    Value::Int64(2),
+                    Value::String("say \"hi\", crab".to_string()),
+                ],
+                vec![Value::Int64(3), Value::Null(Type::String)],
+                vec![Value::Int64(4), Value::String(String::new())],
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_to_csv() {
+        assert_eq!(
+            result_set().to_csv(&CsvOptions::default()),
+            "id,_1\n1,ferris\n2,\"say \"\"hi\"\", crab\"\n3,\n4,\"\"\n"
+        );
+    }
+
+    #[test]
+    fn test_to_csv_options() {
+        let options = CsvOptions::builder()
+            .delimiter(';')
+            .header(false)
+            .null("NULL")
+            .build()
+            .unwrap();
+        assert_eq!(
+            result_set().to_csv(&options),
+            "1;ferris\n2;\"say \"\"hi\"\", crab\"\n3;NULL\n4;\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_options_invalid_delimiter() {
+        for delimiter in ['"', '\n', '\r'] {
+            assert!(matches!(
+                CsvOptions::builder().delimiter(delimiter).build(),
+                Err(Error::Config(_))
+            ));
+        }
+    }
+}
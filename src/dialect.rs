@@ -0,0 +1,263 @@
+use std::collections::BTreeMap;
+
+use google_api_proto::google::spanner::v1 as proto;
+use prost_types::Value as SpannerValue;
+
+use crate::{Error, Statement, Type};
+
+// The `TypeAnnotationCode` values used by PostgreSQL-dialect databases.
+const PG_NUMERIC: i32 = 2;
+const PG_JSONB: i32 = 3;
+
+/// The query used to detect a database's dialect.
+pub(crate) const DIALECT_QUERY: &str =
+    "SELECT option_value FROM information_schema.database_options WHERE option_name = 'database_dialect'";
+
+/// The SQL [dialect](https://cloud.google.com/spanner/docs/postgresql-interface) of a Cloud Spanner database.
+///
+/// The dialect determines how statement parameters are named and how their types are encoded:
+///
+/// * `GoogleStandardSql` parameters are named and referenced as `@name` in SQL;
+/// * `PostgreSql` parameters are positional and referenced as `$1`, `$2`, etc. in SQL.
+///   They may be provided using either `$1` or `p1` as their name.
+///
+/// When using a PostgreSQL-dialect database, the following types are mapped:
+///
+/// | PostgreSQL type | [`crate::Type`] |
+/// |-----------------|-----------------|
+/// | `PG.NUMERIC`    | `Type::Numeric` |
+/// | `PG.JSONB`      | `Type::Json`    |
+/// | `PG.OID`        | `Type::Int64`   |
+///
+/// Note that the `NaN` value of `PG.NUMERIC` cannot be represented and will result in a codec error.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Dialect {
+    /// The [GoogleSQL](https://cloud.google.com/spanner/docs/reference/standard-sql/overview) dialect.
+    #[default]
+    GoogleStandardSql,
+    /// The [PostgreSQL](https://cloud.google.com/spanner/docs/reference/postgresql/overview) dialect.
+    PostgreSql,
+}
+
+impl Dialect {
+    /// Returns the dialect matching the `database_dialect` option's value.
+    pub(crate) fn from_option_value(value: &str) -> Self {
+        if value.eq_ignore_ascii_case("POSTGRESQL") {
+            Dialect::PostgreSql
+        } else {
+            Dialect::GoogleStandardSql
+        }
+    }
+
+    fn parameter_name(&self, name: &str) -> String {
+        match (self, name.strip_prefix('$')) {
+            (Dialect::PostgreSql, Some(position)) => format!("p{}", position),
+            _ => name.to_string(),
+        }
+    }
+
+    fn annotate(&self, tpe: &mut proto::Type) {
+        if *self != Dialect::PostgreSql {
+            return;
+        }
+        match proto::TypeCode::from_i32(tpe.code) {
+            Some(proto::TypeCode::Numeric) => tpe.type_annotation = PG_NUMERIC,
+            Some(proto::TypeCode::Json) => tpe.type_annotation = PG_JSONB,
+            Some(proto::TypeCode::Array) => {
+                if let Some(inner) = tpe.array_element_type.as_mut() {
+                    self.annotate(inner);
+                }
+            }
+            Some(proto::TypeCode::Struct) => {
+                if let Some(struct_type) = tpe.struct_type.as_mut() {
+                    for field in struct_type.fields.iter_mut() {
+                        if let Some(field_type) = field.r#type.as_mut() {
+                            self.annotate(field_type);
+                        }
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn encode_type(&self, tpe: &Type) -> proto::Type {
+        let mut tpe: proto::Type = tpe.into();
+        self.annotate(&mut tpe);
+        tpe
+    }
+
+    fn encode_types<'a>(
+        &self,
+        types: impl Iterator<Item = (&'a String, Type)>,
+    ) -> BTreeMap<String, proto::Type> {
+        types
+            .map(|(name, tpe)| (self.parameter_name(name), self.encode_type(&tpe)))
+            .collect()
+    }
+
+    /// Converts the statement's parameters into the values and types expected by Cloud Spanner for this dialect.
+    ///
+    /// The types of [prepared](crate::PreparedStatement) statements are only encoded once per dialect.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn encode_parameters(
+        &self,
+        statement: &Statement,
+    ) -> Result<
+        (
+            BTreeMap<String, SpannerValue>,
+            BTreeMap<String, proto::Type>,
+        ),
+        Error,
+    > {
+        let values = statement.values()?;
+
+        let param_types = match statement.prepared() {
+            Some(prepared) if prepared.matches(values) => prepared
+                .param_types(*self, |types| {
+                    self.encode_types(types.iter().map(|(name, tpe)| (name, tpe.clone())))
+                })
+                .clone(),
+            _ => self.encode_types(
+                values
+                    .iter()
+                    .map(|(name, value)| (name, value.spanner_type())),
+            ),
+        };
+
+        let params = values
+            .iter()
+            .map(|(name, value)| Ok((self.parameter_name(name), value.clone().try_into()?)))
+            .collect::<Result<BTreeMap<String, SpannerValue>, Error>>()?;
+
+        Ok((params, param_types))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_option_value() {
+        assert_eq!(
+            Dialect::from_option_value("POSTGRESQL"),
+            Dialect::PostgreSql
+        );
+        assert_eq!(
+            Dialect::from_option_value("GOOGLE_STANDARD_SQL"),
+            Dialect::GoogleStandardSql
+        );
+    }
+
+    #[test]
+    fn test_parameter_name() {
+        assert_eq!(Dialect::GoogleStandardSql.parameter_name("id"), "id");
+        assert_eq!(Dialect::GoogleStandardSql.parameter_name("$1"), "$1");
+        assert_eq!(Dialect::PostgreSql.parameter_name("$1"), "p1");
+        assert_eq!(Dialect::PostgreSql.parameter_name("p2"), "p2");
+    }
+
+    #[test]
+    fn test_encode_parameters() {
+        let statement = Statement::new("SELECT $1, $2")
+            .bind("$1", 42)
+            .bind("p2", "foo");
+        let (params, param_types) = Dialect::PostgreSql.encode_parameters(&statement).unwrap();
+
+        assert_eq!(params.keys().collect::<Vec<_>>(), vec!["p1", "p2"]);
+        assert_eq!(param_types["p1"].code, proto::TypeCode::Int64 as i32);
+        assert_eq!(param_types["p2"].code, proto::TypeCode::String as i32);
+    }
+
+    #[cfg(feature = "numeric")]
+    #[test]
+    fn test_encode_parameters_annotations() {
+        use bigdecimal::BigDecimal;
+
+        let statement = Statement::new("SELECT $1").bind("$1", BigDecimal::from(42));
+
+        let (_, param_types) = Dialect::PostgreSql.encode_parameters(&statement).unwrap();
+        assert_eq!(param_types["p1"].type_annotation, PG_NUMERIC);
+
+        let (_, param_types) = Dialect::GoogleStandardSql
+            .encode_parameters(&statement)
+            .unwrap();
+        assert_eq!(
+            param_types["$1"].type_annotation,
+            proto::TypeAnnotationCode::Unspecified as i32
+        );
+    }
+
+    #[test]
+    fn test_encode_parameters_prepared() {
+        let prepared = Statement::new("SELECT $1").bind("$1", 0).prepare();
+
+        for value in 1..3 {
+            let (params, param_types) = Dialect::PostgreSql
+                .encode_parameters(&prepared.bind("$1", value))
+                .unwrap();
+            assert_eq!(params.keys().collect::<Vec<_>>(), vec!["p1"]);
+            assert_eq!(param_types["p1"].code, proto::TypeCode::Int64 as i32);
+        }
+        let encoded = prepared.as_ref().prepared().unwrap();
+        assert_eq!(
+            encoded
+                .param_types(Dialect::PostgreSql, |_| unreachable!("already encoded"))
+                .keys()
+                .collect::<Vec<_>>(),
+            vec!["p1"]
+        );
+
+        // the types are encoded for each dialect
+        let (_, param_types) = Dialect::GoogleStandardSql
+            .encode_parameters(&prepared.statement())
+            .unwrap();
+        assert_eq!(param_types.keys().collect::<Vec<_>>(), vec!["$1"]);
+
+        // binding a different type falls back to encoding the statement's types
+        let (_, param_types) = Dialect::PostgreSql
+            .encode_parameters(&prepared.bind("$1", "one"))
+            .unwrap();
+        assert_eq!(param_types["p1"].code, proto::TypeCode::String as i32);
+    }
+
+    #[test]
+    fn test_encode_parameters_struct() {
+        use crate::{Struct, StructType, Type, Value};
+
+        let key_type = StructType::new(vec![("id", Type::Int64), ("name", Type::String)]);
+        let key = Value::Struct(Struct::new(
+            key_type.clone(),
+            vec![Value::Int64(42), Value::String("ferris".to_string())],
+        ));
+        let statement = Statement::new("SELECT @key, @keys, @nothing")
+            .bind_value("key", key.clone())
+            .bind_value(
+                "keys",
+                Value::Array(Type::Struct(key_type.clone()), vec![key]),
+            )
+            .bind_value("nothing", Value::Null(Type::Struct(key_type)));
+        let (_, param_types) = Dialect::GoogleStandardSql
+            .encode_parameters(&statement)
+            .unwrap();
+
+        let field_names = |tpe: &proto::Type| {
+            tpe.struct_type
+                .as_ref()
+                .unwrap()
+                .fields
+                .iter()
+                .map(|field| field.name.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(param_types["key"].code, proto::TypeCode::Struct as i32);
+        assert_eq!(field_names(&param_types["key"]), vec!["id", "name"]);
+        assert_eq!(param_types["keys"].code, proto::TypeCode::Array as i32);
+        let element_type = param_types["keys"].array_element_type.as_ref().unwrap();
+        assert_eq!(element_type.code, proto::TypeCode::Struct as i32);
+        assert_eq!(field_names(element_type), vec!["id", "name"]);
+        assert_eq!(param_types["nothing"].code, proto::TypeCode::Struct as i32);
+        assert_eq!(field_names(&param_types["nothing"]), vec!["id", "name"]);
+    }
+}
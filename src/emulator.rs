@@ -0,0 +1,110 @@
+use serde_json::json;
+
+use crate::{DatabaseId, Error, InstanceId, SpannerResource};
+
+/// A client of the [Cloud Spanner emulator](https://cloud.google.com/spanner/docs/emulator)'s REST endpoint,
+/// used to create the instances and databases the emulator starts without, e.g.: in integration tests.
+///
+/// Note that this uses the emulator's REST port (`9020` by default), not its gRPC port (`9010` by default).
+///
+/// This requires the `emulator` crate feature.
+///
+/// # Example
+///
+/// ```no_run
+/// # use spanner_rs::{Client, DatabaseId, EmulatorClient, Error, InstanceId, ProjectId};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// let instance = InstanceId::new(ProjectId::new("test-project"), "test-instance");
+/// let database = DatabaseId::new(instance.clone(), "test-database");
+///
+/// let emulator = EmulatorClient::new("http://localhost:9020");
+/// emulator.create_instance(&instance).await?;
+/// emulator
+///     .create_database(
+///         &database,
+///         &["CREATE TABLE person(id INT64, name STRING(MAX)) PRIMARY KEY(id)"],
+///     )
+///     .await?;
+///
+/// let client = Client::configure()
+///     .with_emulator_grpc_port(9010)
+///     .project("test-project")
+///     .instance("test-instance")
+///     .database("test-database")
+///     .connect()
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct EmulatorClient {
+    http: reqwest::Client,
+    endpoint: String,
+}
+
+impl EmulatorClient {
+    /// Creates a client of the emulator's REST endpoint, e.g.: `http://localhost:9020`.
+    pub fn new(endpoint: impl Into<String>) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Creates a client of an emulator whose REST endpoint listens on the provided port of `localhost`.
+    pub fn with_port(port: u16) -> Self {
+        Self::new(format!("http://localhost:{}", port))
+    }
+
+    /// Creates the provided instance.
+    pub async fn create_instance(&self, instance: &InstanceId) -> Result<(), Error> {
+        self.send(
+            self.http
+                .post(self.url(&instance.resources_path()))
+                .json(&json!({ "instanceId": instance.name() })),
+        )
+        .await
+    }
+
+    /// Creates the provided database, applying the DDL statements once it is created.
+    ///
+    /// The database's instance must exist, see [`EmulatorClient::create_instance`].
+    pub async fn create_database(&self, database: &DatabaseId, ddl: &[&str]) -> Result<(), Error> {
+        self.send(
+            self.http
+                .post(self.url(&database.resources_path()))
+                .json(&json!({
+                    "createStatement": format!("CREATE DATABASE `{}`", database.name()),
+                    "extraStatements": ddl,
+                })),
+        )
+        .await
+    }
+
+    /// Drops the provided database and all of its data.
+    pub async fn drop_database(&self, database: &DatabaseId) -> Result<(), Error> {
+        self.send(self.http.delete(self.url(&database.id()))).await
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/v1/{}", self.endpoint, path)
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder) -> Result<(), Error> {
+        let response = request.send().await.map_err(emulator_error)?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(())
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(Error::Client(format!(
+                "emulator request failed with status {}: {}",
+                status, body
+            )))
+        }
+    }
+}
+
+fn emulator_error(error: reqwest::Error) -> Error {
+    Error::Client(format!("emulator request failed: {}", error))
+}
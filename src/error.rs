@@ -1,10 +1,13 @@
 use std::num::TryFromIntError;
+use std::time::Duration;
 
 use bb8::RunError;
 #[cfg(feature = "temporal")]
 use chrono::ParseError;
 use derive_builder::UninitializedFieldError;
 
+use crate::Statement;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("spanner client error: {0}")]
@@ -20,7 +23,258 @@ pub enum Error {
     TransportError(#[from] tonic::transport::Error),
 
     #[error("unexpected gRPC status: {0}")]
-    Status(#[from] tonic::Status),
+    Status(tonic::Status),
+
+    /// Cloud Spanner aborted the transaction, typically due to a conflict with another transaction.
+    ///
+    /// Read/write transactions run using [`crate::TxRunner::run`] are retried automatically when this occurs.
+    #[error("transaction aborted: {}", .0.message())]
+    Aborted(tonic::Status),
+
+    /// The session used for the request does not exist, e.g.: it was deleted after being idle for too long.
+    #[error("session not found: {}", .0.message())]
+    SessionNotFound(tonic::Status),
+
+    /// An error returned by a read/write transaction along with the statements executed by each of its attempts.
+    #[cfg(feature = "replay-log")]
+    #[error("{source}\n{log}")]
+    WithReplayLog {
+        /// The error returned by the transaction.
+        source: Box<Error>,
+        /// The statements executed by the transaction.
+        log: crate::ReplayLog,
+    },
+
+    /// Cloud Spanner rejected the request because a quota was exceeded.
+    #[error("quota exceeded: {quota}")]
+    QuotaExceeded {
+        /// A description of the exceeded quota(s).
+        quota: String,
+        /// The delay recommended by Cloud Spanner before retrying, if any.
+        retry_after: Option<Duration>,
+        /// The `RESOURCE_EXHAUSTED` status returned by Cloud Spanner.
+        status: tonic::Status,
+    },
+
+    /// An error returned while executing a statement along with the statement itself,
+    /// see [`crate::ConfigBuilder::statement_error_context`].
+    ///
+    /// Only the names of the statement's parameters are included, their values are not.
+    #[error("{source} (statement: {sql}, parameters: [{}])", .parameters.join(", "))]
+    WithStatement {
+        /// The error returned while executing the statement.
+        source: Box<Error>,
+        /// The SQL text of the statement.
+        sql: String,
+        /// The names of the statement's parameters, sorted.
+        parameters: Vec<String>,
+    },
+}
+
+/// The canonical error codes returned by Cloud Spanner.
+///
+/// See [the gRPC documentation](https://grpc.github.io/grpc/core/md_doc_statuscodes.html) for the meaning of each code.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ErrorCode {
+    Cancelled,
+    Unknown,
+    InvalidArgument,
+    DeadlineExceeded,
+    NotFound,
+    AlreadyExists,
+    PermissionDenied,
+    ResourceExhausted,
+    FailedPrecondition,
+    Aborted,
+    OutOfRange,
+    Unimplemented,
+    Internal,
+    Unavailable,
+    DataLoss,
+    Unauthenticated,
+}
+
+impl ErrorCode {
+    fn from_code(code: tonic::Code) -> Option<Self> {
+        match code {
+            tonic::Code::Ok => None,
+            tonic::Code::Cancelled => Some(ErrorCode::Cancelled),
+            tonic::Code::Unknown => Some(ErrorCode::Unknown),
+            tonic::Code::InvalidArgument => Some(ErrorCode::InvalidArgument),
+            tonic::Code::DeadlineExceeded => Some(ErrorCode::DeadlineExceeded),
+            tonic::Code::NotFound => Some(ErrorCode::NotFound),
+            tonic::Code::AlreadyExists => Some(ErrorCode::AlreadyExists),
+            tonic::Code::PermissionDenied => Some(ErrorCode::PermissionDenied),
+            tonic::Code::ResourceExhausted => Some(ErrorCode::ResourceExhausted),
+            tonic::Code::FailedPrecondition => Some(ErrorCode::FailedPrecondition),
+            tonic::Code::Aborted => Some(ErrorCode::Aborted),
+            tonic::Code::OutOfRange => Some(ErrorCode::OutOfRange),
+            tonic::Code::Unimplemented => Some(ErrorCode::Unimplemented),
+            tonic::Code::Internal => Some(ErrorCode::Internal),
+            tonic::Code::Unavailable => Some(ErrorCode::Unavailable),
+            tonic::Code::DataLoss => Some(ErrorCode::DataLoss),
+            tonic::Code::Unauthenticated => Some(ErrorCode::Unauthenticated),
+        }
+    }
+}
+
+impl Error {
+    /// Returns the code of the error returned by Cloud Spanner, if any.
+    ///
+    /// Errors that originate from the client itself (e.g.: configuration or codec errors) have no code.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ErrorCode, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let result = client
+    ///     .read_write()
+    ///     .run(|tx| tx.execute_update("INSERT INTO person(id) VALUES(42)", &[]))
+    ///     .await;
+    /// match result {
+    ///     Err(err) if err.code() == Some(ErrorCode::AlreadyExists) => println!("already inserted"),
+    ///     other => println!("{:?}", other?),
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn code(&self) -> Option<ErrorCode> {
+        match self {
+            #[cfg(feature = "replay-log")]
+            Error::WithReplayLog { source, .. } => source.code(),
+            Error::WithStatement { source, .. } => source.code(),
+            _ => self
+                .status()
+                .and_then(|status| ErrorCode::from_code(status.code())),
+        }
+    }
+
+    /// Returns true when the failed operation may be attempted again, possibly after waiting for some time.
+    ///
+    /// This is the case for aborted transactions, missing sessions, exceeded quotas and transient transport failures.
+    /// Note that the whole operation should be retried (e.g.: a complete transaction), not only the last statement.
+    ///
+    /// Transactions run using [`crate::TxRunner::run`] are already retried when they are aborted,
+    /// so this is mostly useful for application-level retry loops.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Aborted(_) | Error::SessionNotFound(_) | Error::QuotaExceeded { .. } => true,
+            Error::TransportError(_) => true,
+            #[cfg(feature = "replay-log")]
+            Error::WithReplayLog { source, .. } => source.is_retryable(),
+            Error::WithStatement { source, .. } => source.is_retryable(),
+            Error::Status(status) => match status.code() {
+                tonic::Code::Unavailable | tonic::Code::ResourceExhausted => true,
+                // the stream was reset by an intermediate proxy, the request never reached Cloud Spanner
+                tonic::Code::Internal => {
+                    status.message().contains("RST_STREAM")
+                        || status
+                            .message()
+                            .contains("Received unexpected EOS on DATA frame")
+                }
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns the id of the request that failed, if the error was returned by Cloud Spanner.
+    ///
+    /// Each request sent to Cloud Spanner has a unique id (in the `x-goog-spanner-request-id` metadata)
+    /// which should be logged along with the error, it allows correlating the error with Cloud Spanner's side of the request,
+    /// e.g.: in support cases.
+    pub fn request_id(&self) -> Option<&str> {
+        self.status().and_then(crate::status::request_id)
+    }
+
+    /// Returns the underlying gRPC status, if any.
+    pub fn status(&self) -> Option<&tonic::Status> {
+        match self {
+            Error::Status(status)
+            | Error::Aborted(status)
+            | Error::SessionNotFound(status)
+            | Error::QuotaExceeded { status, .. } => Some(status),
+            #[cfg(feature = "replay-log")]
+            Error::WithReplayLog { source, .. } => source.status(),
+            Error::WithStatement { source, .. } => source.status(),
+            _ => None,
+        }
+    }
+
+    /// Returns the details attached by Cloud Spanner to the error, if any, e.g.: which field of the request was invalid.
+    ///
+    /// Details are only available for errors holding a gRPC status, see [`Error::status`].
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, TransactionContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let result = client
+    ///     .read_write()
+    ///     .run(|tx| tx.execute_update("INSERT INTO person(id) VALUES(42)", &[]))
+    ///     .await;
+    /// if let Some(details) = result.as_ref().err().and_then(Error::details) {
+    ///     for violation in details.field_violations {
+    ///         println!("invalid field {}: {}", violation.field, violation.description);
+    ///     }
+    /// }
+    /// # Ok(()) }
+    /// ```
+    pub fn details(&self) -> Option<crate::ErrorDetails> {
+        self.status().and_then(crate::status::ErrorDetails::decode)
+    }
+
+    /// Wraps this error in an [`Error::WithStatement`].
+    ///
+    /// Aborted transactions, missing sessions and exceeded quotas are left as-is since the client handles them itself,
+    /// by retrying the transaction, renewing the session or backing off.
+    pub(crate) fn with_statement(self, statement: &Statement) -> Self {
+        match self {
+            Error::Aborted(_)
+            | Error::SessionNotFound(_)
+            | Error::QuotaExceeded { .. }
+            | Error::WithStatement { .. } => self,
+            source => {
+                let mut parameters = statement
+                    .params()
+                    .map(|(name, _)| name.to_string())
+                    .collect::<Vec<_>>();
+                parameters.sort();
+                Error::WithStatement {
+                    source: Box::new(source),
+                    sql: statement.sql().to_string(),
+                    parameters,
+                }
+            }
+        }
+    }
+}
+
+impl From<tonic::Status> for Error {
+    fn from(status: tonic::Status) -> Self {
+        match status.code() {
+            tonic::Code::ResourceExhausted => {
+                if let Some(quota) = crate::status::quota_failure(&status) {
+                    return Error::QuotaExceeded {
+                        quota,
+                        retry_after: crate::status::retry_delay(&status),
+                        status,
+                    };
+                }
+            }
+            tonic::Code::Aborted => return Error::Aborted(status),
+            tonic::Code::NotFound if crate::status::is_session_not_found(&status) => {
+                return Error::SessionNotFound(status)
+            }
+            _ => (),
+        }
+        Error::Status(status)
+    }
 }
 
 impl From<RunError<Error>> for Error {
@@ -57,3 +311,179 @@ impl From<serde_json::Error> for Error {
         Error::Codec(format!("unexpected json value: {}", err))
     }
 }
+
+#[cfg(feature = "uuid")]
+impl From<uuid::Error> for Error {
+    fn from(err: uuid::Error) -> Self {
+        Error::Codec(format!("unexpected uuid value: {}", err))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::status::test::{
+        quota_failure_detail, retry_info, session_resource_info, status_with_details,
+    };
+    use tonic::Code;
+
+    #[test]
+    fn test_from_status_quota_exceeded() {
+        let status = status_with_details(
+            Code::ResourceExhausted,
+            vec![
+                quota_failure_detail("project:my-project", "too many requests"),
+                retry_info(Duration::from_secs(2)),
+            ],
+        );
+        match Error::from(status) {
+            Error::QuotaExceeded {
+                quota,
+                retry_after,
+                status,
+            } => {
+                assert_eq!(quota, "project:my-project: too many requests");
+                assert_eq!(retry_after, Some(Duration::from_secs(2)));
+                assert_eq!(status.code(), Code::ResourceExhausted);
+            }
+            other => panic!("unexpected error {:?}", other),
+        }
+
+        match Error::from(tonic::Status::resource_exhausted("exhausted")) {
+            Error::Status(status) => assert_eq!(status.code(), Code::ResourceExhausted),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_status_dedicated_variants() {
+        let err = Error::from(tonic::Status::aborted("conflict"));
+        assert!(matches!(err, Error::Aborted(_)));
+        assert_eq!(err.code(), Some(ErrorCode::Aborted));
+
+        let err = Error::from(status_with_details(
+            Code::NotFound,
+            vec![session_resource_info("sessions/s")],
+        ));
+        assert!(matches!(err, Error::SessionNotFound(_)));
+        assert_eq!(err.code(), Some(ErrorCode::NotFound));
+
+        let err = Error::from(tonic::Status::not_found("Table not found: person"));
+        assert!(matches!(err, Error::Status(_)));
+        assert_eq!(err.code(), Some(ErrorCode::NotFound));
+    }
+
+    #[test]
+    fn test_request_id() {
+        let mut status = tonic::Status::aborted("conflict");
+        assert_eq!(Error::from(status.clone()).request_id(), None);
+
+        status.metadata_mut().insert(
+            crate::status::REQUEST_ID,
+            tonic::metadata::AsciiMetadataValue::from_static("1.2.3.1.4.1"),
+        );
+        assert_eq!(Error::from(status).request_id(), Some("1.2.3.1.4.1"));
+        assert_eq!(Error::Client("oops".to_string()).request_id(), None);
+
+        let mut quota = status_with_details(
+            Code::ResourceExhausted,
+            vec![quota_failure_detail(
+                "project:my-project",
+                "too many requests",
+            )],
+        );
+        quota.metadata_mut().insert(
+            crate::status::REQUEST_ID,
+            tonic::metadata::AsciiMetadataValue::from_static("1.2.3.1.5.1"),
+        );
+        let err = Error::from(quota);
+        assert!(matches!(err, Error::QuotaExceeded { .. }));
+        assert_eq!(err.request_id(), Some("1.2.3.1.5.1"));
+    }
+
+    #[test]
+    fn test_code() {
+        assert_eq!(
+            Error::from(tonic::Status::already_exists("row exists")).code(),
+            Some(ErrorCode::AlreadyExists)
+        );
+        assert_eq!(
+            Error::QuotaExceeded {
+                quota: "quota".to_string(),
+                retry_after: None,
+                status: tonic::Status::resource_exhausted("quota"),
+            }
+            .code(),
+            Some(ErrorCode::ResourceExhausted)
+        );
+        assert_eq!(Error::Client("client".to_string()).code(), None);
+        assert_eq!(Error::Status(tonic::Status::ok("ok")).code(), None);
+    }
+
+    #[test]
+    fn test_details() {
+        let err = Error::from(status_with_details(
+            Code::NotFound,
+            vec![session_resource_info("sessions/s")],
+        ));
+        let details = err.details().unwrap();
+        assert_eq!(details.resource_info.unwrap().resource_name, "sessions/s");
+        assert_eq!(Error::Client("oops".to_string()).details(), None);
+
+        let err = Error::from(status_with_details(
+            Code::ResourceExhausted,
+            vec![quota_failure_detail(
+                "project:my-project",
+                "too many requests",
+            )],
+        ));
+        assert!(matches!(err, Error::QuotaExceeded { .. }));
+        let violations = err.details().unwrap().quota_violations;
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].subject, "project:my-project");
+    }
+
+    #[test]
+    fn test_with_statement() {
+        let statement = Statement::new("SELECT * FROM person WHERE id = @id AND name = @name")
+            .bind("name", "ferris")
+            .bind("id", 42);
+
+        let err =
+            Error::from(tonic::Status::invalid_argument("bad query")).with_statement(&statement);
+        assert_eq!(err.code(), Some(ErrorCode::InvalidArgument));
+        assert!(err.to_string().ends_with(
+            " (statement: SELECT * FROM person WHERE id = @id AND name = @name, parameters: [id, name])"
+        ));
+        assert!(!err.to_string().contains("ferris"));
+
+        let err = Error::from(tonic::Status::aborted("conflict")).with_statement(&statement);
+        assert!(matches!(err, Error::Aborted(_)));
+
+        let quota = status_with_details(
+            Code::ResourceExhausted,
+            vec![quota_failure_detail(
+                "project:my-project",
+                "too many requests",
+            )],
+        );
+        let err = Error::from(quota).with_statement(&statement);
+        assert!(matches!(err, Error::QuotaExceeded { .. }));
+    }
+
+    #[test]
+    fn test_is_retryable() {
+        assert!(Error::from(tonic::Status::aborted("conflict")).is_retryable());
+        assert!(Error::from(tonic::Status::not_found("Session not found: s")).is_retryable());
+        assert!(Error::from(tonic::Status::unavailable("unavailable")).is_retryable());
+        assert!(Error::from(tonic::Status::internal(
+            "stream terminated by RST_STREAM with error code: PROTOCOL_ERROR"
+        ))
+        .is_retryable());
+
+        assert!(!Error::from(tonic::Status::internal("internal")).is_retryable());
+        assert!(!Error::from(tonic::Status::not_found("Table not found: person")).is_retryable());
+        assert!(!Error::from(tonic::Status::already_exists("row exists")).is_retryable());
+        assert!(!Error::Codec("codec".to_string()).is_retryable());
+    }
+}
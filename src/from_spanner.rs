@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 #[cfg(feature = "numeric")]
 use bigdecimal::BigDecimal;
 use prost::bytes::Bytes;
@@ -13,9 +15,9 @@ use crate::{Error, Type, Value};
 /// | Rust Type | Spanner Type |
 /// |---|---|
 /// | `bool` | [`BOOL`](https://cloud.google.com/spanner/docs/data-types#boolean_type) |
-/// | `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `i64` | [`INT64`](https://cloud.google.com/spanner/docs/data-types#integer_type) |
+/// | `u8`, `i8`, `u16`, `i16`, `u32`, `i32`, `i64`, `u64`, `i128`, `u128` | [`INT64`](https://cloud.google.com/spanner/docs/data-types#integer_type) |
 /// | `f64` | [`FLOAT64`](https://cloud.google.com/spanner/docs/data-types#floating_point_types) |
-/// | `&str`, `String` | [`STRING`](https://cloud.google.com/spanner/docs/data-types#string_type) |
+/// | `&str`, `String`, `Cow<str>` | [`STRING`](https://cloud.google.com/spanner/docs/data-types#string_type) |
 /// | `&[u8]`, `Bytes` | [`BYTES`](https://cloud.google.com/spanner/docs/data-types#bytes_type) |
 ///
 /// The following are provided when the corresponding feature is enabled:
@@ -26,6 +28,7 @@ use crate::{Error, Type, Value};
 /// | `numeric` | `bigdecimal::BigDecimal` | [`NUMERIC`](https://cloud.google.com/spanner/docs/data-types#numeric_type) |
 /// | `temporal` | `chrono::DateTime<Utc>` | [`TIMESTAMP`](https://cloud.google.com/spanner/docs/data-types#timestamp_type) |
 /// | `temporal` | `chrono::NaiveDate` | [`DATE`](https://cloud.google.com/spanner/docs/data-types#date_type) |
+/// | `uuid` | `uuid::Uuid` | [`STRING`](https://cloud.google.com/spanner/docs/data-types#string_type) |
 ///
 /// # Nullability
 ///
@@ -37,6 +40,44 @@ use crate::{Error, Type, Value};
 /// `FromSpanner` is implemented for `Vec<T>` when `T` implements `FromSpanner`.
 /// Such values map to Spanner's [`Array`](https://cloud.google.com/spanner/docs/data-types#array_type) type.
 /// Arrays may contain `null` values (i.e.: `Vec<Option<T>>`). Note that `Vec<Vec<T>>` is not allowed.
+///
+/// # Integers
+///
+/// Integer types are range checked: an [`Error::Codec`] is returned when a value does not fit the requested type,
+/// e.g.: when reading a negative value into a `u64`, which is convenient for schemas storing unsigned IDs in `INT64` columns.
+///
+/// # UUIDs
+///
+/// `uuid::Uuid` can be read from `STRING` columns containing any of the textual formats supported by [`uuid::Uuid::parse_str`]
+/// as well as from `BYTES` columns containing exactly 16 bytes.
+///
+/// # Custom types
+///
+/// Applications can implement this trait (and [`crate::ToSpanner`]) for their own types, which makes them usable
+/// with [`crate::Row::get`] as well as in `Option<T>` and `Vec<T>`.
+///
+/// Due to Rust's orphan rules, a newtype is only required for types defined in other crates.
+///
+/// ```
+/// use spanner_rs::{Error, FromSpanner, Type, Value};
+///
+/// /// A secret that is never stored in plain text.
+/// struct Secret(String);
+///
+/// impl<'a> FromSpanner<'a> for Secret {
+///     fn from_spanner(value: &'a Value) -> Result<Self, Error> {
+///         // a real implementation would use a proper encryption scheme
+///         match value {
+///             Value::String(encrypted) => Ok(Secret(encrypted.chars().rev().collect())),
+///             other => Err(Error::Codec(format!("unexpected type {:?}", other.spanner_type()))),
+///         }
+///     }
+/// }
+///
+/// let secret = Secret::from_spanner(&Value::String("sirref".to_string()))?;
+/// assert_eq!(secret.0, "ferris");
+/// # Ok::<(), Error>(())
+/// ```
 pub trait FromSpanner<'a>: Sized {
     /// Creates a new value of this type from the provided Cloud Spanner value.
     /// Values passed to this method should not be `Value::Null`, if this is not known to be the case, use [FromSpanner::from_spanner_nullable] instead.
@@ -72,6 +113,17 @@ where
     }
 }
 
+/// Returns the value as-is, including `NULL` values, e.g.: for tools that render values of any type.
+impl<'a> FromSpanner<'a> for &'a Value {
+    fn from_spanner(value: &'a Value) -> Result<Self, Error> {
+        Ok(value)
+    }
+
+    fn from_spanner_nullable(value: &'a Value) -> Result<Self, Error> {
+        Ok(value)
+    }
+}
+
 macro_rules! wrong_type {
     ($expect:ident, $tpe:expr) => {
         Err(Error::Codec(format!(
@@ -115,6 +167,24 @@ impl<'a> FromSpanner<'a> for &'a str {
     }
 }
 
+/// Borrows the value, use [`Cow::into_owned`] to own it when needed.
+impl<'a> FromSpanner<'a> for Cow<'a, str> {
+    fn from_spanner(value: &'a Value) -> Result<Self, Error> {
+        <&str as FromSpanner>::from_spanner(value).map(Cow::Borrowed)
+    }
+}
+
+#[cfg(feature = "uuid")]
+impl<'a> FromSpanner<'a> for uuid::Uuid {
+    fn from_spanner(value: &'a Value) -> Result<Self, Error> {
+        match value {
+            Value::String(v) => Ok(uuid::Uuid::parse_str(v)?),
+            Value::Bytes(v) => Ok(uuid::Uuid::from_slice(v)?),
+            _ => wrong_type!(String, value.spanner_type()),
+        }
+    }
+}
+
 macro_rules! simple {
     ($t:ty, $f:ident, TryFrom::try_from) => {
         impl<'a> FromSpanner<'a> for $t {
@@ -146,6 +216,11 @@ where
     *value
 }
 
+#[inline]
+fn widen(value: &i64) -> i128 {
+    i128::from(*value)
+}
+
 simple!(i8, Int64, TryFrom::try_from);
 simple!(u8, Int64, TryFrom::try_from);
 simple!(i16, Int64, TryFrom::try_from);
@@ -153,6 +228,9 @@ simple!(u16, Int64, TryFrom::try_from);
 simple!(i32, Int64, TryFrom::try_from);
 simple!(u32, Int64, TryFrom::try_from);
 simple!(i64, Int64, copy);
+simple!(u64, Int64, TryFrom::try_from);
+simple!(i128, Int64, widen);
+simple!(u128, Int64, TryFrom::try_from);
 simple!(f64, Float64, copy);
 simple!(bool, Bool, copy);
 #[cfg(feature = "numeric")]
@@ -249,6 +327,22 @@ mod test {
         from_spanner_int64!(i8, u8, i16, u16, i32, u32, i64);
     }
 
+    #[test]
+    fn test_from_spanner_wide_int64() {
+        let max = Value::Int64(i64::MAX);
+        assert_eq!(u64::from_spanner(&max).ok(), Some(i64::MAX as u64));
+        assert_eq!(u128::from_spanner(&max).ok(), Some(i64::MAX as u128));
+        assert_eq!(
+            i128::from_spanner(&Value::Int64(i64::MIN)).ok(),
+            Some(i64::MIN as i128)
+        );
+        from_spanner_err!(u64, Int64, -1, i64::MIN);
+        from_spanner_err!(u128, Int64, -1);
+        from_spanner_err!(u64, String, "this is not an int64".to_string());
+        from_spanner_non_nullable!(u64, Int64);
+        from_spanner_nullable!(i128, Int64);
+    }
+
     #[test]
     fn test_from_spanner_bool() {
         from_spanner_ok!(bool, Bool, true, false);
@@ -313,6 +407,27 @@ mod test {
         from_spanner_nullable!(BigDecimal, Numeric);
     }
 
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_spanner_json() {
+        use serde_json::json;
+
+        from_spanner_ok!(
+            serde_json::Value,
+            Json,
+            json!({"foo": "bar", "baz": [1, 2, 3]}),
+            json!(null)
+        );
+        from_spanner_err!(serde_json::Value, String, "{}".to_string());
+        from_spanner_err!(serde_json::Value, Int64, 0);
+        from_spanner_non_nullable!(serde_json::Value, Json);
+        from_spanner_nullable!(serde_json::Value, Json);
+
+        let value = Value::Json(json!({"foo": "bar"}));
+        let borrowed = <&serde_json::Value as FromSpanner>::from_spanner_nullable(&value);
+        assert_eq!(borrowed.ok(), Some(&json!({"foo": "bar"})));
+    }
+
     #[test]
     fn test_from_spanner_array() {
         let bool_array = Type::Array(Box::new(Type::Bool));
@@ -357,4 +472,33 @@ mod test {
         from_spanner_non_nullable!(String, String);
         from_spanner_nullable!(String, String);
     }
+
+    #[test]
+    fn test_from_spanner_cow_str() {
+        let value = Value::String("ferris".to_string());
+        let cow = <Cow<str> as FromSpanner>::from_spanner(&value).unwrap();
+        assert!(matches!(cow, Cow::Borrowed("ferris")));
+        from_spanner_err!(Cow<str>, Int64, 0);
+        from_spanner_non_nullable!(Cow<str>, String);
+        from_spanner_nullable!(Cow<str>, String);
+    }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_from_spanner_uuid() {
+        let uuid = uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        let result = <uuid::Uuid as FromSpanner>::from_spanner_nullable(&Value::String(
+            "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string(),
+        ));
+        assert_eq!(result.ok(), Some(uuid));
+        let result = <uuid::Uuid as FromSpanner>::from_spanner_nullable(&Value::Bytes(
+            Bytes::copy_from_slice(uuid.as_bytes()),
+        ));
+        assert_eq!(result.ok(), Some(uuid));
+        from_spanner_err!(uuid::Uuid, String, "this is not a uuid".to_string());
+        from_spanner_err!(uuid::Uuid, Bytes, Bytes::from_static(&[1, 2, 3, 4]));
+        from_spanner_err!(uuid::Uuid, Int64, 0);
+        from_spanner_non_nullable!(uuid::Uuid, String);
+        from_spanner_nullable!(uuid::Uuid, String);
+    }
 }
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use tonic::Code;
+
+use crate::Error;
+
+/// The query sent to Cloud Spanner when running a health check.
+pub(crate) const HEALTH_CHECK_QUERY: &str = "SELECT 1";
+
+/// The outcome of [`crate::Client::health_check`].
+///
+/// A health check goes through several steps which each depend on the previous one succeeding:
+///
+/// 1. authentication: a token is obtained for the configured credentials;
+/// 2. channel: the gRPC channel to Cloud Spanner is usable;
+/// 3. session: a session is obtained from the pool (or created) and Cloud Spanner accepts it;
+/// 4. query: a trivial query is executed using the session.
+///
+/// When a step fails, the subsequent steps are reported as failed and the error is available using [`HealthReport::error`].
+#[derive(Debug)]
+pub struct HealthReport {
+    reached: Step,
+    latency: Option<Duration>,
+    error: Option<Error>,
+}
+
+/// The steps of a health check, in the order they are attempted.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum Step {
+    Auth,
+    Channel,
+    Session,
+    Query,
+    Done,
+}
+
+impl HealthReport {
+    pub(crate) fn healthy(latency: Duration) -> Self {
+        Self {
+            reached: Step::Done,
+            latency: Some(latency),
+            error: None,
+        }
+    }
+
+    /// Creates a report for an error that occurred during the provided step.
+    ///
+    /// The error's kind takes precedence over the step, e.g.: an authentication error may occur when creating a session.
+    pub(crate) fn failed(during: Step, error: Error) -> Self {
+        Self {
+            reached: Step::of(&error).unwrap_or(during),
+            latency: None,
+            error: Some(error),
+        }
+    }
+
+    /// Returns true when every step of the health check succeeded.
+    pub fn is_healthy(&self) -> bool {
+        self.reached == Step::Done
+    }
+
+    /// Returns true when credentials were successfully obtained.
+    pub fn auth_ok(&self) -> bool {
+        self.reached > Step::Auth
+    }
+
+    /// Returns true when the gRPC channel to Cloud Spanner is usable.
+    pub fn channel_ok(&self) -> bool {
+        self.reached > Step::Channel
+    }
+
+    /// Returns true when a session was obtained and accepted by Cloud Spanner.
+    pub fn session_ok(&self) -> bool {
+        self.reached > Step::Session
+    }
+
+    /// Returns the round-trip latency of the health check query, when it succeeded.
+    pub fn latency(&self) -> Option<Duration> {
+        self.latency
+    }
+
+    /// Returns the error that made the health check fail, if any.
+    pub fn error(&self) -> Option<&Error> {
+        self.error.as_ref()
+    }
+
+    /// Consumes this report and returns the error that made the health check fail, if any.
+    pub fn into_error(self) -> Option<Error> {
+        self.error
+    }
+}
+
+impl Step {
+    /// Returns the step that the error is attributable to, if it can be determined from the error itself.
+    fn of(error: &Error) -> Option<Self> {
+        match error {
+            Error::Auth(_) => Some(Step::Auth),
+            Error::TransportError(_) => Some(Step::Channel),
+            Error::SessionNotFound(_) => Some(Step::Session),
+            Error::WithStatement { source, .. } => Step::of(source),
+            Error::Status(status) => match status.code() {
+                Code::Unauthenticated | Code::PermissionDenied => Some(Step::Auth),
+                // credential errors surface as an unknown status since they occur within the channel's stack
+                Code::Unknown if is_auth_error(status) => Some(Step::Auth),
+                Code::Unavailable | Code::Unknown => Some(Step::Channel),
+                Code::NotFound => Some(Step::Session),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+}
+
+fn is_auth_error(status: &tonic::Status) -> bool {
+    let mut source = std::error::Error::source(status);
+    while let Some(error) = source {
+        if error.is::<gcp_auth::Error>()
+            || matches!(error.downcast_ref::<Error>(), Some(Error::Auth(_)))
+        {
+            return true;
+        }
+        source = error.source();
+    }
+    false
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_healthy() {
+        let report = HealthReport::healthy(Duration::from_millis(5));
+        assert!(report.is_healthy());
+        assert!(report.auth_ok());
+        assert!(report.channel_ok());
+        assert!(report.session_ok());
+        assert_eq!(report.latency(), Some(Duration::from_millis(5)));
+        assert!(report.error().is_none());
+    }
+
+    #[test]
+    fn test_failed() {
+        let report = HealthReport::failed(
+            Step::Session,
+            Error::Status(tonic::Status::unauthenticated("bad token")),
+        );
+        assert!(!report.is_healthy());
+        assert!(!report.auth_ok());
+        assert!(!report.channel_ok());
+        assert!(report.error().is_some());
+
+        let report = HealthReport::failed(
+            Step::Session,
+            Error::Status(tonic::Status::unavailable("no route")),
+        );
+        assert!(report.auth_ok());
+        assert!(!report.channel_ok());
+
+        let report = HealthReport::failed(
+            Step::Session,
+            Error::Status(tonic::Status::not_found("session not found")),
+        );
+        assert!(report.channel_ok());
+        assert!(!report.session_ok());
+
+        let report =
+            HealthReport::failed(Step::Query, Error::Status(tonic::Status::internal("oops")));
+        assert!(report.session_ok());
+        assert!(!report.is_healthy());
+        assert!(report.latency().is_none());
+    }
+}
@@ -0,0 +1,108 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+
+use tonic::body::BoxBody;
+use tonic::service::Interceptor;
+use tonic::transport::Body;
+use tower::util::BoxCloneService;
+use tower::{BoxError, Layer, Service, ServiceExt};
+
+/// A request sent to Cloud Spanner by the underlying gRPC channel.
+pub type GrpcRequest = http::Request<BoxBody>;
+
+/// A response received from Cloud Spanner by the underlying gRPC channel.
+pub type GrpcResponse = http::Response<Body>;
+
+/// The service used to send requests to Cloud Spanner, i.e.: the gRPC channel wrapped in its authentication layer.
+///
+/// Custom layers added using [`crate::ConfigBuilder::layer`] wrap this service.
+pub type GrpcService = BoxCloneService<GrpcRequest, GrpcResponse, BoxError>;
+
+/// A user-supplied layer, see [`crate::ConfigBuilder::layer`].
+#[derive(Clone)]
+pub(crate) struct ServiceLayer(Arc<dyn Fn(GrpcService) -> GrpcService + Send + Sync>);
+
+impl ServiceLayer {
+    pub(crate) fn new<L>(layer: L) -> Self
+    where
+        L: Layer<GrpcService> + Send + Sync + 'static,
+        L::Service: Service<GrpcRequest, Response = GrpcResponse> + Clone + Send + 'static,
+        <L::Service as Service<GrpcRequest>>::Error: Into<BoxError>,
+        <L::Service as Service<GrpcRequest>>::Future: Send + 'static,
+    {
+        Self(Arc::new(move |service| {
+            BoxCloneService::new(layer.layer(service).map_err(Into::into))
+        }))
+    }
+
+    pub(crate) fn interceptor<F>(interceptor: F) -> Self
+    where
+        F: Interceptor + Clone + Send + Sync + 'static,
+    {
+        Self::new(tonic::service::interceptor(interceptor))
+    }
+
+    /// Wraps the service with the provided layers, the first one being the outermost.
+    pub(crate) fn apply_all(layers: &[ServiceLayer], service: GrpcService) -> GrpcService {
+        layers
+            .iter()
+            .rev()
+            .fold(service, |service, layer| (layer.0)(service))
+    }
+}
+
+impl Debug for ServiceLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("ServiceLayer")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::*;
+
+    fn service(order: Arc<std::sync::Mutex<Vec<&'static str>>>) -> GrpcService {
+        BoxCloneService::new(tower::service_fn(move |_: GrpcRequest| {
+            order.lock().unwrap().push("channel");
+            async { Ok::<_, BoxError>(GrpcResponse::new(Body::empty())) }
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_apply_all() {
+        let order = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let calls = Arc::new(AtomicUsize::new(0));
+
+        let outer = {
+            let order = order.clone();
+            ServiceLayer::interceptor(move |request: tonic::Request<()>| {
+                order.lock().unwrap().push("outer");
+                Ok(request)
+            })
+        };
+        let inner = {
+            let order = order.clone();
+            let calls = calls.clone();
+            ServiceLayer::new(tower::layer::layer_fn(move |service: GrpcService| {
+                let order = order.clone();
+                let calls = calls.clone();
+                service.map_request(move |request: GrpcRequest| {
+                    order.lock().unwrap().push("inner");
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    request
+                })
+            }))
+        };
+
+        let service = ServiceLayer::apply_all(&[outer, inner], service(order.clone()));
+        service
+            .oneshot(GrpcRequest::new(tonic::body::empty_body()))
+            .await
+            .unwrap();
+
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(*order.lock().unwrap(), vec!["outer", "inner", "channel"]);
+    }
+}
@@ -67,7 +67,7 @@
 //!     .read_only()
 //!     .execute_query("SELECT COUNT(*) AS people FROM person", &[])
 //!     .await?;
-//! let people: u32 = result_set.iter().next().unwrap().get("people")?;
+//! let people: u32 = result_set.one()?.get("people")?;
 //! # Ok(()) }
 //! ```
 //!
@@ -104,30 +104,83 @@
 //!
 //! Authentication uses the [`gcp_auth`] crate which supports several authentication methods.
 
+pub use crate::backpressure::{AdaptiveConcurrency, AdaptiveConcurrencyBuilder};
+pub use crate::batch_write::{MutationGroup, MutationGroupBuilder, MutationGroupOutcome};
+pub use crate::cache::QueryCache;
 pub use crate::client::*;
 pub use crate::config::*;
-pub(crate) use crate::connection::Connection;
-pub use crate::error::Error;
+pub(crate) use crate::connection::{Connection, SharedConnection};
+pub use crate::csv::{CsvOptions, CsvOptionsBuilder};
+pub use crate::dialect::Dialect;
+#[cfg(feature = "emulator")]
+pub use crate::emulator::EmulatorClient;
+pub use crate::error::{Error, ErrorCode};
 pub use crate::from_spanner::*;
+pub use crate::health::HealthReport;
+pub use crate::layer::{GrpcRequest, GrpcResponse, GrpcService};
+pub use crate::limit::ConcurrencyLimit;
+#[cfg(feature = "test-util")]
+pub use crate::mock::MockConnection;
+pub use crate::mutation::{chunk_mutations, KeySet, Mutation, MAX_COMMIT_MUTATIONS};
+pub use crate::partition::QueryPartition;
+pub use crate::plan::{PlanNode, QueryPlan, Scan};
+#[cfg(feature = "arrow")]
+pub use crate::record_batch::record_batches;
+#[cfg(feature = "replay-log")]
+pub use crate::replay::{ReplayEntry, ReplayLog};
 pub use crate::resource::*;
 pub use crate::result_set::*;
+pub use crate::retry::*;
+pub use crate::schema::{
+    CheckConstraint, ColumnSchema, ForeignKey, IndexColumn, IndexSchema, Interleave, OnDelete,
+    Schema, TableSchema,
+};
 pub(crate) use crate::session::*;
 pub use crate::statement::*;
+pub use crate::status::{
+    ErrorDetails, FieldViolation, PreconditionViolation, QuotaViolation, ResourceInfo,
+};
+pub use crate::to_row::{TableRow, ToRow};
 pub use crate::to_spanner::*;
 pub use crate::transaction::*;
 pub use crate::types::*;
 pub use crate::value::*;
+#[cfg(feature = "derive")]
+pub use spanner_rs_derive::{TableRow, ToRow};
 
 mod auth;
+mod backpressure;
+mod batch_write;
+mod cache;
 mod client;
 mod config;
 mod connection;
+mod csv;
+mod dialect;
+#[cfg(feature = "emulator")]
+mod emulator;
 mod error;
 mod from_spanner;
+mod health;
+mod layer;
+mod limit;
+#[cfg(feature = "test-util")]
+mod mock;
+mod mutation;
+mod partition;
+mod plan;
+#[cfg(feature = "arrow")]
+mod record_batch;
+#[cfg(feature = "replay-log")]
+mod replay;
 mod resource;
 mod result_set;
+mod retry;
+mod schema;
 mod session;
 mod statement;
+mod status;
+mod to_row;
 mod to_spanner;
 mod transaction;
 mod types;
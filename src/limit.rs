@@ -0,0 +1,196 @@
+use std::sync::Arc;
+
+use derive_builder::Builder;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+use crate::{Error, Priority};
+
+/// Limits the number of requests a [`crate::Client`] has in flight, see [`crate::ConfigBuilder::concurrency_limit`].
+///
+/// Requests wait for a permit before being sent, which makes a retry storm or a traffic spike queue up in the client
+/// instead of exhausting sessions and sockets. Limits can be set on the total number of requests as well as per [`Priority`],
+/// e.g.: to keep batch jobs using [`Priority::Low`] from starving latency sensitive requests.
+///
+/// Requests made without a priority, such as the ones creating sessions, count as [`Priority::High`] requests,
+/// which is Cloud Spanner's default priority.
+///
+/// Building limits of 0, which would block requests forever, returns an [`Error::Config`].
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{ConcurrencyLimit, Config};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// Config::builder().concurrency_limit(
+///     ConcurrencyLimit::builder()
+///         .max_requests(100)
+///         .max_low_priority_requests(10)
+///         .build()?,
+/// );
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, Default, PartialEq, Eq)]
+#[builder(
+    pattern = "owned",
+    build_fn(error = "crate::Error", validate = "Self::validate")
+)]
+pub struct ConcurrencyLimit {
+    /// Specify the maximum number of requests in flight, regardless of their priority. Must be at least 1.
+    #[builder(setter(strip_option), default)]
+    max_requests: Option<usize>,
+
+    /// Specify the maximum number of [`Priority::Low`] requests in flight. Must be at least 1.
+    #[builder(setter(strip_option), default)]
+    max_low_priority_requests: Option<usize>,
+
+    /// Specify the maximum number of [`Priority::Medium`] requests in flight. Must be at least 1.
+    #[builder(setter(strip_option), default)]
+    max_medium_priority_requests: Option<usize>,
+
+    /// Specify the maximum number of [`Priority::High`] requests in flight, including requests made without a priority.
+    /// Must be at least 1.
+    #[builder(setter(strip_option), default)]
+    max_high_priority_requests: Option<usize>,
+}
+
+impl ConcurrencyLimitBuilder {
+    fn validate(&self) -> Result<(), Error> {
+        let limits = [
+            ("max_requests", self.max_requests),
+            ("max_low_priority_requests", self.max_low_priority_requests),
+            (
+                "max_medium_priority_requests",
+                self.max_medium_priority_requests,
+            ),
+            (
+                "max_high_priority_requests",
+                self.max_high_priority_requests,
+            ),
+        ];
+        match limits.iter().find(|(_, limit)| *limit == Some(Some(0))) {
+            Some((name, _)) => Err(Error::Config(format!("{} must be at least 1", name))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl ConcurrencyLimit {
+    /// Returns a new [`ConcurrencyLimitBuilder`] for configuring the limits.
+    pub fn builder() -> ConcurrencyLimitBuilder {
+        ConcurrencyLimitBuilder::default()
+    }
+}
+
+/// Enforces a [`ConcurrencyLimit`], its semaphores are shared by all clones of a connection.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct Limiter {
+    total: Option<Arc<Semaphore>>,
+    low: Option<Arc<Semaphore>>,
+    medium: Option<Arc<Semaphore>>,
+    high: Option<Arc<Semaphore>>,
+}
+
+/// The permits held by a request while it is in flight.
+pub(crate) struct Permits {
+    _permits: Vec<OwnedSemaphorePermit>,
+}
+
+fn semaphore(max: Option<usize>) -> Option<Arc<Semaphore>> {
+    max.map(|max| Arc::new(Semaphore::new(max)))
+}
+
+impl Limiter {
+    pub(crate) fn new(limit: &ConcurrencyLimit) -> Self {
+        Self {
+            total: semaphore(limit.max_requests),
+            low: semaphore(limit.max_low_priority_requests),
+            medium: semaphore(limit.max_medium_priority_requests),
+            high: semaphore(limit.max_high_priority_requests),
+        }
+    }
+
+    /// Waits until a request of the provided priority may be sent.
+    pub(crate) async fn acquire(&self, priority: Option<Priority>) -> Result<Permits, Error> {
+        let class = match priority.unwrap_or(Priority::High) {
+            Priority::Low => &self.low,
+            Priority::Medium => &self.medium,
+            Priority::High => &self.high,
+        };
+        let mut permits = Vec::with_capacity(2);
+        // The permit of the priority class is obtained first, so that requests waiting on their class don't hold total permits.
+        for semaphore in [class, &self.total].into_iter().flatten() {
+            let permit = semaphore
+                .clone()
+                .acquire_owned()
+                .await
+                .map_err(|_| Error::Client("concurrency limiter is closed".to_string()))?;
+            permits.push(permit);
+        }
+        Ok(Permits { _permits: permits })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::time::Duration;
+
+    use super::*;
+
+    async fn is_blocked(limiter: &Limiter, priority: Option<Priority>) -> bool {
+        tokio::time::timeout(Duration::from_millis(10), limiter.acquire(priority))
+            .await
+            .is_err()
+    }
+
+    #[tokio::test]
+    async fn test_limiter() {
+        let limiter = Limiter::new(
+            &ConcurrencyLimit::builder()
+                .max_requests(2)
+                .max_low_priority_requests(1)
+                .build()
+                .unwrap(),
+        );
+
+        let low = limiter.acquire(Some(Priority::Low)).await.unwrap();
+        assert!(is_blocked(&limiter, Some(Priority::Low)).await);
+
+        let high = limiter.acquire(None).await.unwrap();
+        assert!(is_blocked(&limiter, Some(Priority::Medium)).await);
+
+        drop(low);
+        let _medium = limiter.acquire(Some(Priority::Medium)).await.unwrap();
+        assert!(is_blocked(&limiter, None).await);
+
+        drop(high);
+        assert!(!is_blocked(&limiter, None).await);
+    }
+
+    #[test]
+    fn test_builder_validate() {
+        for builder in [
+            ConcurrencyLimit::builder().max_requests(0),
+            ConcurrencyLimit::builder().max_low_priority_requests(0),
+            ConcurrencyLimit::builder().max_medium_priority_requests(0),
+            ConcurrencyLimit::builder().max_high_priority_requests(0),
+        ] {
+            assert!(matches!(builder.build(), Err(Error::Config(_))));
+        }
+        assert_eq!(
+            ConcurrencyLimit::builder().build().unwrap(),
+            ConcurrencyLimit::default()
+        );
+        assert!(ConcurrencyLimit::builder().max_requests(1).build().is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_limiter_unlimited() {
+        let limiter = Limiter::default();
+        let _permits: Vec<_> = futures_util::future::try_join_all(
+            (0..100).map(|_| limiter.acquire(Some(Priority::Low))),
+        )
+        .await
+        .unwrap();
+    }
+}
@@ -0,0 +1,356 @@
+//! An interactive SQL shell for Cloud Spanner.
+//!
+//! The connection is configured using the same environment variables as [`Config::from_env`],
+//! which may be overridden using flags, see `spanner-rs --help`.
+
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use rustyline::error::ReadlineError;
+use rustyline::Editor;
+use spanner_rs::{
+    Client, Config, ConfigBuilder, CsvOptions, Error, ReadContext, ResultSet, Statement,
+    StatementKind, TransactionContext, Value,
+};
+
+const USAGE: &str = "\
+Usage: spanner-rs [OPTIONS]
+
+Runs an interactive SQL shell, or executes the statements passed using --execute.
+
+Options:
+      --project <PROJECT>            the GCP project [env: SPANNER_RS_PROJECT_ID, GOOGLE_CLOUD_PROJECT]
+      --instance <INSTANCE>          the Cloud Spanner instance ID [env: SPANNER_RS_INSTANCE]
+      --database <DATABASE>          the Cloud Spanner database name [env: SPANNER_RS_DATABASE]
+      --endpoint <URI>               the URI of the Spanner API [env: SPANNER_RS_ENDPOINT]
+      --emulator-host <HOST:PORT>    connect to a Spanner emulator [env: SPANNER_EMULATOR_HOST]
+      --credentials-file <FILE>      the credentials file [env: SPANNER_RS_CREDENTIALS_FILE]
+  -e, --execute <SQL>                execute the statement(s) and exit
+  -f, --format <FORMAT>              the format of query results: table (default), json, csv or ndjson
+  -h, --help                         print this message";
+
+const PROMPT: &str = "spanner> ";
+const CONTINUATION_PROMPT: &str = "      -> ";
+
+struct Args {
+    config: ConfigBuilder,
+    execute: Option<String>,
+    format: Format,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Option<Self>, String> {
+        let mut config = Config::from_env();
+        let mut execute = None;
+        let mut format = Format::Table;
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("missing value for {}", arg))
+            };
+            match arg.as_str() {
+                "--project" => config = config.project(value()?),
+                "--instance" => config = config.instance(value()?),
+                "--database" => config = config.database(value()?),
+                "--endpoint" => config = config.endpoint(value()?),
+                "--emulator-host" => {
+                    config = config.with_emulator_host(format!("http://{}", value()?))
+                }
+                "--credentials-file" => config = config.credentials_file(value()?),
+                "-e" | "--execute" => execute = Some(value()?),
+                "-f" | "--format" => format = value()?.parse()?,
+                "-h" | "--help" => return Ok(None),
+                other => return Err(format!("unexpected argument: {}", other)),
+            }
+        }
+        Ok(Some(Self {
+            config,
+            execute,
+            format,
+        }))
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let args = match Args::parse(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return;
+        }
+        Err(err) => {
+            eprintln!("{}\n\n{}", err, USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    let client = match args.config.connect().await {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("unable to connect: {}", err);
+            std::process::exit(1);
+        }
+    };
+
+    let result = match args.execute {
+        Some(sql) => execute_all(&client, &sql, args.format).await,
+        None => shell(&client, args.format).await,
+    };
+    if let Err(err) = result {
+        eprintln!("error: {}", err);
+        std::process::exit(1);
+    }
+}
+
+/// Executes every `;`-separated statement, stopping at the first error.
+async fn execute_all(client: &Client, sql: &str, format: Format) -> Result<(), Error> {
+    for statement in split_statements(sql) {
+        execute(client, &statement, format).await?;
+    }
+    Ok(())
+}
+
+async fn shell(client: &Client, format: Format) -> Result<(), Error> {
+    let mut editor = Editor::<()>::new().map_err(|err| Error::Client(err.to_string()))?;
+    let history = history_file();
+    if let Some(history) = &history {
+        // the history file does not exist the first time the shell is used
+        let _ = editor.load_history(history);
+    }
+
+    let mut buffer = String::new();
+    loop {
+        let prompt = if buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                let trimmed = line.trim();
+                if buffer.is_empty() && matches!(trimmed, "exit" | "quit" | "\\q") {
+                    break;
+                }
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if !buffer.is_empty() {
+                    buffer.push('\n');
+                }
+                buffer.push_str(&line);
+                if !trimmed.ends_with(';') {
+                    continue;
+                }
+                editor.add_history_entry(buffer.as_str());
+                for statement in split_statements(&std::mem::take(&mut buffer)) {
+                    if let Err(err) = execute(client, &statement, format).await {
+                        eprintln!("error: {}", err);
+                    }
+                }
+            }
+            // discard the statement being typed
+            Err(ReadlineError::Interrupted) => buffer.clear(),
+            Err(ReadlineError::Eof) => break,
+            Err(err) => return Err(Error::Client(err.to_string())),
+        }
+    }
+
+    if let Some(history) = &history {
+        let _ = editor.save_history(history);
+    }
+    Ok(())
+}
+
+fn history_file() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".spanner_rs_history"))
+}
+
+/// Splits the input on `;`, ignoring those that appear in quoted strings or identifiers.
+fn split_statements(sql: &str) -> Vec<String> {
+    let mut statements = Vec::new();
+    let mut current = String::new();
+    let mut quote: Option<char> = None;
+    let mut chars = sql.chars();
+    while let Some(c) = chars.next() {
+        match (quote, c) {
+            (Some(_), '\\') => {
+                current.push(c);
+                current.extend(chars.next());
+                continue;
+            }
+            (Some(q), c) if c == q => quote = None,
+            (None, '\'' | '"' | '`') => quote = Some(c),
+            (None, ';') => {
+                statements.push(std::mem::take(&mut current));
+                continue;
+            }
+            _ => (),
+        }
+        current.push(c);
+    }
+    statements.push(current);
+    statements
+        .into_iter()
+        .map(|statement| statement.trim().to_string())
+        .filter(|statement| !statement.is_empty())
+        .collect()
+}
+
+async fn execute(client: &Client, sql: &str, format: Format) -> Result<(), Error> {
+    match StatementKind::of(sql) {
+        StatementKind::Dml => {
+            let statement = Statement::new(sql);
+            let rows = client
+                .read_write()
+                .run(|tx| {
+                    let statement = statement.clone();
+                    Box::pin(async move { tx.execute_update_statement(&statement).await })
+                })
+                .await?;
+            println!("{} row(s) affected", rows);
+        }
+        StatementKind::Ddl => {
+            return Err(Error::Client(
+                "DDL statements are not supported, they require the database admin API".to_string(),
+            ))
+        }
+        StatementKind::Query | StatementKind::Unknown => {
+            let result_set = client.read_only().execute_query(sql, &[]).await?;
+            print(&result_set, format)?;
+        }
+    }
+    Ok(())
+}
+
+/// The format used to print query results.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    Table,
+    Json,
+    Csv,
+    Ndjson,
+}
+
+impl FromStr for Format {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(Format::Table),
+            "json" => Ok(Format::Json),
+            "csv" => Ok(Format::Csv),
+            "ndjson" => Ok(Format::Ndjson),
+            other => Err(format!("unknown format: {}", other)),
+        }
+    }
+}
+
+fn print(result_set: &ResultSet, format: Format) -> Result<(), Error> {
+    match format {
+        Format::Table => {
+            let mut rows = result_set.iter().peekable();
+            let header = match rows.peek() {
+                Some(row) => row
+                    .row_type()
+                    .field_names()
+                    .enumerate()
+                    .map(|(index, name)| name.clone().unwrap_or_else(|| format!("_{}", index)))
+                    .collect::<Vec<_>>(),
+                None => Vec::new(),
+            };
+            let rows = rows
+                .map(|row| {
+                    (0..header.len())
+                        .map(|index| row.get::<&Value, _>(index))
+                        .collect::<Result<Vec<_>, _>>()
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            print_table(&header, &rows);
+        }
+        Format::Csv => print!("{}", result_set.to_csv(&CsvOptions::default())),
+        Format::Json => {
+            let json = serde_json::to_string_pretty(&result_set.to_json())
+                .map_err(|err| Error::Client(err.to_string()))?;
+            println!("{}", json);
+        }
+        Format::Ndjson => {
+            if let serde_json::Value::Array(objects) = result_set.to_json() {
+                for object in objects {
+                    println!("{}", object);
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_table(header: &[String], rows: &[Vec<&Value>]) {
+    if rows.is_empty() {
+        println!("(0 rows)");
+        return;
+    }
+    let rows = rows
+        .iter()
+        .map(|row| row.iter().map(|value| display(value)).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let widths = (0..header.len())
+        .map(|index| {
+            rows.iter()
+                .map(|row| row[index].chars().count())
+                .chain(std::iter::once(header[index].chars().count()))
+                .max()
+                .unwrap_or_default()
+        })
+        .collect::<Vec<_>>();
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(width + 2))
+        .collect::<Vec<_>>()
+        .join("+");
+    let line = |cells: &[String]| {
+        cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!(" {:width$} ", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("|")
+    };
+
+    println!("+{}+", separator);
+    println!("|{}|", line(header));
+    println!("+{}+", separator);
+    for row in &rows {
+        println!("|{}|", line(row));
+    }
+    println!("+{}+", separator);
+    println!("({} row(s))", rows.len());
+}
+
+/// Renders a value for display, strings are not quoted.
+fn display(value: &Value) -> String {
+    match value {
+        Value::Null(_) => "NULL".to_string(),
+        Value::Bool(b) => b.to_string(),
+        Value::Int64(i) => i.to_string(),
+        Value::Float64(f) => f.to_string(),
+        Value::String(s) => s.clone(),
+        other => other.to_sql_literal(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_split_statements() {
+        assert_eq!(
+            split_statements("SELECT 1; SELECT ';', \"a;b\", 'it\\'s;' ;\n;"),
+            vec!["SELECT 1", "SELECT ';', \"a;b\", 'it\\'s;'"]
+        );
+        assert_eq!(split_statements("SELECT 1"), vec!["SELECT 1"]);
+        assert!(split_statements(" ; ").is_empty());
+    }
+}
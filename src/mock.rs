@@ -0,0 +1,981 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use async_trait::async_trait;
+use futures_util::stream::{self, BoxStream, StreamExt};
+use google_api_proto::google::spanner::v1 as proto;
+use prost::bytes::Bytes;
+
+use crate::batch_write::proto as batch_write;
+use crate::{
+    Client, CommitResponse, Connection, Error, Mutation, MutationGroup, MutationGroupOutcome,
+    QueryPlan, ResultSet, Session, SessionManager, SessionPool, Statement, TimestampBound,
+    Transaction, TransactionSelector, TxOptions,
+};
+
+/// A scriptable [`Connection`] used to unit test code that takes a [`Client`] without a Cloud Spanner instance or emulator.
+///
+/// Each SQL statement is mapped to a canned [`ResultSet`] (or error), commits can be aborted to exercise retries
+/// and the executed statements are recorded so that they can be asserted on.
+/// Clones share the same script and recordings, so a mock can be inspected after handing a [`Client`] out.
+///
+/// This requires the `test-util` crate feature.
+///
+/// # Example
+///
+/// ```
+/// # use spanner_rs::{Error, MockConnection, ReadContext, ResultSet, StructType, Type, Value};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// let mock = MockConnection::new()
+///     .with_result_set(
+///         "SELECT name FROM person",
+///         ResultSet::new(
+///             StructType::new(vec![("name", Type::String)]),
+///             vec![vec![Value::String("ferris".to_string())]],
+///         )?,
+///     )
+///     .with_row_count("DELETE FROM person WHERE true", 1)
+///     .abort_commit(1);
+///
+/// let client = mock.client();
+/// let result_set = client
+///     .read_only()
+///     .execute_query("SELECT name FROM person", &[])
+///     .await?;
+/// assert_eq!(result_set.iter().count(), 1);
+///
+/// // the first commit is aborted, the transaction is retried
+/// client
+///     .read_write()
+///     .run(|tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+///     .await?;
+/// assert_eq!(mock.commits(), 2);
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Default)]
+pub struct MockConnection {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    result_sets: HashMap<String, ResultSet>,
+    partitions: HashMap<String, Vec<ResultSet>>,
+    errors: HashMap<String, VecDeque<Error>>,
+    aborted_commits: HashSet<usize>,
+    sessions: usize,
+    deleted_sessions: Vec<String>,
+    transactions: usize,
+    executed: Vec<Statement>,
+    mutations: Vec<Mutation>,
+    commits: usize,
+    rollbacks: usize,
+}
+
+impl MockConnection {
+    /// Creates a mock that has no scripted results: executing any statement returns an error.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns `result_set` every time `sql` is executed, regardless of its parameters.
+    #[must_use]
+    pub fn with_result_set(self, sql: impl Into<String>, result_set: ResultSet) -> Self {
+        self.state().result_sets.insert(sql.into(), result_set);
+        self
+    }
+
+    /// Reports `row_count` modified rows every time the `sql` DML statement is executed.
+    #[must_use]
+    pub fn with_row_count(self, sql: impl Into<String>, row_count: i64) -> Self {
+        self.with_result_set(sql, ResultSet::from_row_count(row_count))
+    }
+
+    /// Divides the `sql` query into partitions, each returning one of the provided result sets,
+    /// see [`Client::partition_query`].
+    ///
+    /// Queries without scripted partitions have a single partition that returns their scripted result set.
+    #[must_use]
+    pub fn with_partitions(self, sql: impl Into<String>, partitions: Vec<ResultSet>) -> Self {
+        self.state().partitions.insert(sql.into(), partitions);
+        self
+    }
+
+    /// Fails the next execution of `sql` with `error`.
+    ///
+    /// Errors are queued: calling this more than once for the same statement fails as many subsequent executions,
+    /// after which its result set is returned again.
+    #[must_use]
+    pub fn with_error(self, sql: impl Into<String>, error: Error) -> Self {
+        self.state()
+            .errors
+            .entry(sql.into())
+            .or_default()
+            .push_back(error);
+        self
+    }
+
+    /// Aborts the `n`th commit (starting at 1) with [`Error::Aborted`], which retries the transaction.
+    #[must_use]
+    pub fn abort_commit(self, n: usize) -> Self {
+        self.state().aborted_commits.insert(n);
+        self
+    }
+
+    /// Creates a [`Client`] that sends its requests to this mock using a single session.
+    pub fn client(&self) -> Client {
+        let connection: Box<dyn Connection> = Box::new(self.clone());
+        Client::connect(
+            connection.clone(),
+            SessionPool::single(SessionManager::new(connection), None),
+        )
+    }
+
+    /// Returns the statements executed so far, in order, including those of aborted attempts.
+    pub fn executed(&self) -> Vec<Statement> {
+        self.state().executed.clone()
+    }
+
+    /// Returns the mutations sent along with the commits attempted so far, in order, including those of aborted attempts,
+    /// followed by those of batch writes.
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state().mutations.clone()
+    }
+
+    /// Returns the number of commits attempted so far, including aborted ones.
+    pub fn commits(&self) -> usize {
+        self.state().commits
+    }
+
+    /// Returns the number of rolled back transactions so far.
+    pub fn rollbacks(&self) -> usize {
+        self.state().rollbacks
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl MockState {
+    fn session(&mut self) -> Session {
+        self.sessions += 1;
+        Session::from(proto::Session {
+            name: format!("mock-session-{}", self.sessions),
+            ..Default::default()
+        })
+    }
+
+    fn execute(
+        &mut self,
+        selector: &TransactionSelector,
+        statement: &Statement,
+    ) -> Result<ResultSet, Error> {
+        self.executed.push(statement.clone());
+        if let Some(error) = self
+            .errors
+            .get_mut(statement.sql())
+            .and_then(VecDeque::pop_front)
+        {
+            return Err(error);
+        }
+        let mut result_set = self
+            .result_sets
+            .get(statement.sql())
+            .cloned()
+            .ok_or_else(|| {
+                Error::Client(format!("no result set scripted for: {}", statement.sql()))
+            })?;
+        if let TransactionSelector::Begin = selector {
+            self.transactions += 1;
+            result_set.transaction = Some(Transaction::from(proto::Transaction {
+                id: self.transactions.to_string().into_bytes().into(),
+                read_timestamp: None,
+            }));
+        }
+        Ok(result_set)
+    }
+}
+
+#[async_trait]
+impl Connection for MockConnection {
+    async fn create_session(&mut self) -> Result<Session, Error> {
+        Ok(self.state().session())
+    }
+
+    async fn batch_create_sessions(&mut self, count: u32) -> Result<Vec<Session>, Error> {
+        let mut state = self.state();
+        Ok((0..count).map(|_| state.session()).collect())
+    }
+
+    async fn delete_session(&mut self, session: Session) -> Result<(), Error> {
+        self.state()
+            .deleted_sessions
+            .push(session.name().to_string());
+        Ok(())
+    }
+
+    async fn commit(
+        &mut self,
+        _session: &Session,
+        transaction: Option<Transaction>,
+        mutations: &[Mutation],
+        options: &TxOptions,
+    ) -> Result<CommitResponse, Error> {
+        let mut state = self.state();
+        // Like `GrpcConnection`, invalid mutations roll the transaction back without committing.
+        if let Err(err) = mutations
+            .iter()
+            .cloned()
+            .map(proto::Mutation::try_from)
+            .collect::<Result<Vec<_>, Error>>()
+        {
+            if transaction.is_some() {
+                state.rollbacks += 1;
+            }
+            return Err(err);
+        }
+        state.commits += 1;
+        state.mutations.extend_from_slice(mutations);
+        if state.aborted_commits.contains(&state.commits) {
+            return Err(Error::Aborted(tonic::Status::aborted(format!(
+                "commit {} aborted by MockConnection",
+                state.commits
+            ))));
+        }
+        proto::CommitResponse {
+            commit_timestamp: Some(SystemTime::now().into()),
+            commit_stats: options.return_commit_stats().then(|| {
+                proto::commit_response::CommitStats {
+                    mutation_count: mutations.len() as i64,
+                }
+            }),
+            ..Default::default()
+        }
+        .try_into()
+    }
+
+    async fn rollback(
+        &mut self,
+        _session: &Session,
+        _transaction: Transaction,
+    ) -> Result<(), Error> {
+        self.state().rollbacks += 1;
+        Ok(())
+    }
+
+    async fn batch_write(
+        &mut self,
+        _session: &Session,
+        groups: Vec<MutationGroup>,
+    ) -> Result<BoxStream<'static, Result<MutationGroupOutcome, Error>>, Error> {
+        let mut state = self.state();
+        for group in &groups {
+            state.mutations.extend_from_slice(group.mutations());
+        }
+        // All groups are applied by a single response.
+        let outcomes = crate::batch_write::outcomes(batch_write::BatchWriteResponse {
+            indexes: (0..groups.len() as i32).collect(),
+            status: None,
+            commit_timestamp: Some(SystemTime::now().into()),
+        })?;
+        Ok(stream::iter(outcomes.into_iter().map(Ok)).boxed())
+    }
+
+    async fn execute_sql(
+        &mut self,
+        _session: &Session,
+        selector: &TransactionSelector,
+        statement: &Statement,
+        _seqno: Option<i64>,
+        _options: Option<&TxOptions>,
+    ) -> Result<ResultSet, Error> {
+        self.state().execute(selector, statement)
+    }
+
+    async fn plan_sql(
+        &mut self,
+        _session: &Session,
+        _statement: &Statement,
+    ) -> Result<QueryPlan, Error> {
+        Ok(QueryPlan::from(proto::QueryPlan::default()))
+    }
+
+    async fn execute_batch_dml(
+        &mut self,
+        _session: &Session,
+        selector: &TransactionSelector,
+        statements: &[&Statement],
+        _seqno: i64,
+        _options: &TxOptions,
+    ) -> Result<Vec<ResultSet>, Error> {
+        let mut state = self.state();
+        let mut result_sets = Vec::with_capacity(statements.len());
+        for (index, statement) in statements.iter().enumerate() {
+            // Only the first statement begins the transaction, the others are part of it.
+            let selector = match (index, selector) {
+                (0, _) | (_, TransactionSelector::Id(_)) => selector.clone(),
+                _ => TransactionSelector::SingleUse(None),
+            };
+            result_sets.push(state.execute(&selector, statement)?);
+        }
+        Ok(result_sets)
+    }
+
+    async fn begin_read_only(
+        &mut self,
+        _session: &Session,
+        _bound: TimestampBound,
+    ) -> Result<Transaction, Error> {
+        let mut state = self.state();
+        state.transactions += 1;
+        Ok(Transaction::from(proto::Transaction {
+            id: state.transactions.to_string().into_bytes().into(),
+            read_timestamp: Some(SystemTime::now().into()),
+        }))
+    }
+
+    async fn partition_query(
+        &mut self,
+        _session: &Session,
+        _transaction: &Transaction,
+        statement: &Statement,
+    ) -> Result<Vec<Bytes>, Error> {
+        let partitions = self
+            .state()
+            .partitions
+            .get(statement.sql())
+            .map_or(1, Vec::len);
+        Ok((0..partitions)
+            .map(|partition| partition.to_string().into_bytes().into())
+            .collect())
+    }
+
+    async fn execute_partition(
+        &mut self,
+        _session: &Session,
+        _transaction: &Transaction,
+        statement: &Statement,
+        token: &Bytes,
+    ) -> Result<ResultSet, Error> {
+        let mut state = self.state();
+        let partition = std::str::from_utf8(token)
+            .ok()
+            .and_then(|token| token.parse::<usize>().ok());
+        let scripted = partition.and_then(|partition| {
+            state
+                .partitions
+                .get(statement.sql())
+                .and_then(|partitions| partitions.get(partition))
+                .cloned()
+        });
+        match scripted {
+            Some(result_set) => {
+                state.executed.push(statement.clone());
+                Ok(result_set)
+            }
+            None => state.execute(&TransactionSelector::SingleUse(None), statement),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::{
+        KeySet, ReadContext, ReadContextExt, StructType, ToSpanner, TransactionContext,
+        TransactionContextExt, Type, Value,
+    };
+
+    fn people() -> ResultSet {
+        ResultSet::new(
+            StructType::new(vec![("name", Type::String)]),
+            vec![
+                vec![Value::String("ferris".to_string())],
+                vec![Value::String("corro".to_string())],
+            ],
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_mock_result_set() {
+        let mock = MockConnection::new().with_result_set("SELECT name FROM person", people());
+        let client = mock.client();
+
+        let result_set = client
+            .read_only()
+            .execute_query("SELECT name FROM person", &[])
+            .await
+            .unwrap();
+        let names: Vec<&str> = result_set.iter().map(|row| row.get_unchecked(0)).collect();
+        assert_eq!(names, vec!["ferris", "corro"]);
+
+        match client.read_only().execute_query("SELECT 1", &[]).await {
+            Err(Error::Client(_)) => (),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        assert_eq!(mock.executed().len(), 2);
+        assert_eq!(mock.executed()[1].sql(), "SELECT 1");
+    }
+
+    #[tokio::test]
+    async fn test_mock_shared_client() {
+        let mock = MockConnection::new().with_result_set("SELECT name FROM person", people());
+        let client = Arc::new(mock.client());
+
+        let tasks = (0..2)
+            .map(|_| {
+                let client = client.clone();
+                tokio::spawn(async move {
+                    client
+                        .read_only()
+                        .execute_query("SELECT name FROM person", &[])
+                        .await
+                        .map(|result_set| result_set.len())
+                })
+            })
+            .collect::<Vec<_>>();
+        for task in tasks {
+            assert_eq!(task.await.unwrap().unwrap(), 2);
+        }
+        assert_eq!(mock.executed().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_error() {
+        let mock = MockConnection::new()
+            .with_result_set("SELECT name FROM person", people())
+            .with_error(
+                "SELECT name FROM person",
+                Error::Client("injected".to_string()),
+            );
+        let client = mock.client();
+
+        match client
+            .read_only()
+            .execute_query("SELECT name FROM person", &[])
+            .await
+        {
+            Err(Error::Client(message)) => assert_eq!(message, "injected"),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+        let result_set = client
+            .read_only()
+            .execute_query("SELECT name FROM person", &[])
+            .await
+            .unwrap();
+        assert_eq!(result_set.iter().count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_quota_retry_with_statement_context() {
+        use crate::status::test::{quota_failure_detail, retry_info, status_with_details};
+        use std::time::Duration;
+
+        let sql = "SELECT name FROM person";
+        let quota = status_with_details(
+            tonic::Code::ResourceExhausted,
+            vec![
+                quota_failure_detail("project:my-project", "too many requests"),
+                retry_info(Duration::from_millis(1)),
+            ],
+        );
+        // the error as returned by a connection with `statement_error_context` enabled
+        let mock = MockConnection::new()
+            .with_result_set(sql, people())
+            .with_error(sql, Error::from(quota).with_statement(&Statement::new(sql)));
+        let client = mock.client();
+
+        let result_set = client.read_only().execute_query(sql, &[]).await.unwrap();
+        assert_eq!(result_set.iter().count(), 2);
+        assert_eq!(mock.executed().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_parameter_maps() {
+        let query = "SELECT name FROM person WHERE id = @id";
+        let update = "UPDATE person SET name = @name WHERE id = @id";
+        let mock = MockConnection::new()
+            .with_result_set(query, people())
+            .with_row_count(update, 1);
+        let client = mock.client();
+
+        let params = HashMap::from([("id", Value::Int64(42))]);
+        let result_set = client
+            .read_only()
+            .execute_query_with_parameters(query, params)
+            .await
+            .unwrap();
+        assert_eq!(result_set.iter().count(), 2);
+
+        let params = BTreeMap::<&str, &dyn ToSpanner>::from([
+            ("id", &42 as &dyn ToSpanner),
+            ("name", &"ferris" as &dyn ToSpanner),
+        ]);
+        let rows = client
+            .read_write()
+            .run(|tx| tx.execute_update_with_parameters(update, &params))
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        let executed = mock.executed();
+        assert_eq!(
+            executed[0].params().collect::<Vec<_>>(),
+            vec![("id", &Value::Int64(42))]
+        );
+        assert_eq!(
+            executed[1].params().collect::<Vec<_>>(),
+            vec![
+                ("id", &Value::Int64(42)),
+                ("name", &Value::String("ferris".to_string()))
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_mock_abort_commit() {
+        let mock = MockConnection::new()
+            .with_row_count("DELETE FROM person WHERE true", 2)
+            .abort_commit(1)
+            .abort_commit(2);
+        let client = mock.client();
+
+        let row_count = client
+            .read_write()
+            .run(|tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+            .await
+            .unwrap();
+        assert_eq!(row_count, 2);
+        assert_eq!(mock.commits(), 3);
+        assert_eq!(mock.executed().len(), 3);
+        assert_eq!(mock.rollbacks(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mock_rollback() {
+        let mock = MockConnection::new().with_row_count("DELETE FROM person WHERE true", 2);
+        let client = mock.client();
+
+        let result: Result<(), Error> = client
+            .read_write()
+            .run(|tx| {
+                Box::pin(async move {
+                    tx.execute_update("DELETE FROM person WHERE true", &[])
+                        .await?;
+                    Err(Error::Client("oops".to_string()))
+                })
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(mock.commits(), 0);
+        assert_eq!(mock.rollbacks(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_spawn_read_write() {
+        let mock = MockConnection::new().with_row_count("DELETE FROM person WHERE true", 2);
+        let client = mock.client();
+
+        let row_count = tokio::spawn(async move {
+            client
+                .read_write()
+                .run(|tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+                .await
+        })
+        .await
+        .unwrap()
+        .unwrap();
+        assert_eq!(row_count, 2);
+        assert_eq!(mock.commits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_run_local() {
+        let mock = MockConnection::new().with_row_count("DELETE FROM person WHERE true", 2);
+        let client = mock.client();
+
+        let deleted = std::rc::Rc::new(std::cell::Cell::new(0));
+        client
+            .read_write()
+            .run_local(|tx| {
+                let deleted = deleted.clone();
+                Box::pin(async move {
+                    let row_count = tx
+                        .execute_update("DELETE FROM person WHERE true", &[])
+                        .await?;
+                    deleted.set(row_count);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        assert_eq!(deleted.get(), 2);
+        assert_eq!(mock.commits(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_buffer_write() {
+        let mock = MockConnection::new().abort_commit(1);
+        let client = mock.client();
+
+        let outcome = client
+            .read_write()
+            .run_with_outcome(|tx| {
+                Box::pin(async move {
+                    tx.buffer_write(vec![Mutation::delete("person", KeySet::new().key(&[&42]))]);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        assert!(outcome.commit_timestamp().is_some());
+        assert!(outcome.commit_response().unwrap().commit_stats().is_none());
+        assert_eq!(mock.commits(), 2);
+        // the aborted attempt's buffer is discarded
+        assert_eq!(mock.mutations().len(), 2);
+        assert!(mock.executed().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_mock_buffer_write_invalid() {
+        struct Invalid;
+        impl crate::ToSpanner for Invalid {
+            fn to_spanner(&self) -> Result<Value, Error> {
+                Err(Error::Codec("invalid".to_string()))
+            }
+        }
+
+        let mock = MockConnection::new().with_row_count("DELETE FROM person WHERE true", 1);
+        let client = mock.client();
+
+        let result = client
+            .read_write()
+            .run(|tx| {
+                tx.buffer_write(vec![Mutation::insert("person").set("age", Invalid)]);
+                tx.execute_update("DELETE FROM person WHERE true", &[])
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(mock.commits(), 0);
+        assert_eq!(mock.rollbacks(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_batch_write() {
+        use futures_util::TryStreamExt;
+
+        let mock = MockConnection::new();
+        let client = mock.client();
+
+        let groups = vec![
+            MutationGroup::builder()
+                .mutation(Mutation::insert("person").set("id", 42))
+                .build()
+                .unwrap(),
+            MutationGroup::builder()
+                .mutation(Mutation::insert("person").set("id", 43))
+                .mutation(Mutation::insert("pet").set("id", 43))
+                .build()
+                .unwrap(),
+        ];
+        let outcomes: Vec<_> = client
+            .batch_write(groups)
+            .await
+            .unwrap()
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(outcomes.len(), 2);
+        assert_eq!(outcomes[1].index(), 1);
+        assert!(outcomes
+            .iter()
+            .all(|outcome| outcome.commit_timestamp().is_some()));
+        assert_eq!(mock.mutations().len(), 3);
+        assert_eq!(mock.commits(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mock_warmup() {
+        let mock = MockConnection::new();
+        let connection: Box<dyn Connection> = Box::new(mock.clone());
+        let builder = bb8::Pool::builder()
+            .min_idle(Some(3))
+            .connection_timeout(std::time::Duration::from_secs(1));
+        let pool = SessionPool::pooled(builder, SessionManager::new(connection.clone()), 3, true)
+            .await
+            .unwrap();
+        let client = Client::connect(connection, pool);
+        assert_eq!(mock.state().sessions, 0);
+
+        client.warmup().await.unwrap();
+        assert_eq!(mock.state().sessions, 3);
+        // the pool is already full
+        client.warmup().await.unwrap();
+        assert_eq!(mock.state().sessions, 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_drain() {
+        let mock = MockConnection::new();
+        let connection: Box<dyn Connection> = Box::new(mock.clone());
+        let pool = SessionPool::pooled(
+            bb8::Pool::builder(),
+            SessionManager::new(connection.clone()),
+            0,
+            true,
+        )
+        .await
+        .unwrap();
+
+        let in_use = pool.get().await.unwrap();
+        let idle = pool.get().await.unwrap();
+        let names = vec![in_use.name().to_string(), idle.name().to_string()];
+        drop(idle);
+
+        // the session in use is released while draining
+        let (drained, _) = tokio::join!(
+            pool.drain(connection, std::time::Duration::from_secs(1)),
+            async move {
+                tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+                drop(in_use);
+            }
+        );
+        drained.unwrap();
+
+        let mut deleted = mock.state().deleted_sessions.clone();
+        deleted.sort();
+        assert_eq!(deleted, names);
+        assert!(pool.get().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_mock_expired_sessions() {
+        let mock = MockConnection::new();
+        let connection: Box<dyn Connection> = Box::new(mock.clone());
+        let manager = SessionManager::new(connection.clone())
+            .with_max_session_lifetime(std::time::Duration::ZERO);
+        let pool = SessionPool::pooled(bb8::Pool::builder(), manager, 0, true)
+            .await
+            .unwrap();
+
+        let expired = pool.get().await.unwrap().name().to_string();
+        // deleted in the background
+        tokio::time::sleep(std::time::Duration::from_millis(100)).await;
+        assert_eq!(mock.state().deleted_sessions, vec![expired]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_write_chunked() {
+        let mock = MockConnection::new().abort_commit(2);
+        let client = mock.client();
+
+        let mutations = (0..5)
+            .map(|id| {
+                Mutation::insert("person")
+                    .set("id", id)
+                    .set("name", "ferris")
+            })
+            .collect();
+        let commit_timestamps = client.write_chunked(mutations, 4).await.unwrap();
+        assert_eq!(commit_timestamps.len(), 3);
+        // the aborted chunk is retried
+        assert_eq!(mock.commits(), 4);
+        assert_eq!(mock.mutations().len(), 7);
+    }
+
+    #[tokio::test]
+    async fn test_mock_write_chunked_backpressure() {
+        let mock = MockConnection::new().abort_commit(2);
+        let client = mock.client().with_write_backpressure(Some(
+            crate::AdaptiveConcurrency::builder()
+                .initial(2)
+                .build()
+                .unwrap(),
+        ));
+
+        let mutations = (0..10)
+            .map(|id| {
+                Mutation::insert("person")
+                    .set("id", id)
+                    .set("name", "ferris")
+            })
+            .collect();
+        let commit_timestamps = client.write_chunked(mutations, 2).await.unwrap();
+        assert_eq!(commit_timestamps.len(), 5);
+        assert_eq!(mock.commits(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_mock_retry_observer() {
+        let mock = MockConnection::new()
+            .with_row_count("DELETE FROM person WHERE true", 2)
+            .abort_commit(1)
+            .abort_commit(2);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let observed = events.clone();
+        let client = mock
+            .client()
+            .with_retry_observer(Some(crate::RetryObserver::new(move |event| {
+                observed.lock().unwrap().push(event.attempt())
+            })));
+
+        client
+            .read_write()
+            .run(|tx| tx.execute_update("DELETE FROM person WHERE true", &[]))
+            .await
+            .unwrap();
+        assert_eq!(*events.lock().unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_mock_commit_stats() {
+        let mock = MockConnection::new();
+        let client = mock.client();
+
+        let options = TxOptions::builder()
+            .return_commit_stats(true)
+            .build()
+            .unwrap();
+        let outcome = client
+            .read_write()
+            .run_with_options(options, |tx| {
+                Box::pin(async move {
+                    tx.buffer_write(vec![
+                        Mutation::insert("person").set("id", 42),
+                        Mutation::insert("person").set("id", 43),
+                    ]);
+                    Ok(())
+                })
+            })
+            .await
+            .unwrap();
+        let stats = outcome.commit_response().unwrap().commit_stats().unwrap();
+        assert_eq!(stats.mutation_count(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_begin() {
+        let mock = MockConnection::new()
+            .with_row_count("DELETE FROM person WHERE true", 2)
+            .abort_commit(2);
+        let client = mock.client();
+        let mut runner = client.read_write();
+
+        let mut tx = runner.begin().await.unwrap();
+        assert_eq!(
+            tx.execute_update("DELETE FROM person WHERE true", &[])
+                .await
+                .unwrap(),
+            2
+        );
+        assert!(tx.commit().await.unwrap().is_some());
+
+        // aborted commits are not retried
+        let mut tx = runner.begin().await.unwrap();
+        tx.execute_update("DELETE FROM person WHERE true", &[])
+            .await
+            .unwrap();
+        assert!(matches!(tx.commit().await, Err(Error::Aborted(_))));
+        assert_eq!(mock.commits(), 2);
+
+        let mut tx = runner.begin().await.unwrap();
+        tx.execute_update("DELETE FROM person WHERE true", &[])
+            .await
+            .unwrap();
+        tx.rollback().await.unwrap();
+        assert_eq!(mock.rollbacks(), 1);
+
+        // no work was done
+        let tx = runner.begin().await.unwrap();
+        assert!(tx.commit().await.unwrap().is_none());
+        assert_eq!(mock.commits(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_mock_partitions() {
+        use futures_util::TryStreamExt;
+
+        let names = |names: &[&str]| {
+            ResultSet::new(
+                StructType::new(vec![("name", Type::String)]),
+                names
+                    .iter()
+                    .map(|name| vec![Value::String(name.to_string())])
+                    .collect(),
+            )
+            .unwrap()
+        };
+        let mock = MockConnection::new().with_partitions(
+            "SELECT name FROM person",
+            vec![names(&["ferris", "corro"]), names(&[]), names(&["crab"])],
+        );
+        let client = mock.client();
+
+        let partitions = client
+            .partition_query("SELECT name FROM person", TimestampBound::Strong)
+            .await
+            .unwrap();
+        assert_eq!(partitions.len(), 3);
+
+        let rows: Vec<_> = client
+            .execute_partitions(partitions.clone(), 2)
+            .try_collect()
+            .await
+            .unwrap();
+        let names: Vec<&str> = rows.iter().map(|row| row.get_unchecked(0)).collect();
+        assert_eq!(names, vec!["ferris", "corro", "crab"]);
+
+        let rows: Vec<_> = client
+            .execute_partitions_unordered(partitions, 2)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_batch_read_only() {
+        use futures_util::TryStreamExt;
+
+        let mock = MockConnection::new()
+            .with_partitions("SELECT name FROM person", vec![people(), people()])
+            .with_result_set("SELECT name FROM pet", people());
+        let client = mock.client();
+
+        let batch = client
+            .batch_read_only(TimestampBound::Strong)
+            .await
+            .unwrap();
+        assert!(batch.read_timestamp().is_some());
+
+        let people: Vec<_> = batch
+            .export("SELECT name FROM person", 2)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(people.len(), 4);
+        let pets = batch.partition_query("SELECT name FROM pet").await.unwrap();
+        assert_eq!(pets.len(), 1);
+        assert_eq!(pets[0].statement().sql(), "SELECT name FROM pet");
+        batch.close().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_mock_batch_read_only_single_use_bound() {
+        let mock = MockConnection::new();
+        let client = mock.client();
+
+        let result = client
+            .batch_read_only(TimestampBound::MaxStaleness(
+                std::time::Duration::from_secs(10),
+            ))
+            .await;
+        assert!(matches!(result, Err(Error::Client(_))));
+    }
+}
@@ -0,0 +1,729 @@
+use std::collections::BTreeMap;
+
+use google_api_proto::google::spanner::v1 as proto;
+use prost::Message;
+use prost_types::{ListValue, Value as SpannerValue};
+
+#[cfg(doc)]
+use crate::{Client, TransactionContext};
+use crate::{Error, TableRow, ToRow, ToSpanner, Value};
+
+/// The maximum number of mutations Cloud Spanner accepts in a single commit, see [`Mutation::mutation_count`].
+pub const MAX_COMMIT_MUTATIONS: usize = 80_000;
+
+// Cloud Spanner rejects commits larger than this, including each group of a batch write.
+pub(crate) const MAX_COMMIT_BYTES: usize = 100 * 1024 * 1024;
+
+// Cloud Spanner rejects rows whose key, i.e.: the total size of their key columns, is larger than this.
+pub(crate) const MAX_KEY_BYTES: usize = 8 * 1024;
+
+/// A write to a single table that is applied atomically when its transaction commits,
+/// see [`TransactionContext::buffer_write`].
+///
+/// Unlike DML, mutations are not visible to the reads of the transaction that buffers them.
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{KeySet, Mutation};
+///
+/// let insert = Mutation::insert("person")
+///     .set("id", 42)
+///     .set("name", "ferris");
+/// let delete = Mutation::delete("person", KeySet::new().key(&[&43]));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Mutation {
+    table: String,
+    operation: Operation,
+    // The names of the table's primary key columns, when known, used to check the size of keys.
+    key_columns: Vec<String>,
+    // The first value conversion error, reported when the transaction commits.
+    error: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+enum Operation {
+    Write(WriteKind, Vec<(String, Value)>),
+    Delete(KeySet),
+}
+
+#[derive(Clone, Copy, Debug)]
+enum WriteKind {
+    Insert,
+    Update,
+    InsertOrUpdate,
+    Replace,
+}
+
+impl Mutation {
+    fn write(kind: WriteKind, table: impl Into<String>) -> Self {
+        Self {
+            table: table.into(),
+            operation: Operation::Write(kind, Vec::new()),
+            key_columns: Vec::new(),
+            error: None,
+        }
+    }
+
+    /// Inserts a new row into `table`. The commit fails if the row already exists.
+    pub fn insert(table: impl Into<String>) -> Self {
+        Self::write(WriteKind::Insert, table)
+    }
+
+    /// Updates an existing row of `table`. The commit fails if the row does not exist.
+    pub fn update(table: impl Into<String>) -> Self {
+        Self::write(WriteKind::Update, table)
+    }
+
+    /// Inserts a new row into `table` or updates the columns of the row if it already exists.
+    pub fn insert_or_update(table: impl Into<String>) -> Self {
+        Self::write(WriteKind::InsertOrUpdate, table)
+    }
+
+    /// Inserts a new row into `table` or replaces the row if it already exists.
+    ///
+    /// Unlike [`Mutation::insert_or_update`], the columns that are not set are reset to `NULL` when the row is replaced.
+    pub fn replace(table: impl Into<String>) -> Self {
+        Self::write(WriteKind::Replace, table)
+    }
+
+    /// Deletes the rows of `table` whose primary keys are in `keys`. Missing rows are ignored.
+    pub fn delete(table: impl Into<String>, keys: KeySet) -> Self {
+        Self {
+            table: table.into(),
+            error: keys.error.clone(),
+            operation: Operation::Delete(keys),
+            key_columns: Vec::new(),
+        }
+    }
+
+    /// Sets the value of a column of the written row, replacing any value previously set for the same column.
+    ///
+    /// See [`ToSpanner`] to determine how Rust values are mapped to Cloud Spanner values.
+    /// Any conversion error is returned when the transaction commits.
+    ///
+    /// This has no effect on a [`Mutation::delete`].
+    #[must_use]
+    pub fn set<T>(self, column: impl Into<String>, value: T) -> Self
+    where
+        T: ToSpanner,
+    {
+        let column = column.into();
+        if self.error.is_some() {
+            return self;
+        }
+        match value.to_spanner() {
+            Ok(value) => self.set_value(column, value),
+            Err(err) => Self {
+                error: Some(format!("invalid value for column {}: {}", column, err)),
+                ..self
+            },
+        }
+    }
+
+    /// Sets the [`Value`] of a column of the written row, replacing any value previously set for the same column.
+    ///
+    /// This has no effect on a [`Mutation::delete`].
+    #[must_use]
+    pub fn set_value(mut self, column: impl Into<String>, value: Value) -> Self {
+        if let Operation::Write(_, columns) = &mut self.operation {
+            let column = column.into();
+            match columns.iter_mut().find(|(name, _)| *name == column) {
+                Some((_, set)) => *set = value,
+                None => columns.push((column, value)),
+            }
+        }
+        self
+    }
+
+    /// Sets the columns of the written row to those of a [`ToRow`], e.g.: a struct deriving it,
+    /// replacing any value previously set for the same columns.
+    ///
+    /// Any conversion error is returned when the transaction commits.
+    ///
+    /// This has no effect on a [`Mutation::delete`].
+    #[must_use]
+    pub fn set_row(self, row: &dyn ToRow) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        match row.to_row() {
+            Ok(columns) => columns.into_iter().fold(self, |mutation, (column, value)| {
+                mutation.set_value(column, value)
+            }),
+            Err(err) => Self {
+                error: Some(format!("invalid row: {}", err)),
+                ..self
+            },
+        }
+    }
+
+    /// Specifies the names of the table's primary key columns, in the order of the table's primary key.
+    ///
+    /// Cloud Spanner limits the size of a row's key to 8 KiB. When the key columns are known, a commit writing or deleting
+    /// a larger key fails with an [`Error::Client`] naming the offending column, without reaching Cloud Spanner.
+    /// This is done for the mutations of a [`TableRow`] which specifies its [`TableRow::KEY_COLUMNS`].
+    #[must_use]
+    pub fn with_key_columns(self, columns: &[&str]) -> Self {
+        Self {
+            key_columns: columns.iter().map(ToString::to_string).collect(),
+            ..self
+        }
+    }
+
+    /// Returns the name of the table this mutation writes to.
+    pub fn table(&self) -> &str {
+        &self.table
+    }
+
+    /// Estimates the number of mutations Cloud Spanner counts against its per-commit limit, see [`MAX_COMMIT_MUTATIONS`].
+    ///
+    /// Writes count one mutation per column set, deletes count one per key (or one when deleting every row).
+    /// This is a lower bound: Cloud Spanner also counts the secondary index entries affected by the mutation,
+    /// which depend on the schema.
+    pub fn mutation_count(&self) -> usize {
+        match &self.operation {
+            Operation::Write(_, columns) => columns.len(),
+            Operation::Delete(keys) => keys.keys.len().max(1),
+        }
+    }
+}
+
+/// Splits mutations into chunks that can each be committed on their own, e.g.: using [`Client::write_chunked`].
+///
+/// Chunks preserve the order of the mutations and their estimated [`Mutation::mutation_count`] does not exceed `max_mutations`,
+/// except for a mutation that exceeds it by itself, which is placed in a chunk of its own.
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{chunk_mutations, Mutation};
+///
+/// let mutations = (0..10).map(|id| Mutation::insert("person").set("id", id).set("name", "ferris"));
+/// let chunks = chunk_mutations(mutations, 8);
+/// assert_eq!(chunks.len(), 3);
+/// ```
+pub fn chunk_mutations(
+    mutations: impl IntoIterator<Item = Mutation>,
+    max_mutations: usize,
+) -> Vec<Vec<Mutation>> {
+    let mut chunks = Vec::new();
+    let mut chunk = Vec::new();
+    let mut count = 0;
+    for mutation in mutations {
+        let mutation_count = mutation.mutation_count();
+        if !chunk.is_empty() && count + mutation_count > max_mutations {
+            chunks.push(std::mem::take(&mut chunk));
+            count = 0;
+        }
+        count += mutation_count;
+        chunk.push(mutation);
+    }
+    if !chunk.is_empty() {
+        chunks.push(chunk);
+    }
+    chunks
+}
+
+/// Checks that the mutations of a commit stay within the limits of Cloud Spanner, before sending them.
+pub(crate) fn check_commit(mutations: &[Mutation]) -> Result<(), Error> {
+    check_commit_within(mutations, MAX_COMMIT_MUTATIONS, MAX_COMMIT_BYTES)
+}
+
+/// Checks that the mutations of a commit stay within `max_mutations` and `max_bytes` once encoded, and that their keys
+/// stay within [`MAX_KEY_BYTES`].
+///
+/// The error names the table with the most mutations or bytes, which is usually the one to write using [`Client::write_chunked`],
+/// along with its largest column.
+pub(crate) fn check_commit_within(
+    mutations: &[Mutation],
+    max_mutations: usize,
+    max_bytes: usize,
+) -> Result<(), Error> {
+    check_keys(mutations)?;
+
+    let count = mutations
+        .iter()
+        .map(Mutation::mutation_count)
+        .sum::<usize>();
+    if count > max_mutations {
+        let mut counts = BTreeMap::<&str, usize>::new();
+        for mutation in mutations {
+            *counts.entry(mutation.table()).or_default() += mutation.mutation_count();
+        }
+        let (table, table_count) = largest(counts);
+        return Err(Error::Client(format!(
+            "commit has {} mutations, which exceeds the limit of {} mutations: table {} has {} of them",
+            count, max_mutations, table, table_count
+        )));
+    }
+
+    let mut sizes = BTreeMap::<&str, usize>::new();
+    for mutation in mutations {
+        let size = proto::Mutation::try_from(mutation.clone())?.encoded_len();
+        *sizes.entry(mutation.table()).or_default() += size;
+    }
+    let size = sizes.values().sum::<usize>();
+    if size > max_bytes {
+        let (table, table_size) = largest(sizes);
+        let mut columns = BTreeMap::<&str, usize>::new();
+        for mutation in mutations
+            .iter()
+            .filter(|mutation| mutation.table() == table)
+        {
+            if let Operation::Write(_, values) = &mutation.operation {
+                for (column, value) in values {
+                    *columns.entry(column.as_str()).or_default() +=
+                        SpannerValue::try_from(value.clone())?.encoded_len();
+                }
+            }
+        }
+        let (column, column_size) = largest(columns);
+        return Err(Error::Client(format!(
+            "commit is {} bytes, which exceeds the limit of {} bytes: table {} has {} bytes of them, {} in column {}",
+            size, max_bytes, table, table_size, column_size, column
+        )));
+    }
+    Ok(())
+}
+
+/// Checks that the keys written or deleted by mutations whose key columns are known stay within [`MAX_KEY_BYTES`].
+pub(crate) fn check_keys(mutations: &[Mutation]) -> Result<(), Error> {
+    for mutation in mutations
+        .iter()
+        .filter(|mutation| !mutation.key_columns.is_empty())
+    {
+        let keys: Vec<Vec<(&str, &Value)>> = match &mutation.operation {
+            Operation::Write(_, values) => vec![mutation
+                .key_columns
+                .iter()
+                .filter_map(|column| {
+                    values
+                        .iter()
+                        .find(|(name, _)| name == column)
+                        .map(|(name, value)| (name.as_str(), value))
+                })
+                .collect()],
+            Operation::Delete(keys) => keys
+                .keys
+                .iter()
+                .map(|key| {
+                    mutation
+                        .key_columns
+                        .iter()
+                        .map(String::as_str)
+                        .zip(key)
+                        .collect()
+                })
+                .collect(),
+        };
+        for key in keys {
+            let size = key.iter().map(|(_, value)| key_size(value)).sum::<usize>();
+            if size > MAX_KEY_BYTES {
+                let (column, column_size) = key
+                    .iter()
+                    .map(|(column, value)| (*column, key_size(value)))
+                    .max_by_key(|(_, size)| *size)
+                    .unwrap_or_default();
+                return Err(Error::Client(format!(
+                    "key of a row of table {} is {} bytes, which exceeds the limit of {} bytes: column {} is {} bytes",
+                    mutation.table, size, MAX_KEY_BYTES, column, column_size
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+// Estimates the size of a key column, which is dominated by strings and bytes since other key types are small.
+fn key_size(value: &Value) -> usize {
+    match value {
+        Value::Null(_) => 0,
+        Value::String(value) => value.len(),
+        Value::Bytes(value) => value.len(),
+        _ => 8,
+    }
+}
+
+// Returns the entry with the largest value.
+fn largest(entries: BTreeMap<&str, usize>) -> (&str, usize) {
+    entries
+        .into_iter()
+        .max_by_key(|(_, value)| *value)
+        .unwrap_or_default()
+}
+
+impl TryFrom<Mutation> for proto::Mutation {
+    type Error = Error;
+
+    fn try_from(mutation: Mutation) -> Result<Self, Self::Error> {
+        if let Some(error) = mutation.error {
+            return Err(Error::Codec(error));
+        }
+        let operation = match mutation.operation {
+            Operation::Write(kind, columns) => {
+                let (columns, values): (Vec<String>, Vec<Value>) = columns.into_iter().unzip();
+                let write = proto::mutation::Write {
+                    table: mutation.table,
+                    columns,
+                    values: vec![list_value(values)?],
+                };
+                match kind {
+                    WriteKind::Insert => proto::mutation::Operation::Insert(write),
+                    WriteKind::Update => proto::mutation::Operation::Update(write),
+                    WriteKind::InsertOrUpdate => proto::mutation::Operation::InsertOrUpdate(write),
+                    WriteKind::Replace => proto::mutation::Operation::Replace(write),
+                }
+            }
+            Operation::Delete(keys) => {
+                proto::mutation::Operation::Delete(proto::mutation::Delete {
+                    table: mutation.table,
+                    key_set: Some(keys.try_into()?),
+                })
+            }
+        };
+        Ok(proto::Mutation {
+            operation: Some(operation),
+        })
+    }
+}
+
+/// A set of rows identified by their primary keys, see [`Mutation::delete`].
+#[derive(Clone, Debug, Default)]
+pub struct KeySet {
+    keys: Vec<Vec<Value>>,
+    all: bool,
+    // The first value conversion error, reported when the transaction commits.
+    error: Option<String>,
+}
+
+impl KeySet {
+    /// Creates an empty set of keys.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a set that contains every row of a table.
+    pub fn all() -> Self {
+        Self {
+            all: true,
+            ..Self::default()
+        }
+    }
+
+    /// Adds a primary key to this set, the values being in the order of the table's primary key columns.
+    ///
+    /// Any conversion error is returned when the transaction commits.
+    #[must_use]
+    pub fn key(mut self, values: &[&(dyn ToSpanner + Sync)]) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        match values.iter().map(|value| value.to_spanner()).collect() {
+            Ok(key) => self.keys.push(key),
+            Err(err) => self.error = Some(format!("invalid key value: {}", err)),
+        }
+        self
+    }
+
+    /// Adds the primary key of a [`TableRow`] to this set, see [`TableRow::delete_by_key`].
+    ///
+    /// Any conversion error is returned when the transaction commits.
+    #[must_use]
+    pub fn row<T>(mut self, row: &T) -> Self
+    where
+        T: TableRow,
+    {
+        if self.error.is_some() {
+            return self;
+        }
+        match row.key() {
+            Ok(key) => self.keys.push(key),
+            Err(err) => self.error = Some(format!("invalid key value: {}", err)),
+        }
+        self
+    }
+}
+
+impl TryFrom<KeySet> for proto::KeySet {
+    type Error = Error;
+
+    fn try_from(keys: KeySet) -> Result<Self, Self::Error> {
+        Ok(proto::KeySet {
+            keys: keys
+                .keys
+                .into_iter()
+                .map(list_value)
+                .collect::<Result<_, _>>()?,
+            ranges: vec![],
+            all: keys.all,
+        })
+    }
+}
+
+fn list_value(values: Vec<Value>) -> Result<ListValue, Error> {
+    Ok(ListValue {
+        values: values
+            .into_iter()
+            .map(SpannerValue::try_from)
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use prost_types::value::Kind;
+
+    #[test]
+    fn test_mutation_write() {
+        let mutation: proto::Mutation = Mutation::insert_or_update("person")
+            .set("id", 42)
+            .set("name", "corro")
+            .set("name", "ferris")
+            .try_into()
+            .unwrap();
+        match mutation.operation {
+            Some(proto::mutation::Operation::InsertOrUpdate(write)) => {
+                assert_eq!(write.table, "person");
+                assert_eq!(write.columns, vec!["id", "name"]);
+                assert_eq!(write.values.len(), 1);
+                assert_eq!(
+                    write.values[0].values[1].kind,
+                    Some(Kind::StringValue("ferris".to_string()))
+                );
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mutation_delete() {
+        let mutation: proto::Mutation =
+            Mutation::delete("person", KeySet::new().key(&[&42]).key(&[&43]))
+                .try_into()
+                .unwrap();
+        match mutation.operation {
+            Some(proto::mutation::Operation::Delete(delete)) => {
+                let key_set = delete.key_set.unwrap();
+                assert_eq!(key_set.keys.len(), 2);
+                assert!(!key_set.all);
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        let mutation: proto::Mutation = Mutation::delete("person", KeySet::all())
+            .try_into()
+            .unwrap();
+        match mutation.operation {
+            Some(proto::mutation::Operation::Delete(delete)) => {
+                assert!(delete.key_set.unwrap().all)
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mutation_set_row() {
+        struct Person {
+            id: i64,
+            name: &'static str,
+        }
+        impl ToRow for Person {
+            fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error> {
+                Ok(vec![
+                    ("id", self.id.to_spanner()?),
+                    ("name", self.name.to_spanner()?),
+                ])
+            }
+        }
+
+        let mutation: proto::Mutation = Mutation::update("person")
+            .set("name", "corro")
+            .set_row(&Person {
+                id: 42,
+                name: "ferris",
+            })
+            .try_into()
+            .unwrap();
+        match mutation.operation {
+            Some(proto::mutation::Operation::Update(write)) => {
+                assert_eq!(write.columns, vec!["name", "id"]);
+                assert_eq!(
+                    write.values[0].values[0].kind,
+                    Some(Kind::StringValue("ferris".to_string()))
+                );
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_key_set_row() {
+        struct Album {
+            singer_id: i64,
+            id: Option<i64>,
+        }
+        impl ToRow for Album {
+            fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error> {
+                Ok(vec![
+                    ("singer_id", self.singer_id.to_spanner()?),
+                    ("id", self.id.to_spanner()?),
+                ])
+            }
+        }
+        impl TableRow for Album {
+            const TABLE: &'static str = "album";
+
+            fn key(&self) -> Result<Vec<Value>, Error> {
+                match self.id {
+                    Some(id) => Ok(vec![self.singer_id.to_spanner()?, id.to_spanner()?]),
+                    None => Err(Error::Client("missing album id".to_string())),
+                }
+            }
+        }
+
+        let album = Album {
+            singer_id: 1,
+            id: Some(2),
+        };
+        let mutation: proto::Mutation = album.delete_by_key().try_into().unwrap();
+        match mutation.operation {
+            Some(proto::mutation::Operation::Delete(delete)) => {
+                assert_eq!(delete.table, "album");
+                let key_set = delete.key_set.unwrap();
+                assert_eq!(key_set.keys.len(), 1);
+                assert_eq!(key_set.keys[0].values.len(), 2);
+            }
+            other => panic!("unexpected operation: {:?}", other),
+        }
+
+        let invalid = Album {
+            singer_id: 1,
+            id: None,
+        };
+        assert!(proto::Mutation::try_from(invalid.delete_by_key()).is_err());
+    }
+
+    #[test]
+    fn test_mutation_count() {
+        let insert = Mutation::insert("person")
+            .set("id", 42)
+            .set("name", "ferris");
+        assert_eq!(insert.mutation_count(), 2);
+        let delete = Mutation::delete("person", KeySet::new().key(&[&42]).key(&[&43]));
+        assert_eq!(delete.mutation_count(), 2);
+        assert_eq!(
+            Mutation::delete("person", KeySet::all()).mutation_count(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_chunk_mutations() {
+        let mutations = vec![
+            Mutation::insert("person")
+                .set("id", 1)
+                .set("name", "ferris"),
+            Mutation::insert("person").set("id", 2).set("name", "corro"),
+            Mutation::delete("person", KeySet::new().key(&[&3])),
+            Mutation::insert("person")
+                .set("id", 4)
+                .set("name", "bob")
+                .set("age", 42)
+                .set("email", "bob@example.com"),
+            Mutation::delete("person", KeySet::new().key(&[&5])),
+        ];
+        let chunks = chunk_mutations(mutations, 3);
+        let sizes = chunks.iter().map(Vec::len).collect::<Vec<_>>();
+        // the 4 columns insert exceeds the limit by itself
+        assert_eq!(sizes, vec![1, 2, 1, 1]);
+        assert!(chunk_mutations(Vec::new(), 3).is_empty());
+    }
+
+    #[test]
+    fn test_check_commit_mutations() {
+        let mutations = vec![
+            Mutation::insert("person")
+                .set("id", 1)
+                .set("name", "ferris"),
+            Mutation::delete("pet", KeySet::new().key(&[&1])),
+            Mutation::insert("person").set("id", 2).set("name", "corro"),
+        ];
+        assert!(check_commit_within(&mutations, 5, MAX_COMMIT_BYTES).is_ok());
+        match check_commit_within(&mutations, 4, MAX_COMMIT_BYTES) {
+            Err(Error::Client(message)) => {
+                assert!(message.contains("5 mutations"), "{}", message);
+                assert!(message.contains("table person has 4"), "{}", message);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_commit_bytes() {
+        let mutations = vec![
+            Mutation::insert("person")
+                .set("id", 1)
+                .set("name", "ferris")
+                .set("bio", "a".repeat(1000)),
+            Mutation::insert("pet").set("id", 1).set("name", "corro"),
+        ];
+        assert!(check_commit_within(&mutations, MAX_COMMIT_MUTATIONS, 2000).is_ok());
+        match check_commit_within(&mutations, MAX_COMMIT_MUTATIONS, 1000) {
+            Err(Error::Client(message)) => {
+                assert!(message.contains("table person"), "{}", message);
+                assert!(message.contains("in column bio"), "{}", message);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_check_keys() {
+        let long = "a".repeat(MAX_KEY_BYTES);
+        let write = |name: &str| {
+            Mutation::insert("person")
+                .set("id", 1)
+                .set("name", name)
+                .with_key_columns(&["id", "name"])
+        };
+        assert!(check_keys(&[write("ferris")]).is_ok());
+        // the key columns are unknown
+        assert!(check_keys(&[Mutation::insert("person").set("name", long.as_str())]).is_ok());
+        match check_keys(&[write(&long)]) {
+            Err(Error::Client(message)) => {
+                assert!(message.contains("table person"), "{}", message);
+                assert!(message.contains("column name"), "{}", message);
+            }
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let delete = Mutation::delete(
+            "person",
+            KeySet::new().key(&[&1, &"ferris"]).key(&[&2, &long]),
+        )
+        .with_key_columns(&["id", "name"]);
+        match check_keys(&[delete]) {
+            Err(Error::Client(message)) => assert!(message.contains("column name"), "{}", message),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_mutation_error() {
+        struct Invalid;
+        impl ToSpanner for Invalid {
+            fn to_spanner(&self) -> Result<Value, Error> {
+                Err(Error::Codec("invalid".to_string()))
+            }
+        }
+
+        let mutation = Mutation::insert("person").set("name", Invalid);
+        assert!(matches!(
+            proto::Mutation::try_from(mutation),
+            Err(Error::Codec(_))
+        ));
+    }
+}
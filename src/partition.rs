@@ -0,0 +1,173 @@
+use google_api_proto::google::spanner::v1 as proto;
+use prost::bytes::Bytes;
+use prost::Message;
+
+#[cfg(doc)]
+use crate::Client;
+use crate::{Dialect, Error, Session, Statement, Transaction, TransactionSelector, Type, Value};
+
+/// A partition of a query that can be executed independently of, and in parallel with, the query's other partitions,
+/// see [`Client::partition_query`].
+///
+/// All the partitions of a query read from the same read-only transaction, i.e.: they observe a consistent snapshot of the data.
+///
+/// Partitions can be handed to other processes, e.g.: from a coordinator to a fleet of workers,
+/// see [`QueryPartition::to_bytes`] and [`QueryPartition::from_bytes`]. When the `serde` feature is enabled (it is
+/// enabled by the `json` feature), partitions also implement `serde::Serialize` and `serde::Deserialize` as a base64 string.
+#[derive(Clone, Debug)]
+pub struct QueryPartition {
+    pub(crate) session: Session,
+    pub(crate) transaction: Transaction,
+    pub(crate) statement: Statement,
+    pub(crate) token: Bytes,
+}
+
+impl QueryPartition {
+    /// Returns the partitioned statement.
+    pub fn statement(&self) -> &Statement {
+        &self.statement
+    }
+
+    /// Encodes this partition so that it can be executed by another process, see [`QueryPartition::from_bytes`].
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        // Parameters are decoded into values, so they are encoded independently of the database's dialect.
+        let (params, param_types) =
+            Dialect::GoogleStandardSql.encode_parameters(&self.statement)?;
+        let request = proto::ExecuteSqlRequest {
+            session: self.session.name().to_string(),
+            transaction: Some(TransactionSelector::Id(self.transaction.clone()).try_into()?),
+            sql: self.statement.sql().to_string(),
+            params: Some(prost_types::Struct { fields: params }),
+            param_types,
+            partition_token: self.token.clone(),
+            ..Default::default()
+        };
+        Ok(request.encode_to_vec())
+    }
+
+    /// Decodes a partition encoded using [`QueryPartition::to_bytes`], which can then be executed using [`Client::execute_partition`].
+    ///
+    /// The executing client must be connected to the database that the partition was created for,
+    /// and the partition's session must not have been deleted.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let request = proto::ExecuteSqlRequest::decode(bytes)
+            .map_err(|err| Error::Codec(format!("invalid partition: {}", err)))?;
+        let transaction = match request.transaction.and_then(|selector| selector.selector) {
+            Some(proto::transaction_selector::Selector::Id(id)) => proto::Transaction {
+                id,
+                ..Default::default()
+            }
+            .into(),
+            _ => {
+                return Err(Error::Codec(
+                    "invalid partition: missing transaction".to_string(),
+                ))
+            }
+        };
+        let mut params = request.params.unwrap_or_default().fields;
+        let statement = request.param_types.iter().try_fold(
+            Statement::new(request.sql),
+            |statement, (name, tpe)| {
+                let value = params.remove(name).ok_or_else(|| {
+                    Error::Codec(format!("invalid partition: missing parameter {}", name))
+                })?;
+                let value = Value::try_from(&Type::try_from(tpe)?, value)?;
+                Ok::<_, Error>(statement.bind_value(name.clone(), value))
+            },
+        )?;
+        Ok(Self {
+            session: proto::Session {
+                name: request.session,
+                ..Default::default()
+            }
+            .into(),
+            transaction,
+            statement,
+            token: request.partition_token,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for QueryPartition {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let bytes = self.to_bytes().map_err(serde::ser::Error::custom)?;
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for QueryPartition {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = base64::decode(encoded).map_err(serde::de::Error::custom)?;
+        Self::from_bytes(&bytes).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn partition() -> QueryPartition {
+        QueryPartition {
+            session: proto::Session {
+                name: "projects/p/instances/i/databases/d/sessions/s".to_string(),
+                ..Default::default()
+            }
+            .into(),
+            transaction: proto::Transaction {
+                id: Bytes::from_static(b"tx"),
+                ..Default::default()
+            }
+            .into(),
+            statement: Statement::new("SELECT name FROM person WHERE id > @id AND name != @name")
+                .bind("id", 42)
+                .bind("name", "ferris"),
+            token: Bytes::from_static(b"token"),
+        }
+    }
+
+    fn assert_same(decoded: &QueryPartition, partition: &QueryPartition) {
+        assert_eq!(decoded.session.name(), partition.session.name());
+        assert_eq!(decoded.transaction.id(), partition.transaction.id());
+        assert_eq!(decoded.statement.sql(), partition.statement.sql());
+        let mut params: Vec<_> = decoded.statement.params().collect();
+        params.sort_by_key(|(name, _)| *name);
+        assert_eq!(
+            params,
+            vec![
+                ("id", &Value::Int64(42)),
+                ("name", &Value::String("ferris".to_string()))
+            ]
+        );
+        assert_eq!(decoded.token, partition.token);
+    }
+
+    #[test]
+    fn test_partition_bytes() {
+        let partition = partition();
+        let decoded = QueryPartition::from_bytes(&partition.to_bytes().unwrap()).unwrap();
+        assert_same(&decoded, &partition);
+
+        assert!(matches!(
+            QueryPartition::from_bytes(b"not a partition"),
+            Err(Error::Codec(_))
+        ));
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_partition_serde() {
+        let partition = partition();
+        let json = serde_json::to_string(&partition).unwrap();
+        let decoded: QueryPartition = serde_json::from_str(&json).unwrap();
+        assert_same(&decoded, &partition);
+    }
+}
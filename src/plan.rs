@@ -0,0 +1,225 @@
+use std::collections::BTreeMap;
+
+use google_api_proto::google::spanner::v1 as proto;
+use prost_types::value::Kind;
+
+/// The execution plan of a query, as determined by Cloud Spanner without executing it.
+///
+/// Plans are obtained using [`crate::Client::estimate`] and can be used to flag statements that would scan
+/// entire tables, e.g.: in CI checks.
+///
+/// Note that Cloud Spanner does not report the number of rows it expects to scan or return in a plan,
+/// only its structure (see [the Cloud Spanner documentation](https://cloud.google.com/spanner/docs/query-execution-plans)).
+#[derive(Clone, Debug)]
+pub struct QueryPlan {
+    nodes: Vec<PlanNode>,
+}
+
+/// A node of a [`QueryPlan`].
+#[derive(Clone, Debug)]
+pub struct PlanNode {
+    display_name: String,
+    relational: bool,
+    children: Vec<usize>,
+    metadata: BTreeMap<String, String>,
+}
+
+/// A scan of a table or index, see [`QueryPlan::scans`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Scan {
+    target: String,
+    index: bool,
+    full: bool,
+}
+
+impl QueryPlan {
+    /// Returns the nodes of this plan, the first one being the root.
+    pub fn nodes(&self) -> &[PlanNode] {
+        &self.nodes
+    }
+
+    /// Returns the scans of tables or indexes done by this plan.
+    pub fn scans(&self) -> Vec<Scan> {
+        self.nodes
+            .iter()
+            .filter(|node| node.relational && node.display_name == "Scan")
+            .filter_map(|node| {
+                let metadata = |key: &str| node.metadata.get(key).map(String::as_str);
+                Some(Scan {
+                    target: metadata("scan_target")?.to_string(),
+                    index: metadata("scan_type") == Some("IndexScan"),
+                    full: metadata("Full scan") == Some("true"),
+                })
+            })
+            .collect()
+    }
+
+    /// Returns true when at least one index is scanned by this plan.
+    pub fn uses_index(&self) -> bool {
+        self.scans().iter().any(Scan::is_index)
+    }
+
+    /// Returns true when at least one table or index is scanned entirely by this plan.
+    pub fn has_full_scan(&self) -> bool {
+        self.scans().iter().any(Scan::is_full)
+    }
+}
+
+impl PlanNode {
+    /// Returns the display name of this node, e.g.: `Scan`, `Filter` or `Distributed Union`.
+    pub fn display_name(&self) -> &str {
+        &self.display_name
+    }
+
+    /// Returns true for relational operators (which produce rows), false for scalar ones (which produce values).
+    pub fn is_relational(&self) -> bool {
+        self.relational
+    }
+
+    /// Returns the indexes (in [`QueryPlan::nodes`]) of the children of this node.
+    pub fn children(&self) -> &[usize] {
+        &self.children
+    }
+
+    /// Returns the node's type-specific metadata, e.g.: `scan_type` and `scan_target` for `Scan` nodes.
+    pub fn metadata(&self) -> &BTreeMap<String, String> {
+        &self.metadata
+    }
+}
+
+impl Scan {
+    /// Returns the name of the scanned table or index.
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    /// Returns true when an index is scanned, false when a table is scanned.
+    pub fn is_index(&self) -> bool {
+        self.index
+    }
+
+    /// Returns true when the table or index is scanned entirely.
+    pub fn is_full(&self) -> bool {
+        self.full
+    }
+}
+
+impl From<proto::QueryPlan> for QueryPlan {
+    fn from(value: proto::QueryPlan) -> Self {
+        let nodes = value
+            .plan_nodes
+            .into_iter()
+            .map(|node| PlanNode {
+                relational: node.kind == proto::plan_node::Kind::Relational as i32,
+                display_name: node.display_name,
+                children: node
+                    .child_links
+                    .iter()
+                    .map(|link| link.child_index as usize)
+                    .collect(),
+                metadata: node
+                    .metadata
+                    .map(|metadata| {
+                        metadata
+                            .fields
+                            .into_iter()
+                            .filter_map(|(key, value)| Some((key, metadata_value(value.kind?)?)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+            })
+            .collect();
+        Self { nodes }
+    }
+}
+
+fn metadata_value(kind: Kind) -> Option<String> {
+    match kind {
+        Kind::StringValue(value) => Some(value),
+        Kind::BoolValue(value) => Some(value.to_string()),
+        Kind::NumberValue(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn node(
+        display_name: &str,
+        children: Vec<i32>,
+        metadata: Vec<(&str, Kind)>,
+    ) -> proto::PlanNode {
+        proto::PlanNode {
+            index: 0,
+            kind: proto::plan_node::Kind::Relational as i32,
+            display_name: display_name.to_string(),
+            child_links: children
+                .into_iter()
+                .map(|child_index| proto::plan_node::ChildLink {
+                    child_index,
+                    r#type: String::new(),
+                    variable: String::new(),
+                })
+                .collect(),
+            short_representation: None,
+            metadata: Some(prost_types::Struct {
+                fields: metadata
+                    .into_iter()
+                    .map(|(key, kind)| (key.to_string(), prost_types::Value { kind: Some(kind) }))
+                    .collect(),
+            }),
+            execution_stats: None,
+        }
+    }
+
+    #[test]
+    fn test_query_plan() {
+        let plan = QueryPlan::from(proto::QueryPlan {
+            plan_nodes: vec![
+                node("Distributed Union", vec![1, 2], vec![]),
+                node(
+                    "Scan",
+                    vec![],
+                    vec![
+                        ("scan_type", Kind::StringValue("TableScan".to_string())),
+                        ("scan_target", Kind::StringValue("person".to_string())),
+                        ("Full scan", Kind::StringValue("true".to_string())),
+                    ],
+                ),
+                node(
+                    "Scan",
+                    vec![],
+                    vec![
+                        ("scan_type", Kind::StringValue("IndexScan".to_string())),
+                        (
+                            "scan_target",
+                            Kind::StringValue("person_by_name".to_string()),
+                        ),
+                    ],
+                ),
+            ],
+        });
+
+        assert_eq!(plan.nodes().len(), 3);
+        assert_eq!(plan.nodes()[0].children(), &[1, 2]);
+        assert_eq!(
+            plan.scans(),
+            vec![
+                Scan {
+                    target: "person".to_string(),
+                    index: false,
+                    full: true,
+                },
+                Scan {
+                    target: "person_by_name".to_string(),
+                    index: true,
+                    full: false,
+                },
+            ]
+        );
+        assert!(plan.uses_index());
+        assert!(plan.has_full_scan());
+    }
+}
@@ -0,0 +1,493 @@
+use std::sync::Arc;
+
+#[cfg(feature = "numeric")]
+use ::arrow::array::Decimal128Array;
+use ::arrow::array::{
+    make_array, Array, ArrayData, ArrayRef, BinaryArray, BooleanArray, Float64Array, Int64Array,
+    StringArray,
+};
+#[cfg(feature = "temporal")]
+use ::arrow::array::{Date32Array, TimestampNanosecondArray};
+use ::arrow::buffer::Buffer;
+#[cfg(feature = "temporal")]
+use ::arrow::datatypes::TimeUnit;
+use ::arrow::datatypes::{DataType, Field, Schema};
+use ::arrow::error::ArrowError;
+use ::arrow::record_batch::RecordBatch;
+#[cfg(feature = "numeric")]
+use bigdecimal::{BigDecimal, ToPrimitive};
+#[cfg(feature = "temporal")]
+use chrono::{DateTime, Datelike, NaiveDate, Utc};
+use futures_util::stream::{Stream, TryStreamExt};
+
+use crate::{Error, OwnedRow, ResultSet, StructType, Type, Value};
+
+/// The precision and scale of Cloud Spanner's `NUMERIC` type.
+#[cfg(feature = "numeric")]
+const NUMERIC_PRECISION: u8 = 38;
+#[cfg(feature = "numeric")]
+const NUMERIC_SCALE: i8 = 9;
+
+/// The number of days between 0001-01-01 and 1970-01-01.
+#[cfg(feature = "temporal")]
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+impl From<ArrowError> for Error {
+    fn from(err: ArrowError) -> Self {
+        Error::Codec(format!("arrow error: {}", err))
+    }
+}
+
+impl StructType {
+    /// Returns the Arrow schema of rows of this type, see [`ResultSet::to_record_batch`] for how types are mapped.
+    pub fn to_arrow_schema(&self) -> Schema {
+        Schema::new(fields(self))
+    }
+}
+
+impl ResultSet {
+    /// Converts the rows of this result set to an Arrow [`RecordBatch`], e.g.: to query them using DataFusion or Polars.
+    ///
+    /// All columns are nullable and Cloud Spanner types are mapped to the following Arrow types:
+    ///
+    /// | Cloud Spanner | Arrow |
+    /// |---------------|-------|
+    /// | `BOOL` | `Boolean` |
+    /// | `INT64` | `Int64` |
+    /// | `FLOAT64` | `Float64` |
+    /// | `STRING` | `Utf8` |
+    /// | `BYTES` | `Binary` |
+    /// | `JSON` | `Utf8` |
+    /// | `NUMERIC` | `Decimal128(38, 9)` |
+    /// | `TIMESTAMP` | `Timestamp(Nanosecond, "UTC")` |
+    /// | `DATE` | `Date32` |
+    /// | `ARRAY` | `List` |
+    /// | `STRUCT` | `Struct` |
+    ///
+    /// Unnamed columns and struct fields are named after their position, e.g.: `_0`.
+    ///
+    /// An error is returned if a `TIMESTAMP` is outside of the range representable using nanoseconds,
+    /// i.e.: before 1677 or after 2262.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query("SELECT id, name FROM person", &[])
+    ///     .await?;
+    /// let batch = rs.to_record_batch()?;
+    /// # Ok(()) }
+    /// ```
+    pub fn to_record_batch(&self) -> Result<RecordBatch, Error> {
+        let rows = self
+            .iter()
+            .map(|row| {
+                (0..self.row_type().fields().len())
+                    .map(|index| row.get_unchecked::<&Value, _>(index))
+                    .collect()
+            })
+            .collect::<Vec<Vec<_>>>();
+        record_batch(self.row_type(), &rows)
+    }
+}
+
+/// Converts a stream of rows, e.g.: the one returned by [`crate::BatchClient::export`],
+/// to a stream of Arrow [`RecordBatch`]es of at most `batch_size` rows, see [`ResultSet::to_record_batch`].
+///
+/// The stream ends after the first error.
+pub fn record_batches<S>(
+    rows: S,
+    batch_size: usize,
+) -> impl Stream<Item = Result<RecordBatch, Error>>
+where
+    S: Stream<Item = Result<OwnedRow, Error>>,
+{
+    rows.try_chunks(batch_size.max(1))
+        .map_err(|err| err.1)
+        .and_then(|chunk| async move {
+            let row_type = chunk[0].row_type().clone();
+            let values = chunk
+                .into_iter()
+                .map(OwnedRow::into_values)
+                .collect::<Vec<_>>();
+            let rows = values
+                .iter()
+                .map(|row| row.iter().collect())
+                .collect::<Vec<Vec<_>>>();
+            record_batch(&row_type, &rows)
+        })
+}
+
+fn record_batch(row_type: &StructType, rows: &[Vec<&Value>]) -> Result<RecordBatch, Error> {
+    let columns = row_type
+        .types()
+        .enumerate()
+        .map(|(index, tpe)| {
+            let values = rows
+                .iter()
+                .map(|row| present(row[index]))
+                .collect::<Vec<_>>();
+            to_array(tpe, &values)
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(RecordBatch::try_new(
+        Arc::new(row_type.to_arrow_schema()),
+        columns,
+    )?)
+}
+
+fn fields(struct_type: &StructType) -> Vec<Field> {
+    struct_type
+        .fields()
+        .iter()
+        .enumerate()
+        .map(|(index, (name, tpe))| {
+            let name = name.clone().unwrap_or_else(|| format!("_{}", index));
+            Field::new(&name, data_type(tpe), true)
+        })
+        .collect()
+}
+
+fn data_type(tpe: &Type) -> DataType {
+    match tpe {
+        Type::Bool => DataType::Boolean,
+        Type::Int64 => DataType::Int64,
+        Type::Float64 => DataType::Float64,
+        Type::String => DataType::Utf8,
+        Type::Bytes => DataType::Binary,
+        #[cfg(feature = "json")]
+        Type::Json => DataType::Utf8,
+        #[cfg(feature = "numeric")]
+        Type::Numeric => DataType::Decimal128(NUMERIC_PRECISION, NUMERIC_SCALE),
+        #[cfg(feature = "temporal")]
+        Type::Timestamp => DataType::Timestamp(TimeUnit::Nanosecond, Some("UTC".to_string())),
+        #[cfg(feature = "temporal")]
+        Type::Date => DataType::Date32,
+        Type::Array(inner) => DataType::List(Box::new(Field::new("item", data_type(inner), true))),
+        Type::Struct(struct_type) => DataType::Struct(fields(struct_type)),
+    }
+}
+
+/// Returns `None` for `NULL` values.
+fn present(value: &Value) -> Option<&Value> {
+    match value {
+        Value::Null(_) => None,
+        value => Some(value),
+    }
+}
+
+fn mismatch(tpe: &Type, value: &Value) -> Error {
+    Error::Codec(format!(
+        "expected a value of type {:?}, got {:?}",
+        tpe, value
+    ))
+}
+
+/// Extracts the values of a column, failing on values that don't match the column's type.
+macro_rules! extract {
+    ($values:expr, $tpe:expr, $pattern:pat => $extract:expr) => {
+        $values
+            .iter()
+            .copied()
+            .map(|value| match value {
+                None => Ok(None),
+                Some($pattern) => Ok(Some($extract)),
+                Some(value) => Err(mismatch($tpe, value)),
+            })
+            .collect::<Result<Vec<_>, Error>>()?
+    };
+}
+
+fn to_array(tpe: &Type, values: &[Option<&Value>]) -> Result<ArrayRef, Error> {
+    let array: ArrayRef = match tpe {
+        Type::Bool => Arc::new(BooleanArray::from(
+            extract!(values, tpe, Value::Bool(b) => *b),
+        )),
+        Type::Int64 => Arc::new(Int64Array::from(
+            extract!(values, tpe, Value::Int64(i) => *i),
+        )),
+        Type::Float64 => Arc::new(Float64Array::from(
+            extract!(values, tpe, Value::Float64(f) => *f),
+        )),
+        Type::String => Arc::new(StringArray::from(
+            extract!(values, tpe, Value::String(s) => s.as_str()),
+        )),
+        Type::Bytes => Arc::new(BinaryArray::from(
+            extract!(values, tpe, Value::Bytes(b) => b.as_ref()),
+        )),
+        #[cfg(feature = "json")]
+        Type::Json => Arc::new(StringArray::from(
+            extract!(values, tpe, Value::Json(json) => json.to_string()),
+        )),
+        #[cfg(feature = "numeric")]
+        Type::Numeric => Arc::new(
+            Decimal128Array::from(extract!(values, tpe, Value::Numeric(n) => unscaled(n)?))
+                .with_precision_and_scale(NUMERIC_PRECISION, NUMERIC_SCALE)?,
+        ),
+        #[cfg(feature = "temporal")]
+        Type::Timestamp => Arc::new(
+            TimestampNanosecondArray::from(
+                extract!(values, tpe, Value::Timestamp(dt) => nanos(dt)?),
+            )
+            .with_timezone("UTC".to_string()),
+        ),
+        #[cfg(feature = "temporal")]
+        Type::Date => Arc::new(Date32Array::from(
+            extract!(values, tpe, Value::Date(d) => days(d)),
+        )),
+        Type::Array(inner) => {
+            let arrays = extract!(values, tpe, Value::Array(_, elements) => elements);
+            let mut offsets = Vec::with_capacity(arrays.len() + 1);
+            offsets.push(0_i32);
+            for elements in &arrays {
+                let length = i32::try_from(elements.map_or(0, Vec::len))?;
+                offsets.push(offsets[offsets.len() - 1] + length);
+            }
+            let elements = arrays
+                .iter()
+                .flatten()
+                .flat_map(|elements| elements.iter().map(present))
+                .collect::<Vec<_>>();
+            let data = ArrayData::builder(data_type(tpe))
+                .len(arrays.len())
+                .add_buffer(Buffer::from_slice_ref(&offsets))
+                .add_child_data(to_array(inner, &elements)?.data().clone())
+                .null_bit_buffer(Some(validity(&arrays)))
+                .build()?;
+            make_array(data)
+        }
+        Type::Struct(struct_type) => {
+            let structs = extract!(values, tpe, Value::Struct(s) => s.values());
+            let children = struct_type
+                .types()
+                .enumerate()
+                .map(|(index, tpe)| {
+                    let values = structs
+                        .iter()
+                        .map(|values| values.and_then(|values| present(&values[index])))
+                        .collect::<Vec<_>>();
+                    Ok(to_array(tpe, &values)?.data().clone())
+                })
+                .collect::<Result<Vec<_>, Error>>()?;
+            let data = ArrayData::builder(data_type(tpe))
+                .len(structs.len())
+                .child_data(children)
+                .null_bit_buffer(Some(validity(&structs)))
+                .build()?;
+            make_array(data)
+        }
+    };
+    Ok(array)
+}
+
+fn validity<T>(values: &[Option<T>]) -> Buffer {
+    values.iter().map(Option::is_some).collect()
+}
+
+/// Returns the value of a `NUMERIC` scaled by `10^9`, the representation of `Decimal128(38, 9)`.
+#[cfg(feature = "numeric")]
+fn unscaled(n: &BigDecimal) -> Result<i128, Error> {
+    let (unscaled, _) = n
+        .with_scale(NUMERIC_SCALE.into())
+        .into_bigint_and_exponent();
+    unscaled
+        .to_i128()
+        .ok_or_else(|| Error::Codec(format!("NUMERIC {} is out of range", n)))
+}
+
+#[cfg(feature = "temporal")]
+fn nanos(dt: &DateTime<Utc>) -> Result<i64, Error> {
+    dt.timestamp()
+        .checked_mul(1_000_000_000)
+        .and_then(|nanos| nanos.checked_add(dt.timestamp_subsec_nanos().into()))
+        .ok_or_else(|| {
+            Error::Codec(format!(
+                "TIMESTAMP {} cannot be represented using nanoseconds",
+                dt
+            ))
+        })
+}
+
+#[cfg(feature = "temporal")]
+fn days(date: &NaiveDate) -> i32 {
+    date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE
+}
+
+#[cfg(test)]
+mod test {
+    use ::arrow::array::{ListArray, StructArray};
+    use futures_util::stream;
+
+    use super::*;
+
+    fn result_set() -> ResultSet {
+        ResultSet::new(
+            StructType::new(vec![
+                ("id", Type::Int64),
+                ("", Type::String),
+                ("tags", Type::array(Type::String)),
+                (
+                    "point",
+                    Type::strct(vec![("x", Type::Float64), ("y", Type::Float64)]),
+                ),
+            ]),
+            vec![
+                vec![
+                    Value::Int64(1),
+                    Value::String("ferris".to_string()),
+                    Value::Array(
+                        Type::String,
+                        vec![Value::String("crab".to_string()), Value::Null(Type::String)],
+                    ),
+                    Value::Struct(crate::Struct::new(
+                        StructType::new(vec![("x", Type::Float64), ("y", Type::Float64)]),
+                        vec![Value::Float64(1.0), Value::Float64(2.0)],
+                    )),
+                ],
+                vec![
+                    Value::Int64(2),
+                    Value::Null(Type::String),
+                    Value::Null(Type::array(Type::String)),
+                    Value::Null(Type::strct(vec![
+                        ("x", Type::Float64),
+                        ("y", Type::Float64),
+                    ])),
+                ],
+            ],
+        )
+        .unwrap()
+    }
+
+    fn assert_batch(batch: &RecordBatch) {
+        let schema = batch.schema();
+        let names = schema
+            .fields()
+            .iter()
+            .map(|field| field.name().as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["id", "_1", "tags", "point"]);
+        assert_eq!(batch.num_rows(), 2);
+
+        let ids = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Int64Array>()
+            .unwrap();
+        assert_eq!(ids.values(), &[1, 2]);
+
+        let names = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<StringArray>()
+            .unwrap();
+        assert_eq!(names.value(0), "ferris");
+        assert!(names.is_null(1));
+
+        let tags = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<ListArray>()
+            .unwrap();
+        let first = tags.value(0);
+        let first = first.as_any().downcast_ref::<StringArray>().unwrap();
+        assert_eq!(first.len(), 2);
+        assert_eq!(first.value(0), "crab");
+        assert!(first.is_null(1));
+        assert!(tags.is_null(1));
+
+        let points = batch
+            .column(3)
+            .as_any()
+            .downcast_ref::<StructArray>()
+            .unwrap();
+        let xs = points
+            .column(0)
+            .as_any()
+            .downcast_ref::<Float64Array>()
+            .unwrap();
+        assert_eq!(xs.value(0), 1.0);
+        assert!(points.is_null(1));
+    }
+
+    #[test]
+    fn test_to_record_batch() {
+        assert_batch(&result_set().to_record_batch().unwrap());
+    }
+
+    #[test]
+    fn test_to_record_batch_mismatch() {
+        let rs = ResultSet::new(
+            StructType::new(vec![("id", Type::Int64)]),
+            vec![vec![Value::String("1".to_string())]],
+        )
+        .unwrap();
+        assert!(matches!(rs.to_record_batch(), Err(Error::Codec(_))));
+    }
+
+    #[cfg(all(feature = "numeric", feature = "temporal"))]
+    #[test]
+    fn test_to_record_batch_numeric_temporal() {
+        use std::str::FromStr;
+
+        let rs = ResultSet::new(
+            StructType::new(vec![
+                ("n", Type::Numeric),
+                ("ts", Type::Timestamp),
+                ("d", Type::Date),
+            ]),
+            vec![vec![
+                Value::Numeric(BigDecimal::from_str("-12.5").unwrap()),
+                Value::Timestamp(DateTime::from_utc(
+                    NaiveDate::from_ymd(1970, 1, 2).and_hms_nano(0, 0, 0, 42),
+                    Utc,
+                )),
+                Value::Date(NaiveDate::from_ymd(1969, 12, 31)),
+            ]],
+        )
+        .unwrap();
+        let batch = rs.to_record_batch().unwrap();
+
+        let n = batch
+            .column(0)
+            .as_any()
+            .downcast_ref::<Decimal128Array>()
+            .unwrap();
+        assert_eq!(n.value(0), -12_500_000_000);
+        let ts = batch
+            .column(1)
+            .as_any()
+            .downcast_ref::<TimestampNanosecondArray>()
+            .unwrap();
+        assert_eq!(ts.value(0), 86_400_000_000_042);
+        let d = batch
+            .column(2)
+            .as_any()
+            .downcast_ref::<Date32Array>()
+            .unwrap();
+        assert_eq!(d.value(0), -1);
+    }
+
+    #[tokio::test]
+    async fn test_record_batches() {
+        let rows = result_set().into_rows().into_iter().map(Ok);
+        let batches = record_batches(stream::iter(rows), 1)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].num_rows(), 1);
+
+        let rows = result_set().into_rows().into_iter().map(Ok);
+        let batches = record_batches(stream::iter(rows), 10)
+            .try_collect::<Vec<_>>()
+            .await
+            .unwrap();
+        assert_eq!(batches.len(), 1);
+        assert_batch(&batches[0]);
+    }
+}
@@ -0,0 +1,137 @@
+use std::fmt::{Display, Formatter};
+
+use crate::{Error, Statement, Type};
+
+/// A record of the statements executed by each attempt of a read/write transaction.
+///
+/// When the `replay-log` feature is enabled, errors returned by [`crate::TxRunner::run`] are wrapped in
+/// [`Error::WithReplayLog`] which holds this log. This helps diagnosing transactions that only fail (or succeed)
+/// after being retried.
+///
+/// Parameter values are never recorded, only their names and types.
+#[derive(Clone, Debug, Default)]
+pub struct ReplayLog {
+    attempt: u32,
+    entries: Vec<ReplayEntry>,
+}
+
+/// A statement executed by a transaction attempt, see [`ReplayLog`].
+#[derive(Clone, Debug)]
+pub struct ReplayEntry {
+    attempt: u32,
+    sql: String,
+    params: Vec<(String, Type)>,
+}
+
+impl ReplayLog {
+    /// Returns the statements executed by all attempts, in order.
+    pub fn entries(&self) -> &[ReplayEntry] {
+        &self.entries
+    }
+
+    /// Returns the number of attempts made by the transaction.
+    pub fn attempts(&self) -> u32 {
+        self.attempt
+    }
+
+    pub(crate) fn next_attempt(&mut self) {
+        self.attempt += 1;
+    }
+
+    pub(crate) fn record(&mut self, statement: &Statement) {
+        self.entries.push(ReplayEntry {
+            attempt: self.attempt,
+            sql: statement.sql().to_string(),
+            params: statement
+                .params()
+                .map(|(name, value)| (name.to_string(), value.spanner_type()))
+                .collect(),
+        });
+    }
+
+    /// Wraps the error with this log, unless there is nothing to report.
+    pub(crate) fn attach(self, error: Error) -> Error {
+        if self.entries.is_empty() {
+            error
+        } else {
+            Error::WithReplayLog {
+                source: Box::new(error),
+                log: self,
+            }
+        }
+    }
+}
+
+impl ReplayEntry {
+    /// Returns the attempt that executed this statement, starting at 1.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Returns the statement's SQL.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the names and types of the statement's parameters.
+    pub fn params(&self) -> &[(String, Type)] {
+        &self.params
+    }
+}
+
+impl Display for ReplayLog {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "replay log ({} attempt(s)):", self.attempt)?;
+        for entry in &self.entries {
+            write!(f, "\n  #{}: {}", entry.attempt, entry.sql)?;
+            if !entry.params.is_empty() {
+                let params = entry
+                    .params
+                    .iter()
+                    .map(|(name, tpe)| format!("{}: {}", name, tpe))
+                    .collect::<Vec<_>>();
+                write!(f, " [{}]", params.join(", "))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_replay_log() {
+        let mut log = ReplayLog::default();
+        assert!(matches!(
+            log.clone().attach(Error::Client("empty".to_string())),
+            Error::Client(_)
+        ));
+
+        let insert = Statement::new("INSERT INTO person(id, name) VALUES(@id, @name)")
+            .bind("id", 42)
+            .bind("name", "secret");
+        log.next_attempt();
+        log.record(&Statement::new("SELECT 1"));
+        log.next_attempt();
+        log.record(&Statement::new("SELECT 1"));
+        log.record(&insert);
+
+        assert_eq!(log.attempts(), 2);
+        assert_eq!(log.entries().len(), 3);
+        assert_eq!(log.entries()[2].attempt(), 2);
+        assert_eq!(
+            log.to_string(),
+            "replay log (2 attempt(s)):\n  #1: SELECT 1\n  #2: SELECT 1\n  #2: INSERT INTO person(id, name) VALUES(@id, @name) [id: INT64, name: STRING(MAX)]"
+        );
+
+        let err = log.attach(Error::from(tonic::Status::aborted("conflict")));
+        assert!(err.is_retryable());
+        assert!(!err.to_string().contains("secret"));
+        match err {
+            Error::WithReplayLog { source, .. } => assert!(matches!(*source, Error::Aborted(_))),
+            other => panic!("unexpected error {:?}", other),
+        }
+    }
+}
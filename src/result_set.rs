@@ -1,10 +1,12 @@
 use std::convert::TryFrom;
 use std::convert::TryInto;
+use std::sync::Arc;
 
 use crate::Error;
 use crate::FromSpanner;
 use crate::StructType;
 use crate::Transaction;
+use crate::Type;
 use crate::Value;
 use google_api_proto::google::spanner::v1 as proto;
 
@@ -100,7 +102,51 @@ impl<'a> Row<'a> {
         }
     }
 
-    fn get_impl<T, R>(&'a self, row_index: &R) -> Result<T, Error>
+    /// Deserializes the value of the specified `JSON` column into any type implementing `serde::Deserialize`.
+    ///
+    /// A `NULL` value is deserialized as JSON's `null`, so `Option<T>` should be used for nullable columns.
+    ///
+    /// An error is returned if the requested column does not exist, is not a `JSON` column or if the value cannot be deserialized.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext};
+    /// #[derive(serde::Deserialize)]
+    /// struct Preferences {
+    ///     theme: String,
+    /// }
+    ///
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let mut client = Client::configure().connect().await?;
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query("SELECT preferences FROM person", &[])
+    ///     .await?;
+    /// for row in rs.iter() {
+    ///     let preferences: Option<Preferences> = row.get_json("preferences")?;
+    /// }
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn get_json<T, R>(&'a self, row_index: R) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+        R: RowIndex + std::fmt::Display,
+    {
+        let json: Option<&serde_json::Value> = self.get_impl(&row_index)?;
+        <T as serde::Deserialize>::deserialize(json.unwrap_or(&serde_json::Value::Null)).map_err(
+            |err| {
+                Error::Codec(format!(
+                    "unable to deserialize column {}: {}",
+                    row_index, err
+                ))
+            },
+        )
+    }
+
+    fn get_impl<T, R>(&self, row_index: &R) -> Result<T, Error>
     where
         T: FromSpanner<'a>,
         R: RowIndex + std::fmt::Display,
@@ -121,23 +167,151 @@ impl<'a> std::fmt::Debug for Row<'a> {
     }
 }
 
+/// A row of a result set which owns its values, see [`ResultSet::into_iter`].
+///
+/// Unlike [`Row`], an owned row can outlive its result set, e.g.: to be moved into a spawned task.
+/// The row type is shared between all rows of a result set.
+#[derive(Clone)]
+pub struct OwnedRow {
+    row_type: Arc<StructType>,
+    columns: Vec<Value>,
+}
+
+impl OwnedRow {
+    /// Returns a borrowed view of this row.
+    pub fn row(&self) -> Row<'_> {
+        Row {
+            row_type: &self.row_type,
+            columns: &self.columns,
+        }
+    }
+
+    /// Returns the structure of this row (field names and type).
+    pub fn row_type(&self) -> &StructType {
+        &self.row_type
+    }
+
+    /// Returns true when this row has no fields.
+    pub fn is_empty(&self) -> bool {
+        self.row_type.fields().is_empty()
+    }
+
+    /// Returns the converted value of the specified column, see [`Row::get`].
+    pub fn get<'a, T, R>(&'a self, row_index: R) -> Result<T, Error>
+    where
+        T: FromSpanner<'a>,
+        R: RowIndex + std::fmt::Display,
+    {
+        self.row().get_impl(&row_index)
+    }
+
+    /// Returns the converted value of the specified column, see [`Row::get_unchecked`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the specified index does not exist or if the value cannot be converted to requested type.
+    pub fn get_unchecked<'a, T, R>(&'a self, row_index: R) -> T
+    where
+        T: FromSpanner<'a>,
+        R: RowIndex + std::fmt::Display,
+    {
+        match self.row().get_impl(&row_index) {
+            Ok(value) => value,
+            Err(error) => panic!(
+                "unexpected error while reading column {}: {}",
+                row_index, error
+            ),
+        }
+    }
+
+    /// Deserializes the value of the specified `JSON` column, see [`Row::get_json`].
+    #[cfg(feature = "json")]
+    pub fn get_json<T, R>(&self, row_index: R) -> Result<T, Error>
+    where
+        T: serde::de::DeserializeOwned,
+        R: RowIndex + std::fmt::Display,
+    {
+        self.row().get_json(row_index)
+    }
+
+    /// Consumes this row and returns its values, in column order.
+    pub fn into_values(self) -> Vec<Value> {
+        self.columns
+    }
+}
+
+/// Prints the row's type, but omits the values.
+impl std::fmt::Debug for OwnedRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OwnedRow")
+            .field("columns", &self.row_type)
+            .finish()
+    }
+}
+
+/// An iterator over the [`OwnedRow`]s of a result set, see [`ResultSet::into_iter`].
 #[derive(Debug)]
-pub(crate) struct Stats {
-    pub(crate) row_count: Option<i64>,
+pub struct IntoRows {
+    row_type: Arc<StructType>,
+    rows: std::vec::IntoIter<Vec<Value>>,
+}
+
+impl Iterator for IntoRows {
+    type Item = OwnedRow;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|columns| OwnedRow {
+            row_type: self.row_type.clone(),
+            columns,
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
 }
 
-impl TryFrom<proto::ResultSetStats> for Stats {
-    type Error = Error;
+impl ExactSizeIterator for IntoRows {}
 
-    fn try_from(value: proto::ResultSetStats) -> Result<Self, Self::Error> {
-        let row_count = match value.row_count {
-            Some(proto::result_set_stats::RowCount::RowCountExact(exact)) => Ok(Some(exact)),
-            Some(proto::result_set_stats::RowCount::RowCountLowerBound(_)) => Err(Error::Client(
-                "lower bound row count is unsupported".to_string(),
-            )),
-            None => Ok(None),
-        }?;
-        Ok(Self { row_count })
+/// The number of rows modified by a DML statement, see [`ResultSet::row_count`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RowCount {
+    /// The exact number of modified rows, which is what DML statements executed in a read/write transaction return.
+    Exact(i64),
+    /// A lower bound of the number of modified rows, which is what partitioned DML statements return.
+    LowerBound(i64),
+}
+
+impl RowCount {
+    /// Returns the number of modified rows, which may be a lower bound.
+    pub fn value(&self) -> i64 {
+        match self {
+            RowCount::Exact(count) | RowCount::LowerBound(count) => *count,
+        }
+    }
+}
+
+impl From<proto::result_set_stats::RowCount> for RowCount {
+    fn from(value: proto::result_set_stats::RowCount) -> Self {
+        match value {
+            proto::result_set_stats::RowCount::RowCountExact(exact) => RowCount::Exact(exact),
+            proto::result_set_stats::RowCount::RowCountLowerBound(lower_bound) => {
+                RowCount::LowerBound(lower_bound)
+            }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub(crate) struct Stats {
+    pub(crate) row_count: Option<RowCount>,
+}
+
+impl From<proto::ResultSetStats> for Stats {
+    fn from(value: proto::ResultSetStats) -> Self {
+        Self {
+            row_count: value.row_count.map(RowCount::from),
+        }
     }
 }
 
@@ -145,7 +319,7 @@ impl TryFrom<proto::ResultSetStats> for Stats {
 ///
 /// Contains the structure of each row as well as each row's values.
 /// A result set is not lazy and will eagerly decode all rows in the result set.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct ResultSet {
     row_type: StructType,
     rows: Vec<Vec<Value>>,
@@ -154,6 +328,146 @@ pub struct ResultSet {
 }
 
 impl ResultSet {
+    /// Creates a result set of the provided rows, e.g.: to script the results of a [`crate::MockConnection`].
+    ///
+    /// An error is returned if a row does not have exactly one value per field of `row_type`.
+    #[cfg(any(test, feature = "test-util"))]
+    pub fn new(row_type: StructType, rows: Vec<Vec<Value>>) -> Result<Self, Error> {
+        if let Some(row) = rows.iter().find(|row| row.len() != row_type.fields().len()) {
+            return Err(Error::Client(format!(
+                "expected {} values per row, got {}",
+                row_type.fields().len(),
+                row.len()
+            )));
+        }
+        Ok(Self {
+            row_type,
+            rows,
+            transaction: None,
+            stats: Stats { row_count: None },
+        })
+    }
+
+    /// Creates an empty result set reporting the number of rows modified by a DML statement,
+    /// e.g.: to script the results of a [`crate::MockConnection`].
+    #[cfg(feature = "test-util")]
+    pub fn from_row_count(row_count: i64) -> Self {
+        Self {
+            row_type: StructType::default(),
+            rows: Vec::new(),
+            transaction: None,
+            stats: Stats {
+                row_count: Some(RowCount::Exact(row_count)),
+            },
+        }
+    }
+
+    /// Returns the structure of the rows of this result set (field names and type).
+    pub fn row_type(&self) -> &StructType {
+        &self.row_type
+    }
+
+    /// Returns the names of the columns of this result set, in order.
+    ///
+    /// Unnamed columns, e.g.: `SELECT 1`, are returned as empty strings.
+    pub fn column_names(&self) -> Vec<&str> {
+        self.row_type
+            .field_names()
+            .map(|name| name.as_deref().unwrap_or_default())
+            .collect()
+    }
+
+    /// Returns the type of the column with the provided name, if any.
+    pub fn column_type(&self, name: &str) -> Option<&Type> {
+        self.row_type
+            .field_index(name)
+            .map(|index| &self.row_type.fields()[index].1)
+    }
+
+    /// Returns the number of rows modified by the DML statement that returned this result set,
+    /// or `None` when it was returned by a query.
+    pub fn row_count(&self) -> Option<RowCount> {
+        self.stats.row_count
+    }
+
+    /// Returns the number of rows in this result set.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns true when this result set has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Converts the rows of this result set to a JSON array of objects keyed by column name, e.g.: to return them from an HTTP API.
+    ///
+    /// Unnamed columns are keyed by their position, e.g.: `"_0"`. Values are encoded faithfully to their type,
+    /// e.g.: `BYTES` are base64-encoded and `TIMESTAMP`s use RFC 3339, see `From<Value> for serde_json::Value` for details.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query("SELECT id, name FROM person", &[])
+    ///     .await?;
+    /// // e.g.: [{"id": 42, "name": "ferris"}]
+    /// println!("{}", rs.to_json());
+    /// # Ok(()) }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Array(
+            self.rows
+                .iter()
+                .map(|row| {
+                    Value::Struct(crate::Struct::new(self.row_type.clone(), row.clone())).into()
+                })
+                .collect(),
+        )
+    }
+
+    /// Returns the only row of this result set.
+    ///
+    /// An error is returned if this result set has no rows or more than one row.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext};
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let rs = client
+    ///     .read_only()
+    ///     .execute_query("SELECT name FROM person WHERE id = @id", &[("id", &42)])
+    ///     .await?;
+    /// let name: String = rs.one()?.get("name")?;
+    /// # Ok(()) }
+    /// ```
+    pub fn one(&self) -> Result<Row<'_>, Error> {
+        self.opt()?
+            .ok_or_else(|| Error::Client("expected exactly one row, got none".to_string()))
+    }
+
+    /// Returns the only row of this result set, or `None` when it has no rows.
+    ///
+    /// An error is returned if this result set has more than one row.
+    pub fn opt(&self) -> Result<Option<Row<'_>>, Error> {
+        if self.rows.len() > 1 {
+            return Err(Error::Client(format!(
+                "expected at most one row, got {}",
+                self.rows.len()
+            )));
+        }
+        Ok(self.iter().next())
+    }
+
     /// Returns an iterator over the rows of this result set.
     pub fn iter(&self) -> impl Iterator<Item = Row<'_>> {
         self.rows.iter().map(move |columns| Row {
@@ -161,13 +475,54 @@ impl ResultSet {
             columns,
         })
     }
+
+    /// Consumes this result set and returns its rows as [`OwnedRow`]s, e.g.: to move them into spawned tasks.
+    pub fn into_rows(self) -> Vec<OwnedRow> {
+        self.into_iter().collect()
+    }
+}
+
+/// Iterates over [`OwnedRow`]s, which do not borrow from the result set.
+///
+/// # Example
+///
+/// ```no_run
+/// # use spanner_rs::{Client, Error, ReadContext};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = Client::configure().connect().await?;
+/// let rs = client
+///     .read_only()
+///     .execute_query("SELECT id, name FROM person", &[])
+///     .await?;
+/// let tasks: Vec<_> = rs
+///     .into_iter()
+///     .map(|row| {
+///         tokio::spawn(async move {
+///             let name: String = row.get("name")?;
+///             Ok::<_, Error>(name.len())
+///         })
+///     })
+///     .collect();
+/// # Ok(()) }
+/// ```
+impl IntoIterator for ResultSet {
+    type Item = OwnedRow;
+    type IntoIter = IntoRows;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoRows {
+            row_type: Arc::new(self.row_type),
+            rows: self.rows.into_iter(),
+        }
+    }
 }
 
 impl TryFrom<proto::ResultSet> for ResultSet {
     type Error = crate::Error;
 
     fn try_from(value: proto::ResultSet) -> Result<Self, Self::Error> {
-        let stats = value.stats.unwrap_or_default().try_into()?;
+        let stats = value.stats.unwrap_or_default().into();
         let metadata = value.metadata.unwrap_or_default();
         let row_type: StructType = metadata.row_type.unwrap_or_default().try_into()?;
 
@@ -191,3 +546,166 @@ impl TryFrom<proto::ResultSet> for ResultSet {
         })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_stats_row_count() {
+        let stats = |row_count| {
+            Stats::from(proto::ResultSetStats {
+                row_count,
+                ..Default::default()
+            })
+            .row_count
+        };
+        assert_eq!(
+            stats(Some(proto::result_set_stats::RowCount::RowCountExact(2))),
+            Some(RowCount::Exact(2))
+        );
+        assert_eq!(
+            stats(Some(proto::result_set_stats::RowCount::RowCountLowerBound(
+                3
+            ))),
+            Some(RowCount::LowerBound(3))
+        );
+        assert_eq!(stats(None), None);
+        assert_eq!(RowCount::LowerBound(3).value(), 3);
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_to_json() {
+        let result_set = ResultSet {
+            row_type: StructType::new(vec![("id", Type::Int64), ("", Type::Bytes)]),
+            rows: vec![
+                vec![
+                    Value::Int64(1),
+                    Value::Bytes(prost::bytes::Bytes::from_static(b"hi")),
+                ],
+                vec![Value::Int64(2), Value::Null(Type::Bytes)],
+            ],
+            transaction: None,
+            stats: Stats { row_count: None },
+        };
+
+        assert_eq!(
+            result_set.to_json(),
+            serde_json::json!([{"id": 1, "_1": "aGk="}, {"id": 2, "_1": null}])
+        );
+    }
+
+    #[test]
+    fn test_metadata() {
+        let result_set = ResultSet {
+            row_type: StructType::new(vec![("id", Type::Int64), ("", Type::String)]),
+            rows: vec![vec![Value::Int64(1), Value::String("ferris".to_string())]],
+            transaction: None,
+            stats: Stats { row_count: None },
+        };
+
+        assert_eq!(result_set.column_names(), vec!["id", ""]);
+        assert_eq!(result_set.column_type("id"), Some(&Type::Int64));
+        assert_eq!(result_set.column_type("name"), None);
+        assert_eq!(result_set.len(), 1);
+        assert!(!result_set.is_empty());
+    }
+
+    #[test]
+    fn test_one_opt() {
+        let row_type = StructType::new(vec![("id", Type::Int64)]);
+        let result_set = |ids: Vec<i64>| ResultSet {
+            row_type: row_type.clone(),
+            rows: ids.into_iter().map(|id| vec![Value::Int64(id)]).collect(),
+            transaction: None,
+            stats: Stats { row_count: None },
+        };
+
+        let empty = result_set(vec![]);
+        assert!(empty.one().is_err());
+        assert!(empty.opt().unwrap().is_none());
+
+        let single = result_set(vec![42]);
+        assert_eq!(single.one().unwrap().get_unchecked::<i64, _>("id"), 42);
+        assert_eq!(
+            single.opt().unwrap().unwrap().get_unchecked::<i64, _>("id"),
+            42
+        );
+
+        let many = result_set(vec![1, 2]);
+        assert!(many.one().is_err());
+        assert!(many.opt().is_err());
+    }
+
+    #[test]
+    fn test_into_iter() {
+        let result_set = ResultSet {
+            row_type: StructType::new(vec![("id", Type::Int64), ("name", Type::String)]),
+            rows: vec![
+                vec![Value::Int64(1), Value::String("ferris".to_string())],
+                vec![Value::Int64(2), Value::Null(Type::String)],
+            ],
+            transaction: None,
+            stats: Stats { row_count: None },
+        };
+
+        let rows = result_set.into_iter();
+        assert_eq!(rows.len(), 2);
+        let rows: Vec<OwnedRow> = rows.collect();
+        let handle = std::thread::spawn(move || {
+            rows.iter()
+                .map(|row| {
+                    (
+                        row.get_unchecked::<i64, _>("id"),
+                        row.get_unchecked::<Option<String>, _>(1),
+                    )
+                })
+                .collect::<Vec<_>>()
+        });
+        assert_eq!(
+            handle.join().unwrap(),
+            vec![(1, Some("ferris".to_string())), (2, None)]
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_row_get_json() {
+        use serde_json::json;
+
+        #[derive(serde::Deserialize, Debug, PartialEq)]
+        struct Preferences {
+            theme: String,
+        }
+
+        let row_type = StructType::new(vec![
+            ("preferences", Type::Json),
+            ("nothing", Type::Json),
+            ("name", Type::String),
+        ]);
+        let columns = vec![
+            Value::Json(json!({"theme": "dark"})),
+            Value::Null(Type::Json),
+            Value::String("ferris".to_string()),
+        ];
+        let row = Row {
+            row_type: &row_type,
+            columns: &columns,
+        };
+
+        let preferences: Preferences = row.get_json("preferences").unwrap();
+        assert_eq!(
+            preferences,
+            Preferences {
+                theme: "dark".to_string()
+            }
+        );
+        let nothing: Option<Preferences> = row.get_json("nothing").unwrap();
+        assert_eq!(nothing, None);
+        assert!(row.get_json::<Preferences, _>("nothing").is_err());
+        assert!(row.get_json::<Vec<String>, _>("preferences").is_err());
+        assert!(row.get_json::<Preferences, _>("name").is_err());
+        assert!(row.get_json::<Preferences, _>("missing").is_err());
+    }
+}
@@ -0,0 +1,207 @@
+use std::fmt::{Debug, Formatter};
+use std::sync::Arc;
+use std::time::Duration;
+
+use derive_builder::Builder;
+
+/// Configuration for retrying read/write transactions that were aborted by Cloud Spanner.
+///
+/// Cloud Spanner may abort a transaction due to conflicts with other transactions.
+/// Such transactions are retried by [`crate::TxRunner::run`] after waiting for an exponentially increasing delay.
+///
+/// # Example
+///
+/// ```
+/// use std::time::Duration;
+/// use spanner_rs::{Config, RetryPolicy};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// Config::builder().retry_policy(
+///     RetryPolicy::builder()
+///         .max_attempts(5)
+///         .initial_backoff(Duration::from_millis(50))
+///         .build()?,
+/// );
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, PartialEq)]
+#[builder(pattern = "owned", build_fn(error = "crate::Error"))]
+pub struct RetryPolicy {
+    /// Specify the maximum number of times a transaction is attempted, including the first attempt.
+    /// Leave unspecified to retry indefinitely.
+    #[builder(setter(strip_option), default)]
+    max_attempts: Option<u32>,
+
+    /// Specify the delay before the first retry.
+    #[builder(default = "Duration::from_millis(20)")]
+    initial_backoff: Duration,
+
+    /// Specify the maximum delay between two attempts.
+    #[builder(default = "Duration::from_secs(32)")]
+    max_backoff: Duration,
+
+    /// Specify the factor by which the delay increases after each attempt.
+    #[builder(default = "1.5")]
+    multiplier: f64,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: None,
+            initial_backoff: Duration::from_millis(20),
+            max_backoff: Duration::from_secs(32),
+            multiplier: 1.5,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a new [`RetryPolicyBuilder`] for configuring a new retry policy.
+    pub fn builder() -> RetryPolicyBuilder {
+        RetryPolicyBuilder::default()
+    }
+
+    /// Returns true if another attempt may be made after the specified number of attempts.
+    pub(crate) fn should_retry(&self, attempts: u32) -> bool {
+        self.max_attempts
+            .map_or(true, |max_attempts| attempts < max_attempts)
+    }
+
+    /// Returns the delay to wait for before retrying after the specified number of attempts failed with the provided status.
+    ///
+    /// The delay recommended by Cloud Spanner is used when present, otherwise, the exponential backoff applies.
+    pub(crate) fn delay(&self, status: &tonic::Status, attempts: u32) -> Duration {
+        crate::status::retry_delay(status).unwrap_or_else(|| self.backoff(attempts))
+    }
+
+    /// Returns the delay to wait for after the specified number of attempts.
+    pub(crate) fn backoff(&self, attempts: u32) -> Duration {
+        let exponent = attempts.saturating_sub(1).min(i32::MAX as u32) as i32;
+        let backoff = self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent);
+        // Clamped before converting, since large attempt counts overflow `Duration`, or even `f64`.
+        if backoff.is_finite() {
+            Duration::from_secs_f64(backoff.clamp(0.0, self.max_backoff.as_secs_f64()))
+        } else {
+            self.max_backoff
+        }
+    }
+}
+
+/// Describes a read/write transaction that was aborted by Cloud Spanner and is about to be retried,
+/// see [`crate::ConfigBuilder::on_retry`].
+#[derive(Clone, Debug)]
+pub struct RetryEvent {
+    attempt: u32,
+    delay: Duration,
+    status: tonic::Status,
+}
+
+impl RetryEvent {
+    /// Returns the number of the attempt that was aborted, starting at 1.
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Returns how long to wait for before the next attempt.
+    pub fn delay(&self) -> Duration {
+        self.delay
+    }
+
+    /// Returns the `ABORTED` status returned by Cloud Spanner.
+    pub fn status(&self) -> &tonic::Status {
+        &self.status
+    }
+}
+
+/// A user-supplied callback invoked before retrying aborted transactions, see [`crate::ConfigBuilder::on_retry`].
+#[derive(Clone)]
+pub(crate) struct RetryObserver(Arc<dyn Fn(&RetryEvent) + Send + Sync>);
+
+impl RetryObserver {
+    pub(crate) fn new<F>(observer: F) -> Self
+    where
+        F: Fn(&RetryEvent) + Send + Sync + 'static,
+    {
+        Self(Arc::new(observer))
+    }
+
+    pub(crate) fn notify(&self, attempt: u32, delay: Duration, status: &tonic::Status) {
+        (self.0)(&RetryEvent {
+            attempt,
+            delay,
+            status: status.clone(),
+        })
+    }
+}
+
+impl Debug for RetryObserver {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("RetryObserver")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::status::test::{retry_info, status_with_details};
+    use tonic::Code;
+
+    #[test]
+    fn test_builder_defaults() {
+        assert_eq!(
+            RetryPolicy::builder().build().unwrap(),
+            RetryPolicy::default()
+        );
+    }
+
+    #[test]
+    fn test_should_retry() {
+        assert!(RetryPolicy::default().should_retry(1_000));
+
+        let policy = RetryPolicy::builder().max_attempts(3).build().unwrap();
+        assert!(policy.should_retry(1));
+        assert!(policy.should_retry(2));
+        assert!(!policy.should_retry(3));
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::builder()
+            .initial_backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_secs(1))
+            .multiplier(2.0)
+            .build()
+            .unwrap();
+
+        assert_eq!(policy.backoff(1), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(200));
+        assert_eq!(policy.backoff(3), Duration::from_millis(400));
+        assert_eq!(policy.backoff(5), Duration::from_secs(1));
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_backoff_high_attempts() {
+        // 1.5^200 is finite, but exceeds `Duration::MAX` once multiplied by the initial backoff.
+        let policy = RetryPolicy::default();
+        for attempts in [116, 200, 1_000, 2_000] {
+            assert_eq!(policy.backoff(attempts), Duration::from_secs(32));
+        }
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy::builder()
+            .initial_backoff(Duration::from_millis(100))
+            .build()
+            .unwrap();
+
+        let status = status_with_details(Code::Aborted, vec![retry_info(Duration::from_millis(7))]);
+        assert_eq!(policy.delay(&status, 1), Duration::from_millis(7));
+        assert_eq!(
+            policy.delay(&tonic::Status::aborted("aborted"), 1),
+            Duration::from_millis(100)
+        );
+    }
+}
@@ -0,0 +1,501 @@
+use crate::{Error, ReadContext, Type};
+
+const TABLES_QUERY: &str = "SELECT TABLE_NAME, PARENT_TABLE_NAME, ON_DELETE_ACTION \
+    FROM INFORMATION_SCHEMA.TABLES \
+    WHERE TABLE_CATALOG = '' AND TABLE_SCHEMA = '' AND TABLE_TYPE = 'BASE TABLE' \
+    ORDER BY TABLE_NAME";
+
+const COLUMNS_QUERY: &str = "SELECT TABLE_NAME, COLUMN_NAME, SPANNER_TYPE, IS_NULLABLE \
+    FROM INFORMATION_SCHEMA.COLUMNS \
+    WHERE TABLE_CATALOG = '' AND TABLE_SCHEMA = '' \
+    ORDER BY TABLE_NAME, ORDINAL_POSITION";
+
+const INDEX_COLUMNS_QUERY: &str =
+    "SELECT i.TABLE_NAME, i.INDEX_NAME, i.INDEX_TYPE, i.IS_UNIQUE, i.IS_NULL_FILTERED, \
+    c.COLUMN_NAME, c.COLUMN_ORDERING, c.ORDINAL_POSITION \
+    FROM INFORMATION_SCHEMA.INDEXES AS i \
+    JOIN INFORMATION_SCHEMA.INDEX_COLUMNS AS c \
+    ON c.TABLE_CATALOG = i.TABLE_CATALOG AND c.TABLE_SCHEMA = i.TABLE_SCHEMA \
+    AND c.TABLE_NAME = i.TABLE_NAME AND c.INDEX_NAME = i.INDEX_NAME \
+    WHERE i.TABLE_CATALOG = '' AND i.TABLE_SCHEMA = '' \
+    ORDER BY i.TABLE_NAME, i.INDEX_NAME, c.ORDINAL_POSITION";
+
+// Joins each referencing column to the referenced column at the same position of the referenced key.
+const FOREIGN_KEYS_QUERY: &str = "SELECT tc.TABLE_NAME, tc.CONSTRAINT_NAME, rc.DELETE_RULE, \
+    kcu.COLUMN_NAME, ukcu.TABLE_NAME AS REFERENCED_TABLE_NAME, ukcu.COLUMN_NAME AS REFERENCED_COLUMN_NAME \
+    FROM INFORMATION_SCHEMA.TABLE_CONSTRAINTS AS tc \
+    JOIN INFORMATION_SCHEMA.REFERENTIAL_CONSTRAINTS AS rc \
+    ON rc.CONSTRAINT_CATALOG = tc.CONSTRAINT_CATALOG AND rc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA \
+    AND rc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME \
+    JOIN INFORMATION_SCHEMA.KEY_COLUMN_USAGE AS kcu \
+    ON kcu.CONSTRAINT_CATALOG = tc.CONSTRAINT_CATALOG AND kcu.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA \
+    AND kcu.CONSTRAINT_NAME = tc.CONSTRAINT_NAME \
+    JOIN INFORMATION_SCHEMA.KEY_COLUMN_USAGE AS ukcu \
+    ON ukcu.CONSTRAINT_CATALOG = rc.UNIQUE_CONSTRAINT_CATALOG AND ukcu.CONSTRAINT_SCHEMA = rc.UNIQUE_CONSTRAINT_SCHEMA \
+    AND ukcu.CONSTRAINT_NAME = rc.UNIQUE_CONSTRAINT_NAME AND ukcu.ORDINAL_POSITION = kcu.POSITION_IN_UNIQUE_CONSTRAINT \
+    WHERE tc.TABLE_CATALOG = '' AND tc.TABLE_SCHEMA = '' AND tc.CONSTRAINT_TYPE = 'FOREIGN KEY' \
+    ORDER BY tc.TABLE_NAME, tc.CONSTRAINT_NAME, kcu.ORDINAL_POSITION";
+
+// Cloud Spanner reports an implicit check constraint for each NOT NULL column, these are excluded.
+const CHECK_CONSTRAINTS_QUERY: &str = "SELECT tc.TABLE_NAME, cc.CONSTRAINT_NAME, cc.CHECK_CLAUSE \
+    FROM INFORMATION_SCHEMA.TABLE_CONSTRAINTS AS tc \
+    JOIN INFORMATION_SCHEMA.CHECK_CONSTRAINTS AS cc \
+    ON cc.CONSTRAINT_CATALOG = tc.CONSTRAINT_CATALOG AND cc.CONSTRAINT_SCHEMA = tc.CONSTRAINT_SCHEMA \
+    AND cc.CONSTRAINT_NAME = tc.CONSTRAINT_NAME \
+    WHERE tc.TABLE_CATALOG = '' AND tc.TABLE_SCHEMA = '' AND tc.CONSTRAINT_TYPE = 'CHECK' \
+    AND NOT STARTS_WITH(cc.CONSTRAINT_NAME, 'CK_IS_NOT_NULL_') \
+    ORDER BY tc.TABLE_NAME, cc.CONSTRAINT_NAME";
+
+/// The schema of a database, as reported by its `INFORMATION_SCHEMA`, see [`crate::Client::schema`].
+///
+/// Besides tables, columns and indexes, this models table interleaving, foreign keys and check constraints,
+/// e.g.: to compare the schemas of two databases.
+///
+/// Only the tables of the default schema are included, i.e.: not those of `INFORMATION_SCHEMA` or `SPANNER_SYS`.
+/// Note that only databases using the [`crate::Dialect::GoogleStandardSql`] dialect are supported.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Schema {
+    tables: Vec<TableSchema>,
+}
+
+/// A table of a [`Schema`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct TableSchema {
+    name: String,
+    columns: Vec<ColumnSchema>,
+    primary_key: Vec<IndexColumn>,
+    indexes: Vec<IndexSchema>,
+    interleaved_in: Option<Interleave>,
+    foreign_keys: Vec<ForeignKey>,
+    check_constraints: Vec<CheckConstraint>,
+}
+
+/// The parent of an interleaved table, i.e.: a table created using `INTERLEAVE IN PARENT`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Interleave {
+    parent: String,
+    on_delete: OnDelete,
+}
+
+/// What happens to rows that reference a deleted row, see [`Interleave::on_delete`] and [`ForeignKey::on_delete`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OnDelete {
+    /// Deleting a referenced row fails (`ON DELETE NO ACTION`).
+    NoAction,
+    /// Referencing rows are deleted along with the referenced row (`ON DELETE CASCADE`).
+    Cascade,
+}
+
+/// A foreign key of a [`TableSchema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForeignKey {
+    name: String,
+    columns: Vec<String>,
+    referenced_table: String,
+    referenced_columns: Vec<String>,
+    on_delete: OnDelete,
+}
+
+/// A check constraint of a [`TableSchema`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckConstraint {
+    name: String,
+    expression: String,
+}
+
+/// A column of a [`TableSchema`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnSchema {
+    name: String,
+    spanner_type: String,
+    column_type: Option<Type>,
+    nullable: bool,
+}
+
+/// A secondary index of a [`TableSchema`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct IndexSchema {
+    name: String,
+    columns: Vec<IndexColumn>,
+    storing: Vec<String>,
+    unique: bool,
+    null_filtered: bool,
+}
+
+/// A key column of an [`IndexSchema`] or of a [`TableSchema`]'s primary key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IndexColumn {
+    name: String,
+    descending: bool,
+}
+
+impl Schema {
+    /// Returns the tables of this schema, ordered by name.
+    pub fn tables(&self) -> &[TableSchema] {
+        &self.tables
+    }
+
+    /// Returns the table with the provided name, if any.
+    pub fn table(&self, name: &str) -> Option<&TableSchema> {
+        self.tables.iter().find(|table| table.name == name)
+    }
+
+    /// Reads the schema using the provided context.
+    pub(crate) async fn read<C>(ctx: &mut C) -> Result<Self, Error>
+    where
+        C: ReadContext + Send,
+    {
+        let mut tables: Vec<TableSchema> = ctx
+            .execute_query(TABLES_QUERY, &[])
+            .await?
+            .iter()
+            .map(|row| {
+                let parent: Option<String> = row.get("PARENT_TABLE_NAME")?;
+                let on_delete: Option<&str> = row.get("ON_DELETE_ACTION")?;
+                Ok(TableSchema {
+                    name: row.get("TABLE_NAME")?,
+                    columns: Vec::new(),
+                    primary_key: Vec::new(),
+                    indexes: Vec::new(),
+                    interleaved_in: parent.map(|parent| Interleave {
+                        parent,
+                        on_delete: OnDelete::parse(on_delete),
+                    }),
+                    foreign_keys: Vec::new(),
+                    check_constraints: Vec::new(),
+                })
+            })
+            .collect::<Result<_, Error>>()?;
+
+        for row in ctx.execute_query(COLUMNS_QUERY, &[]).await?.iter() {
+            let table_name: &str = row.get("TABLE_NAME")?;
+            let spanner_type: String = row.get("SPANNER_TYPE")?;
+            let nullable: &str = row.get("IS_NULLABLE")?;
+            if let Some(table) = tables.iter_mut().find(|table| table.name == table_name) {
+                table.columns.push(ColumnSchema {
+                    name: row.get("COLUMN_NAME")?,
+                    column_type: parse_type(&spanner_type),
+                    spanner_type,
+                    nullable: nullable == "YES",
+                });
+            }
+        }
+
+        for row in ctx.execute_query(INDEX_COLUMNS_QUERY, &[]).await?.iter() {
+            let table_name: &str = row.get("TABLE_NAME")?;
+            let table = match tables.iter_mut().find(|table| table.name == table_name) {
+                Some(table) => table,
+                None => continue,
+            };
+            let index_name: String = row.get("INDEX_NAME")?;
+            let index_type: &str = row.get("INDEX_TYPE")?;
+            let column_name: String = row.get("COLUMN_NAME")?;
+            let ordering: Option<&str> = row.get("COLUMN_ORDERING")?;
+            // Stored columns are not part of the index's key and have no position.
+            let position: Option<i64> = row.get("ORDINAL_POSITION")?;
+            let column = IndexColumn {
+                name: column_name,
+                descending: ordering == Some("DESC"),
+            };
+
+            if index_type == "PRIMARY_KEY" {
+                table.primary_key.push(column);
+                continue;
+            }
+            let index = match table
+                .indexes
+                .iter_mut()
+                .position(|index| index.name == index_name)
+            {
+                Some(position) => &mut table.indexes[position],
+                None => {
+                    table.indexes.push(IndexSchema {
+                        name: index_name,
+                        columns: Vec::new(),
+                        storing: Vec::new(),
+                        unique: row.get("IS_UNIQUE")?,
+                        null_filtered: row.get("IS_NULL_FILTERED")?,
+                    });
+                    table.indexes.last_mut().expect("index was just added")
+                }
+            };
+            if position.is_some() {
+                index.columns.push(column);
+            } else {
+                index.storing.push(column.name);
+            }
+        }
+
+        for row in ctx.execute_query(FOREIGN_KEYS_QUERY, &[]).await?.iter() {
+            let table_name: &str = row.get("TABLE_NAME")?;
+            let table = match tables.iter_mut().find(|table| table.name == table_name) {
+                Some(table) => table,
+                None => continue,
+            };
+            let name: String = row.get("CONSTRAINT_NAME")?;
+            let foreign_key = match table
+                .foreign_keys
+                .iter()
+                .position(|foreign_key| foreign_key.name == name)
+            {
+                Some(position) => &mut table.foreign_keys[position],
+                None => {
+                    let delete_rule: Option<&str> = row.get("DELETE_RULE")?;
+                    table.foreign_keys.push(ForeignKey {
+                        name,
+                        columns: Vec::new(),
+                        referenced_table: row.get("REFERENCED_TABLE_NAME")?,
+                        referenced_columns: Vec::new(),
+                        on_delete: OnDelete::parse(delete_rule),
+                    });
+                    table
+                        .foreign_keys
+                        .last_mut()
+                        .expect("foreign key was just added")
+                }
+            };
+            foreign_key.columns.push(row.get("COLUMN_NAME")?);
+            foreign_key
+                .referenced_columns
+                .push(row.get("REFERENCED_COLUMN_NAME")?);
+        }
+
+        for row in ctx
+            .execute_query(CHECK_CONSTRAINTS_QUERY, &[])
+            .await?
+            .iter()
+        {
+            let table_name: &str = row.get("TABLE_NAME")?;
+            if let Some(table) = tables.iter_mut().find(|table| table.name == table_name) {
+                table.check_constraints.push(CheckConstraint {
+                    name: row.get("CONSTRAINT_NAME")?,
+                    expression: row.get("CHECK_CLAUSE")?,
+                });
+            }
+        }
+
+        Ok(Self { tables })
+    }
+}
+
+impl TableSchema {
+    /// Returns the name of this table.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the columns of this table, in the order they were defined.
+    pub fn columns(&self) -> &[ColumnSchema] {
+        &self.columns
+    }
+
+    /// Returns the column with the provided name, if any.
+    pub fn column(&self, name: &str) -> Option<&ColumnSchema> {
+        self.columns.iter().find(|column| column.name == name)
+    }
+
+    /// Returns the columns of this table's primary key, in key order.
+    pub fn primary_key(&self) -> &[IndexColumn] {
+        &self.primary_key
+    }
+
+    /// Returns the secondary indexes of this table, ordered by name.
+    pub fn indexes(&self) -> &[IndexSchema] {
+        &self.indexes
+    }
+
+    /// Returns the parent of this table when it is interleaved, `None` for top-level tables.
+    pub fn interleaved_in(&self) -> Option<&Interleave> {
+        self.interleaved_in.as_ref()
+    }
+
+    /// Returns the foreign keys of this table, ordered by name.
+    pub fn foreign_keys(&self) -> &[ForeignKey] {
+        &self.foreign_keys
+    }
+
+    /// Returns the check constraints of this table, ordered by name.
+    ///
+    /// The constraints Cloud Spanner creates implicitly for `NOT NULL` columns are not included.
+    pub fn check_constraints(&self) -> &[CheckConstraint] {
+        &self.check_constraints
+    }
+}
+
+impl Interleave {
+    /// Returns the name of the parent table.
+    pub fn parent(&self) -> &str {
+        &self.parent
+    }
+
+    /// Returns what happens to the rows of this table when their parent row is deleted.
+    pub fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+}
+
+impl OnDelete {
+    // Parses the `ON_DELETE_ACTION` or `DELETE_RULE` columns, which are `NO ACTION` by default.
+    fn parse(action: Option<&str>) -> Self {
+        match action {
+            Some("CASCADE") => OnDelete::Cascade,
+            _ => OnDelete::NoAction,
+        }
+    }
+}
+
+impl ForeignKey {
+    /// Returns the name of this foreign key's constraint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the referencing columns of this table.
+    pub fn columns(&self) -> &[String] {
+        &self.columns
+    }
+
+    /// Returns the name of the referenced table.
+    pub fn referenced_table(&self) -> &str {
+        &self.referenced_table
+    }
+
+    /// Returns the referenced columns, in the same order as [`ForeignKey::columns`].
+    pub fn referenced_columns(&self) -> &[String] {
+        &self.referenced_columns
+    }
+
+    /// Returns what happens to referencing rows when a referenced row is deleted.
+    pub fn on_delete(&self) -> OnDelete {
+        self.on_delete
+    }
+}
+
+impl CheckConstraint {
+    /// Returns the name of this constraint.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the expression checked by this constraint, e.g.: `age >= 0`.
+    pub fn expression(&self) -> &str {
+        &self.expression
+    }
+}
+
+impl ColumnSchema {
+    /// Returns the name of this column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the type of this column as it appears in DDL statements, e.g.: `STRING(36)` or `ARRAY<INT64>`.
+    pub fn spanner_type(&self) -> &str {
+        &self.spanner_type
+    }
+
+    /// Returns the type of this column, or `None` when it is not supported by this crate (e.g.: a crate feature is disabled).
+    pub fn column_type(&self) -> Option<&Type> {
+        self.column_type.as_ref()
+    }
+
+    /// Returns true when this column accepts `NULL` values.
+    pub fn is_nullable(&self) -> bool {
+        self.nullable
+    }
+}
+
+impl IndexSchema {
+    /// Returns the name of this index.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the key columns of this index, in key order.
+    pub fn columns(&self) -> &[IndexColumn] {
+        &self.columns
+    }
+
+    /// Returns the names of the columns stored in this index using a `STORING` clause.
+    pub fn storing(&self) -> &[String] {
+        &self.storing
+    }
+
+    /// Returns true for `UNIQUE` indexes.
+    pub fn is_unique(&self) -> bool {
+        self.unique
+    }
+
+    /// Returns true for `NULL_FILTERED` indexes.
+    pub fn is_null_filtered(&self) -> bool {
+        self.null_filtered
+    }
+}
+
+impl IndexColumn {
+    /// Returns the name of this column.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true when this column is sorted in descending order.
+    pub fn is_descending(&self) -> bool {
+        self.descending
+    }
+}
+
+/// Parses the type of a column as reported in `INFORMATION_SCHEMA.COLUMNS.SPANNER_TYPE`, e.g.: `ARRAY<STRING(MAX)>`.
+fn parse_type(spanner_type: &str) -> Option<Type> {
+    if let Some(inner) = spanner_type
+        .strip_prefix("ARRAY<")
+        .and_then(|inner| inner.strip_suffix('>'))
+    {
+        return parse_type(inner).map(Type::array);
+    }
+    // Drop the length of STRING and BYTES columns.
+    let name = match spanner_type.find('(') {
+        Some(length) => &spanner_type[..length],
+        None => spanner_type,
+    };
+    match name {
+        "BOOL" => Some(Type::Bool),
+        "INT64" => Some(Type::Int64),
+        "FLOAT64" => Some(Type::Float64),
+        "STRING" => Some(Type::String),
+        "BYTES" => Some(Type::Bytes),
+        #[cfg(feature = "json")]
+        "JSON" => Some(Type::Json),
+        #[cfg(feature = "numeric")]
+        "NUMERIC" => Some(Type::Numeric),
+        #[cfg(feature = "temporal")]
+        "TIMESTAMP" => Some(Type::Timestamp),
+        #[cfg(feature = "temporal")]
+        "DATE" => Some(Type::Date),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_type() {
+        assert_eq!(parse_type("BOOL"), Some(Type::Bool));
+        assert_eq!(parse_type("INT64"), Some(Type::Int64));
+        assert_eq!(parse_type("STRING(MAX)"), Some(Type::String));
+        assert_eq!(parse_type("BYTES(1024)"), Some(Type::Bytes));
+        assert_eq!(
+            parse_type("ARRAY<STRING(36)>"),
+            Some(Type::array(Type::String))
+        );
+        assert_eq!(
+            parse_type("ARRAY<FLOAT64>"),
+            Some(Type::array(Type::Float64))
+        );
+        #[cfg(feature = "temporal")]
+        assert_eq!(parse_type("TIMESTAMP"), Some(Type::Timestamp));
+        assert_eq!(parse_type("TOKENLIST"), None);
+        assert_eq!(parse_type("ARRAY<TOKENLIST>"), None);
+    }
+}
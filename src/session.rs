@@ -1,49 +1,400 @@
-use bb8::ManageConnection;
-use tokio::sync::Mutex;
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex as SyncMutex};
+use std::time::Duration;
+
+use bb8::{Builder as PoolBuilder, ManageConnection, Pool, PooledConnection};
+use futures_util::future::try_join_all;
+use tokio::sync::{Mutex, MutexGuard};
+use tokio::time::Instant;
 
-use crate::Connection;
 use crate::Error;
+use crate::{Connection, SharedConnection};
 use google_api_proto::google::spanner::v1 as proto;
-pub(crate) struct Session(String);
+
+/// Cloud Spanner recommends replacing sessions before they are 28 days old.
+pub(crate) const DEFAULT_MAX_SESSION_LIFETIME: Duration = Duration::from_secs(28 * 24 * 60 * 60);
+
+#[derive(Clone, Debug)]
+pub(crate) struct Session {
+    name: String,
+    created: Instant,
+}
 
 impl Session {
     pub fn name(&self) -> &str {
-        &self.0
+        &self.name
+    }
+
+    /// Returns how long ago this session was created.
+    pub(crate) fn age(&self) -> Duration {
+        self.created.elapsed()
+    }
+}
+
+/// A session owned by a [`SessionPool`].
+///
+/// The pool drops its sessions once they can no longer be handed out, e.g.: when expired or released while draining,
+/// as well as idle ones reaped because of their `max_lifetime` or `idle_timeout`.
+/// Those dropped while [`SessionPool::drain`] collects them are deleted by it, others are deleted in the background.
+pub(crate) struct PooledSession {
+    session: Session,
+    connection: Arc<SharedConnection>,
+    // Shared with the pool, `Some` while draining collects the discarded sessions.
+    discarded: Arc<SyncMutex<Option<Vec<Session>>>>,
+}
+
+impl Drop for PooledSession {
+    fn drop(&mut self) {
+        let session = self.session.clone();
+        if let Some(discarded) = lock_discarded(&self.discarded).as_mut() {
+            discarded.push(session);
+        } else if let Ok(runtime) = tokio::runtime::Handle::try_current() {
+            // Best effort, Cloud Spanner eventually deletes unused sessions anyway.
+            let mut connection = self.connection.get();
+            runtime.spawn(async move {
+                let _ = connection.delete_session(session).await;
+            });
+        }
     }
 }
 
 impl From<proto::Session> for Session {
     fn from(value: proto::Session) -> Self {
-        Self(value.name)
+        Self {
+            name: value.name,
+            created: Instant::now(),
+        }
     }
 }
 
 pub(crate) struct SessionManager {
-    connection: Mutex<Box<dyn Connection>>,
+    connection: Arc<SharedConnection>,
+    // Set when the pool is drained, after which no session is created nor handed out.
+    draining: Arc<AtomicBool>,
+    // Sessions created in bulk ahead of time, these are handed out before creating new ones.
+    prefetched: Arc<SyncMutex<Vec<Session>>>,
+    // Sessions dropped by the pool while draining, see `PooledSession`.
+    discarded: Arc<SyncMutex<Option<Vec<Session>>>>,
+    // Sessions older than this are replaced instead of being handed out again.
+    max_session_lifetime: Duration,
 }
 
 impl SessionManager {
     pub(crate) fn new(connection: Box<dyn Connection>) -> Self {
         Self {
-            connection: Mutex::new(connection),
+            connection: Arc::new(SharedConnection::new(connection)),
+            draining: Arc::new(AtomicBool::new(false)),
+            prefetched: Arc::new(SyncMutex::new(Vec::new())),
+            discarded: Arc::new(SyncMutex::new(None)),
+            max_session_lifetime: DEFAULT_MAX_SESSION_LIFETIME,
+        }
+    }
+
+    pub(crate) fn with_max_session_lifetime(self, max_session_lifetime: Duration) -> Self {
+        Self {
+            max_session_lifetime,
+            ..self
+        }
+    }
+
+    fn is_draining(&self) -> bool {
+        self.draining.load(Ordering::SeqCst)
+    }
+
+    fn is_expired(&self, session: &Session) -> bool {
+        session.age() >= self.max_session_lifetime
+    }
+
+    /// Hands out a prefetched session, or creates a new one.
+    async fn create(&self) -> Result<Session, Error> {
+        if self.is_draining() {
+            return Err(draining_error());
+        }
+        let mut connection = self.connection.get();
+        let prefetched = lock(&self.prefetched).pop();
+        match prefetched {
+            Some(session) if !self.is_expired(&session) => return Ok(session),
+            Some(expired) => {
+                // Best effort, Cloud Spanner eventually deletes unused sessions anyway.
+                let _ = connection.delete_session(expired).await;
+            }
+            None => {}
         }
+        connection.create_session().await
+    }
+
+    async fn prefetch(&self, count: u32) -> Result<(), Error> {
+        let sessions = self.connection.get().batch_create_sessions(count).await?;
+        lock(&self.prefetched).extend(sessions);
+        Ok(())
     }
 }
 
+fn lock(prefetched: &SyncMutex<Vec<Session>>) -> std::sync::MutexGuard<'_, Vec<Session>> {
+    prefetched.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn lock_discarded(
+    discarded: &SyncMutex<Option<Vec<Session>>>,
+) -> std::sync::MutexGuard<'_, Option<Vec<Session>>> {
+    discarded.lock().unwrap_or_else(|err| err.into_inner())
+}
+
+fn draining_error() -> Error {
+    Error::Client("session pool is draining".to_string())
+}
+
 #[async_trait::async_trait]
 impl ManageConnection for SessionManager {
-    type Connection = Session;
+    type Connection = PooledSession;
     type Error = Error;
 
     async fn connect(&self) -> Result<Self::Connection, Self::Error> {
-        self.connection.lock().await.create_session().await
+        Ok(PooledSession {
+            session: self.create().await?,
+            connection: self.connection.clone(),
+            discarded: self.discarded.clone(),
+        })
     }
 
     async fn is_valid(&self, _conn: &mut Self::Connection) -> Result<(), Self::Error> {
         Ok(())
     }
 
-    fn has_broken(&self, _conn: &mut Self::Connection) -> bool {
-        false
+    // Sessions released while draining, or that exceeded their lifetime, are removed from the pool instead of being made available again.
+    fn has_broken(&self, conn: &mut Self::Connection) -> bool {
+        self.is_draining() || self.is_expired(&conn.session)
+    }
+}
+
+/// The source of sessions for a [`crate::Client`].
+///
+/// Sessions are either obtained from a `bb8` pool or, in single-session mode,
+/// the same session is handed out to one context at a time.
+#[derive(Clone)]
+pub(crate) enum SessionPool {
+    Pooled {
+        pool: Pool<SessionManager>,
+        draining: Arc<AtomicBool>,
+        prefetched: Arc<SyncMutex<Vec<Session>>>,
+        discarded: Arc<SyncMutex<Option<Vec<Session>>>>,
+        min_idle: u32,
+    },
+    Single(Arc<SingleSession>),
+}
+
+// How often the pool is checked for released sessions while draining.
+const DRAIN_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+impl SessionPool {
+    /// Creates a pool of sessions using the provided `bb8` configuration.
+    ///
+    /// Unless `lazy` is true, the pool is filled up to its minimum size using a single `BatchCreateSessions` request.
+    pub(crate) async fn pooled(
+        builder: PoolBuilder<SessionManager>,
+        manager: SessionManager,
+        min_idle: u32,
+        lazy: bool,
+    ) -> Result<Self, Error> {
+        let draining = manager.draining.clone();
+        let prefetched = manager.prefetched.clone();
+        let discarded = manager.discarded.clone();
+        let pool = if lazy {
+            builder.build_unchecked(manager)
+        } else {
+            if min_idle > 0 {
+                manager.prefetch(min_idle).await?;
+            }
+            builder.build(manager).await?
+        };
+        Ok(SessionPool::Pooled {
+            pool,
+            draining,
+            prefetched,
+            discarded,
+            min_idle,
+        })
+    }
+
+    /// Creates a pool of exactly one session.
+    ///
+    /// When `session` is `None`, the session is only created the first time it is needed.
+    pub(crate) fn single(manager: SessionManager, session: Option<Session>) -> Self {
+        SessionPool::Single(Arc::new(SingleSession {
+            manager,
+            session: Mutex::new(session),
+        }))
+    }
+
+    /// Obtains a session, waiting for one to become available if necessary.
+    pub(crate) async fn get(&self) -> Result<SessionGuard<'_>, Error> {
+        match self {
+            SessionPool::Pooled { draining, .. } if draining.load(Ordering::SeqCst) => {
+                Err(draining_error())
+            }
+            SessionPool::Pooled { pool, .. } => Ok(SessionGuard::Pooled(pool.get().await?)),
+            SessionPool::Single(single) => {
+                if single.manager.is_draining() {
+                    return Err(draining_error());
+                }
+                let mut session = single.session.lock().await;
+                if session
+                    .as_ref()
+                    .map_or(true, |session| single.manager.is_expired(session))
+                {
+                    let expired = session.replace(single.manager.create().await?);
+                    if let Some(expired) = expired {
+                        // Best effort, Cloud Spanner eventually deletes unused sessions anyway.
+                        let mut connection = single.manager.connection.get();
+                        let _ = connection.delete_session(expired).await;
+                    }
+                }
+                Ok(SessionGuard::Single(session))
+            }
+        }
+    }
+}
+
+impl SessionPool {
+    /// Fills the pool up to its minimum size, creating the missing sessions using a single `BatchCreateSessions` request.
+    pub(crate) async fn warmup(&self, mut connection: Box<dyn Connection>) -> Result<(), Error> {
+        match self {
+            SessionPool::Pooled {
+                pool,
+                prefetched,
+                min_idle,
+                ..
+            } => {
+                let missing = min_idle.saturating_sub(pool.state().idle_connections);
+                if missing > 0 {
+                    let sessions = connection.batch_create_sessions(missing).await?;
+                    lock(prefetched).extend(sessions);
+                }
+
+                // Hold on to enough sessions for the pool to create the missing ones, they become idle once released.
+                // They are obtained concurrently, so that this waits for at most one connection timeout overall.
+                let _sessions = try_join_all((0..*min_idle).map(|_| self.get())).await?;
+                Ok(())
+            }
+            SessionPool::Single(_) => self.get().await.map(|_| ()),
+        }
+    }
+
+    /// Stops handing out sessions, waits for the ones in use to be released and deletes them.
+    ///
+    /// Sessions that are still in use when the timeout elapses are deleted in the background once released.
+    pub(crate) async fn drain(
+        &self,
+        mut connection: Box<dyn Connection>,
+        timeout: Duration,
+    ) -> Result<(), Error> {
+        let deadline = Instant::now() + timeout;
+        match self {
+            SessionPool::Pooled {
+                pool,
+                draining,
+                prefetched,
+                discarded,
+                ..
+            } => {
+                // Collect the sessions dropped by the pool from now on, including those released while waiting.
+                lock_discarded(discarded).get_or_insert_with(Vec::new);
+                draining.store(true, Ordering::SeqCst);
+
+                let unused = std::mem::take(&mut *lock(prefetched));
+                for session in unused {
+                    connection.delete_session(session).await?;
+                }
+
+                let in_use = loop {
+                    let state = pool.state();
+                    let in_use = state.connections.saturating_sub(state.idle_connections);
+                    if in_use == 0 || Instant::now() >= deadline {
+                        break in_use;
+                    }
+                    tokio::time::sleep(DRAIN_POLL_INTERVAL).await;
+                };
+
+                // Idle sessions are dropped by the pool once released, since it is draining.
+                let mut idle = Vec::new();
+                for _ in 0..pool.state().idle_connections {
+                    idle.push(pool.get().await?);
+                }
+                drop(idle);
+
+                let released = lock_discarded(discarded).take().unwrap_or_default();
+                for session in released {
+                    connection.delete_session(session).await?;
+                }
+
+                if in_use > 0 {
+                    return Err(Error::Client(format!(
+                        "timed out waiting for {} session(s) to be released",
+                        in_use
+                    )));
+                }
+                Ok(())
+            }
+            SessionPool::Single(single) => {
+                single.manager.draining.store(true, Ordering::SeqCst);
+
+                let mut session = tokio::time::timeout_at(deadline, single.session.lock())
+                    .await
+                    .map_err(|_| {
+                        Error::Client(
+                            "timed out waiting for the session to be released".to_string(),
+                        )
+                    })?;
+                if let Some(session) = session.take() {
+                    connection.delete_session(session).await?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(crate) struct SingleSession {
+    manager: SessionManager,
+    session: Mutex<Option<Session>>,
+}
+
+/// A session obtained from a [`SessionPool`], it is made available again when dropped.
+pub(crate) enum SessionGuard<'a> {
+    Pooled(PooledConnection<'a, SessionManager>),
+    Single(MutexGuard<'a, Option<Session>>),
+}
+
+impl<'a> Deref for SessionGuard<'a> {
+    type Target = Session;
+
+    fn deref(&self) -> &Self::Target {
+        match self {
+            SessionGuard::Pooled(session) => &session.session,
+            SessionGuard::Single(session) => session
+                .as_ref()
+                .expect("single session should have been created"),
+        }
+    }
+}
+
+impl<'a> DerefMut for SessionGuard<'a> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match self {
+            SessionGuard::Pooled(session) => &mut session.session,
+            SessionGuard::Single(session) => session
+                .as_mut()
+                .expect("single session should have been created"),
+        }
+    }
+}
+
+impl<'a> SessionGuard<'a> {
+    /// Replaces the guarded session, e.g.: when it no longer exists in Cloud Spanner.
+    ///
+    /// The new session is made available to the pool instead of the replaced one when this guard is dropped.
+    pub(crate) fn replace(&mut self, session: Session) {
+        **self = session;
     }
 }
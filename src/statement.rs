@@ -1,32 +1,1008 @@
-use crate::ToSpanner;
-#[cfg(doc)]
-use crate::TransactionContext;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
 use google_api_proto::google::spanner::v1 as proto;
 
-/// A single DML statement that can be used in a batch of DML statements using [`TransactionContext::execute_updates`]
-pub struct Statement<'a> {
-    pub sql: &'a str,
-    pub params: &'a [(&'a str, &'a (dyn ToSpanner + Sync))],
+#[cfg(doc)]
+use crate::{
+    BatchClient, Client, ReadContext, ReadContextExt, TransactionContext, TransactionContextExt,
+};
+use crate::{Dialect, Error, SpannerType, ToSpanner, Type, Value};
+
+/// A SQL statement along with the values of its parameters.
+///
+/// Statements are built fluently and can be executed using [`ReadContext::execute_statement`],
+/// [`TransactionContext::execute_update_statement`] or in a batch of DML statements using [`TransactionContext::execute_updates`].
+/// They own their parameter values, so they can be constructed centrally, cloned and executed several times.
+///
+/// [`ReadContext::execute_query`] and [`TransactionContext::execute_update`] take SQL text and parameters instead,
+/// and convert them to a statement. Statements can also be obtained from SQL strings and [`PreparedStatement`]s using `From`,
+/// which is how [`Client::partition_query`], [`BatchClient::partition_query`] and [`BatchClient::export`] accept either.
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::Statement;
+///
+/// let statement = Statement::new("SELECT * FROM person WHERE id = @id AND name = @name")
+///     .bind("id", 42)
+///     .bind("name", "ferris");
+/// assert_eq!(statement.params().count(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Statement {
+    sql: Arc<str>,
+    params: Vec<(String, Value)>,
+    // The first parameter conversion error, reported when the statement is executed.
+    error: Option<String>,
+    // Set on statements bound from a `PreparedStatement`.
+    prepared: Option<Arc<Prepared>>,
+    // Overrides `RpcTimeouts::read` when set.
+    deadline: Option<Duration>,
 }
 
-impl<'a> TryFrom<&Statement<'a>> for proto::execute_batch_dml_request::Statement {
-    type Error = crate::Error;
+impl Statement {
+    /// Creates a new statement with the provided SQL and no parameters.
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: Arc::from(sql.into()),
+            params: Vec::new(),
+            error: None,
+            prepared: None,
+            deadline: None,
+        }
+    }
+
+    /// Binds a value to the named parameter, replacing any value previously bound to the same name.
+    ///
+    /// See [`ToSpanner`] to determine how Rust values are mapped to Cloud Spanner values.
+    /// Any conversion error is returned when the statement is executed.
+    #[must_use]
+    pub fn bind<T>(self, name: impl Into<String>, value: T) -> Self
+    where
+        T: ToSpanner,
+    {
+        self.bind_ref(name.into(), &value)
+    }
+
+    /// Binds a value to the named parameter using the provided type instead of the value's own type.
+    ///
+    /// This is mostly useful for `NULL`s and empty arrays, whose type Cloud Spanner cannot infer from the value.
+    /// Any conversion error, or a non-`NULL` value whose type differs from the provided one, is returned when the statement is executed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Statement, Type, Value};
+    ///
+    /// let statement = Statement::new("UPDATE person SET age = @age, tags = @tags WHERE id = 42")
+    ///     .bind_typed("age", None::<i64>, Type::Int64)
+    ///     .bind_typed("tags", Vec::<String>::new(), Type::array(Type::String));
+    /// assert_eq!(statement.params().next(), Some(("age", &Value::Null(Type::Int64))));
+    /// ```
+    #[must_use]
+    pub fn bind_typed<T>(self, name: impl Into<String>, value: T, tpe: Type) -> Self
+    where
+        T: ToSpanner,
+    {
+        let name = name.into();
+        if self.error.is_some() {
+            return self;
+        }
+        let value = match value.to_spanner() {
+            Ok(Value::Null(_)) => Ok(Value::Null(tpe)),
+            Ok(Value::Array(_, values)) if values.is_empty() => match tpe {
+                Type::Array(inner) => Ok(Value::Array(*inner, values)),
+                other => Err(format!("an array cannot be bound as {}", other)),
+            },
+            Ok(value) if value.spanner_type() == tpe => Ok(value),
+            Ok(value) => Err(format!(
+                "a value of type {} cannot be bound as {}",
+                value.spanner_type(),
+                tpe
+            )),
+            Err(err) => Err(err.to_string()),
+        };
+        match value {
+            Ok(value) => self.bind_value(name, value),
+            Err(err) => Statement {
+                error: Some(format!("invalid value for parameter {}: {}", name, err)),
+                ..self
+            },
+        }
+    }
+
+    /// Creates a DML statement that inserts all the provided rows into `table` in a single round trip.
+    ///
+    /// The rows are bound as a single `ARRAY<STRUCT<...>>` parameter named `rows`,
+    /// i.e.: `INSERT INTO table (a, b) SELECT * FROM UNNEST(@rows)`.
+    /// The inserted columns are the names of the fields of the `STRUCT` type returned by [`SpannerType::spanner_type`],
+    /// which must therefore be named after the table's columns.
+    ///
+    /// An error is returned when the statement is executed if `T` does not map to a `STRUCT` type with named fields.
+    /// Note that this uses GoogleSQL syntax.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Error, SpannerType, Statement, Struct, StructType, ToSpanner, Type, Value};
+    ///
+    /// struct Person {
+    ///     id: i64,
+    ///     name: String,
+    /// }
+    ///
+    /// impl ToSpanner for Person {
+    ///     fn to_spanner(&self) -> Result<Value, Error> {
+    ///         let Type::Struct(struct_type) = Self::spanner_type() else { unreachable!() };
+    ///         Ok(Value::Struct(Struct::new(
+    ///             struct_type,
+    ///             vec![self.id.to_spanner()?, self.name.to_spanner()?],
+    ///         )))
+    ///     }
+    /// }
+    ///
+    /// impl SpannerType for Person {
+    ///     fn spanner_type() -> Type {
+    ///         Type::strct(vec![("id", Type::Int64), ("name", Type::String)])
+    ///     }
+    /// }
+    ///
+    /// let people = vec![
+    ///     Person { id: 1, name: "ferris".to_string() },
+    ///     Person { id: 2, name: "corro".to_string() },
+    /// ];
+    /// let statement = Statement::bulk_insert("person", people);
+    /// assert_eq!(statement.sql(), "INSERT INTO person (id, name) SELECT * FROM UNNEST(@rows)");
+    /// ```
+    pub fn bulk_insert<T, I>(table: &str, rows: I) -> Self
+    where
+        T: ToSpanner + SpannerType,
+        I: IntoIterator<Item = T>,
+    {
+        let columns = match T::spanner_type() {
+            Type::Struct(struct_type) => struct_type
+                .field_names()
+                .map(|name| {
+                    name.clone()
+                        .ok_or_else(|| "bulk inserts require named STRUCT fields".to_string())
+                })
+                .collect::<Result<Vec<String>, String>>(),
+            other => Err(format!("bulk inserts require a STRUCT type, got {}", other)),
+        };
+        match columns {
+            Ok(columns) => Statement::new(format!(
+                "INSERT INTO {} ({}) SELECT * FROM UNNEST(@rows)",
+                table,
+                columns.join(", ")
+            ))
+            .bind("rows", rows.into_iter().collect::<Vec<T>>()),
+            Err(error) => Statement {
+                error: Some(error),
+                ..Statement::new(format!("INSERT INTO {}", table))
+            },
+        }
+    }
+
+    /// Binds a [`Value`] to the named parameter, replacing any value previously bound to the same name.
+    ///
+    /// This allows binding values whose type is only known at runtime, such as [`crate::Struct`]s, arrays of structs
+    /// or typed `NULL`s. The parameter's type, including the names of struct fields, is sent along with its value,
+    /// so struct parameters can be used in `IN UNNEST(@param)` or in struct comparisons.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Statement, Struct, StructType, Type, Value};
+    ///
+    /// let key_type = StructType::new(vec![("id", Type::Int64), ("name", Type::String)]);
+    /// let keys = vec![
+    ///     Value::Struct(Struct::new(key_type.clone(), vec![Value::Int64(1), Value::String("ferris".to_string())])),
+    ///     Value::Struct(Struct::new(key_type.clone(), vec![Value::Int64(2), Value::String("corro".to_string())])),
+    /// ];
+    /// let statement = Statement::new("SELECT * FROM person WHERE STRUCT<id INT64, name STRING>(id, name) IN UNNEST(@keys)")
+    ///     .bind_value("keys", Value::Array(Type::Struct(key_type), keys));
+    /// ```
+    #[must_use]
+    pub fn bind_value(mut self, name: impl Into<String>, value: Value) -> Self {
+        let name = name.into();
+        match self.params.iter_mut().find(|(param, _)| *param == name) {
+            Some((_, bound)) => *bound = value,
+            None => self.params.push((name, value)),
+        }
+        self
+    }
+
+    pub(crate) fn bind_ref(mut self, name: String, value: &dyn ToSpanner) -> Self {
+        if self.error.is_some() {
+            return self;
+        }
+        match value.to_spanner() {
+            Ok(value) => self.bind_value(name, value),
+            Err(err) => {
+                self.error = Some(format!("invalid value for parameter {}: {}", name, err));
+                self
+            }
+        }
+    }
+
+    /// Creates a new statement from the SQL and parameter slice accepted by [`ReadContext::execute_query`].
+    pub(crate) fn from_parameters(
+        sql: &str,
+        parameters: &[(&str, &(dyn ToSpanner + Sync))],
+    ) -> Self {
+        Self::with_parameters(sql, parameters)
+    }
+
+    /// Creates a new statement with the provided SQL and parameters, which may be collected in a map, see [`IntoParameters`].
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use std::collections::HashMap;
+    /// use spanner_rs::{Statement, Value};
+    ///
+    /// let mut params = HashMap::new();
+    /// params.insert("id", Value::Int64(42));
+    /// params.insert("name", Value::String("ferris".to_string()));
+    ///
+    /// let statement = Statement::with_parameters("SELECT * FROM person WHERE id = @id AND name = @name", params);
+    /// assert_eq!(statement.params().count(), 2);
+    /// ```
+    pub fn with_parameters(sql: impl Into<String>, parameters: impl IntoParameters) -> Self {
+        parameters.bind_to(Self::new(sql))
+    }
 
-    fn try_from(
-        value: &Statement,
-    ) -> Result<proto::execute_batch_dml_request::Statement, Self::Error> {
-        let mut params = std::collections::BTreeMap::new();
-        let mut param_types = std::collections::BTreeMap::new();
-        for (name, value) in value.params {
-            let value = value.to_spanner()?;
-            param_types.insert(name.to_string(), value.spanner_type().into());
-            params.insert(name.to_string(), value.try_into()?);
+    /// Sets the deadline of the requests executing this statement, overriding the client's [`crate::RpcTimeouts::read`].
+    ///
+    /// This allows using different limits within the same client, e.g.: short ones for interactive requests
+    /// and long ones for batch jobs. When executed in a batch of DML statements, the batch's deadline is the longest of its statements'.
+    ///
+    /// # Example
+    ///
+    /// ```no_run
+    /// # use spanner_rs::{Client, Error, ReadContext, Statement};
+    /// # use std::time::Duration;
+    /// # #[tokio::main]
+    /// # async fn main() -> Result<(), Error> {
+    /// # let client = Client::configure().connect().await?;
+    /// let statement = Statement::new("SELECT name FROM person WHERE id = @id")
+    ///     .bind("id", 42)
+    ///     .with_deadline(Duration::from_secs(2));
+    /// let rs = client.read_only().execute_statement(&statement).await?;
+    /// # Ok(()) }
+    /// ```
+    pub fn with_deadline(self, deadline: Duration) -> Self {
+        Self {
+            deadline: Some(deadline),
+            ..self
         }
+    }
+
+    /// Returns the deadline set using [`Statement::with_deadline`], if any.
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
 
-        Ok(proto::execute_batch_dml_request::Statement {
-            sql: value.sql.to_string(),
-            params: Some(prost_types::Struct { fields: params }),
-            param_types,
+    /// Returns this statement's SQL.
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns an iterator over the names and values of this statement's parameters.
+    pub fn params(&self) -> impl Iterator<Item = (&str, &Value)> {
+        self.params
+            .iter()
+            .map(|(name, value)| (name.as_str(), value))
+    }
+
+    /// Returns the parameters of this statement or the error that occurred while binding them.
+    pub(crate) fn values(&self) -> Result<&[(String, Value)], Error> {
+        match &self.error {
+            Some(error) => Err(Error::Codec(error.clone())),
+            None => Ok(&self.params),
+        }
+    }
+
+    /// Returns the [`Fingerprint`] of this statement's SQL.
+    pub fn fingerprint(&self) -> Fingerprint {
+        Fingerprint::of(&self.sql)
+    }
+
+    /// Returns a [`PreparedStatement`] for executing this statement repeatedly with different values.
+    ///
+    /// The parameters currently bound to this statement are retained, as default values, along with their types.
+    pub fn prepare(self) -> PreparedStatement {
+        let types = self
+            .params
+            .iter()
+            .map(|(name, value)| (name.clone(), value.spanner_type()))
+            .collect();
+        PreparedStatement(Statement {
+            prepared: Some(Arc::new(Prepared {
+                types,
+                google_standard_sql: OnceLock::new(),
+                postgresql: OnceLock::new(),
+            })),
+            ..self
         })
     }
+
+    pub(crate) fn prepared(&self) -> Option<&Prepared> {
+        self.prepared.as_deref()
+    }
+}
+
+/// Parameters that can be bound to a [`Statement`], see [`Statement::with_parameters`],
+/// [`ReadContextExt::execute_query_with_parameters`] and [`TransactionContextExt::execute_update_with_parameters`].
+///
+/// Besides the parameter slices accepted by [`ReadContext::execute_query`], this is implemented for maps
+/// (`HashMap` and `BTreeMap`, owned or borrowed) from parameter names to either [`Value`]s or `&dyn ToSpanner` values,
+/// which is how dynamically built queries usually collect their parameters.
+pub trait IntoParameters {
+    /// Binds these parameters to the provided statement.
+    fn bind_to(self, statement: Statement) -> Statement;
+}
+
+mod sealed {
+    use super::*;
+
+    /// The values of parameter maps, see [`IntoParameters`].
+    pub trait ParameterValue {
+        fn bind_to(&self, statement: Statement, name: &str) -> Statement;
+
+        /// Binds the value of an owned map, without copying it when possible.
+        fn bind_into(self, statement: Statement, name: &str) -> Statement
+        where
+            Self: Sized,
+        {
+            self.bind_to(statement, name)
+        }
+    }
+
+    impl ParameterValue for Value {
+        fn bind_to(&self, statement: Statement, name: &str) -> Statement {
+            statement.bind_value(name, self.clone())
+        }
+
+        fn bind_into(self, statement: Statement, name: &str) -> Statement {
+            statement.bind_value(name, self)
+        }
+    }
+
+    impl ParameterValue for &dyn ToSpanner {
+        fn bind_to(&self, statement: Statement, name: &str) -> Statement {
+            statement.bind_ref(name.to_string(), *self)
+        }
+    }
+
+    impl ParameterValue for &(dyn ToSpanner + Sync) {
+        fn bind_to(&self, statement: Statement, name: &str) -> Statement {
+            statement.bind_ref(name.to_string(), *self)
+        }
+    }
+}
+
+impl IntoParameters for &[(&str, &(dyn ToSpanner + Sync))] {
+    fn bind_to(self, statement: Statement) -> Statement {
+        self.iter().fold(statement, |statement, (name, value)| {
+            statement.bind_ref(name.to_string(), *value)
+        })
+    }
+}
+
+impl<K, V, S> IntoParameters for &HashMap<K, V, S>
+where
+    K: AsRef<str>,
+    V: sealed::ParameterValue,
+{
+    fn bind_to(self, statement: Statement) -> Statement {
+        self.iter().fold(statement, |statement, (name, value)| {
+            value.bind_to(statement, name.as_ref())
+        })
+    }
+}
+
+impl<K, V, S> IntoParameters for HashMap<K, V, S>
+where
+    K: AsRef<str>,
+    V: sealed::ParameterValue,
+{
+    fn bind_to(self, statement: Statement) -> Statement {
+        self.into_iter()
+            .fold(statement, |statement, (name, value)| {
+                value.bind_into(statement, name.as_ref())
+            })
+    }
+}
+
+impl<K, V> IntoParameters for &BTreeMap<K, V>
+where
+    K: AsRef<str>,
+    V: sealed::ParameterValue,
+{
+    fn bind_to(self, statement: Statement) -> Statement {
+        self.iter().fold(statement, |statement, (name, value)| {
+            value.bind_to(statement, name.as_ref())
+        })
+    }
+}
+
+impl<K, V> IntoParameters for BTreeMap<K, V>
+where
+    K: AsRef<str>,
+    V: sealed::ParameterValue,
+{
+    fn bind_to(self, statement: Statement) -> Statement {
+        self.into_iter()
+            .fold(statement, |statement, (name, value)| {
+                value.bind_into(statement, name.as_ref())
+            })
+    }
+}
+
+/// A statement that is meant to be executed several times with different parameter values.
+///
+/// Encoding parameters requires converting each of their types to the representation expected by Cloud Spanner.
+/// A prepared statement does this once per dialect and only encodes the values on subsequent executions.
+/// Each statement bound from a prepared statement shares its SQL text, rather than copying it,
+/// and starts with the parameter values the statement was prepared with.
+///
+/// Statements bound from a prepared statement should bind values of the same types to the same parameters as
+/// the statement it was prepared from. Binding other parameters or types is allowed, but their types are then encoded
+/// on every execution.
+///
+/// # Example
+///
+/// ```no_run
+/// # use spanner_rs::{Client, Error, ReadContext, Statement};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let mut client = Client::configure().connect().await?;
+/// let lookup = Statement::new("SELECT name FROM person WHERE id = @id")
+///     .bind("id", 0)
+///     .prepare();
+///
+/// let mut read_only = client.read_only();
+/// for id in 1..10 {
+///     let rs = read_only.execute_statement(&lookup.bind("id", id)).await?;
+///     # let _ = rs;
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug)]
+pub struct PreparedStatement(Statement);
+
+impl PreparedStatement {
+    /// Returns a new [`Statement`] with the provided value bound to the named parameter.
+    ///
+    /// Other parameters retain the values they had when this statement was prepared.
+    pub fn bind<T>(&self, name: impl Into<String>, value: T) -> Statement
+    where
+        T: ToSpanner,
+    {
+        self.statement().bind(name, value)
+    }
+
+    /// Returns a new [`Statement`] with the parameter values this statement was prepared with.
+    pub fn statement(&self) -> Statement {
+        self.0.clone()
+    }
+
+    /// Returns this statement's SQL.
+    pub fn sql(&self) -> &str {
+        self.0.sql()
+    }
+}
+
+impl From<&str> for Statement {
+    fn from(sql: &str) -> Self {
+        Statement::new(sql)
+    }
+}
+
+impl From<String> for Statement {
+    fn from(sql: String) -> Self {
+        Statement::new(sql)
+    }
+}
+
+/// Returns a statement with the parameter values the statement was prepared with.
+impl From<PreparedStatement> for Statement {
+    fn from(prepared: PreparedStatement) -> Self {
+        prepared.0
+    }
+}
+
+/// Allows executing a prepared statement with the parameter values it was prepared with, without cloning it.
+impl AsRef<Statement> for PreparedStatement {
+    fn as_ref(&self) -> &Statement {
+        &self.0
+    }
+}
+
+/// The parameter types of a [`PreparedStatement`] and their encoding, shared by the statements bound from it.
+#[derive(Debug)]
+pub(crate) struct Prepared {
+    types: Vec<(String, Type)>,
+    google_standard_sql: OnceLock<BTreeMap<String, proto::Type>>,
+    postgresql: OnceLock<BTreeMap<String, proto::Type>>,
+}
+
+impl Prepared {
+    /// Returns true when the provided parameters have the same names and types as the prepared ones.
+    pub(crate) fn matches(&self, params: &[(String, Value)]) -> bool {
+        self.types.len() == params.len()
+            && self
+                .types
+                .iter()
+                .zip(params)
+                .all(|((name, tpe), (param, value))| name == param && value.has_type(tpe))
+    }
+
+    /// Returns the parameter types encoded for the provided dialect, encoding them using `encode` the first time.
+    pub(crate) fn param_types<F>(
+        &self,
+        dialect: Dialect,
+        encode: F,
+    ) -> &BTreeMap<String, proto::Type>
+    where
+        F: FnOnce(&[(String, Type)]) -> BTreeMap<String, proto::Type>,
+    {
+        let encoded = match dialect {
+            Dialect::GoogleStandardSql => &self.google_standard_sql,
+            Dialect::PostgreSql => &self.postgresql,
+        };
+        encoded.get_or_init(|| encode(&self.types))
+    }
+}
+
+/// A stable identifier for the "shape" of a SQL statement.
+///
+/// Statements that only differ in their literal values, comments, whitespace or keyword and identifier case share the same fingerprint.
+/// For example, `SELECT * FROM person WHERE id = 42` and `select *  from PERSON where id=7 -- lookup` have the same fingerprint.
+/// Parameter names are retained, so `@id` and `@other_id` produce different fingerprints.
+///
+/// This allows grouping statements in metrics or logs without recording the raw SQL which may contain sensitive values.
+///
+/// The fingerprint is computed by hashing a normalized version of the SQL using FNV-1a, which makes it stable
+/// across processes, platforms and versions of the Rust compiler. Its [`Display`](std::fmt::Display) implementation
+/// produces a fixed-width hexadecimal string.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u64);
+
+impl Fingerprint {
+    /// Computes the fingerprint of the provided SQL statement.
+    pub fn of(sql: &str) -> Self {
+        const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+        const FNV_PRIME: u64 = 0x100000001b3;
+
+        let hash = normalize(sql).bytes().fold(FNV_OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(FNV_PRIME)
+        });
+        Self(hash)
+    }
+
+    /// Returns the numerical value of this fingerprint.
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+/// A coarse classification of SQL statements, based on their leading keyword.
+///
+/// This is a heuristic that ignores leading comments, statement hints and parentheses.
+/// It does not parse nor validate the statement.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatementKind {
+    /// A query, e.g.: `SELECT` or `WITH`.
+    Query,
+    /// A DML statement, e.g.: `INSERT`, `UPDATE` or `DELETE`.
+    Dml,
+    /// A DDL statement, e.g.: `CREATE`, `ALTER` or `DROP`.
+    Ddl,
+    /// Any other statement.
+    Unknown,
+}
+
+impl StatementKind {
+    /// Classifies the provided SQL statement.
+    pub fn of(sql: &str) -> Self {
+        let normalized = normalize(sql);
+        let mut remaining = normalized.as_str();
+        loop {
+            remaining = remaining.trim_start_matches('(');
+            match remaining.strip_prefix("@{") {
+                Some(hint) => remaining = hint.split_once('}').map_or("", |(_, rest)| rest),
+                None => break,
+            }
+        }
+
+        let keyword = remaining
+            .split(|c: char| !(c.is_alphanumeric() || c == '_'))
+            .next()
+            .unwrap_or_default();
+
+        match keyword {
+            "select" | "with" | "graph" | "show" => StatementKind::Query,
+            "insert" | "update" | "delete" => StatementKind::Dml,
+            "create" | "alter" | "drop" | "grant" | "revoke" | "rename" | "analyze" => {
+                StatementKind::Ddl
+            }
+            _ => StatementKind::Unknown,
+        }
+    }
+
+    /// Returns true when the statement is a query and therefore does not write anything.
+    pub fn is_query(&self) -> bool {
+        *self == StatementKind::Query
+    }
+}
+
+// Normalizes SQL by removing comments, removing whitespace that does not separate words, lowercasing keywords and identifiers
+// and replacing string and numeric literals with `?`. Quoted identifiers are retained as-is.
+fn normalize(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+    let mut separate = false;
+
+    fn is_word(c: char) -> bool {
+        c.is_alphanumeric() || c == '_' || c == '@' || c == '?' || c == '`'
+    }
+
+    while let Some(c) = chars.next() {
+        if c.is_whitespace() {
+            separate = true;
+            continue;
+        }
+        if c == '#' || (c == '-' && chars.peek() == Some(&'-')) {
+            for c in chars.by_ref() {
+                if c == '\n' {
+                    break;
+                }
+            }
+            separate = true;
+            continue;
+        }
+        if c == '/' && chars.peek() == Some(&'*') {
+            chars.next();
+            let mut previous = None;
+            for c in chars.by_ref() {
+                if previous == Some('*') && c == '/' {
+                    break;
+                }
+                previous = Some(c);
+            }
+            separate = true;
+            continue;
+        }
+
+        if separate && is_word(c) && normalized.ends_with(is_word) {
+            normalized.push(' ');
+        }
+        separate = false;
+
+        match c {
+            '\'' | '"' => {
+                let mut escaped = false;
+                for next in chars.by_ref() {
+                    if escaped {
+                        escaped = false;
+                    } else if next == '\\' {
+                        escaped = true;
+                    } else if next == c {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            '`' => {
+                normalized.push(c);
+                for next in chars.by_ref() {
+                    normalized.push(next);
+                    if next == '`' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_ascii_digit() && !normalized.ends_with(is_word) => {
+                let mut previous = c;
+                while let Some(&next) = chars.peek() {
+                    let exponent_sign =
+                        (next == '+' || next == '-') && (previous == 'e' || previous == 'E');
+                    if next.is_ascii_alphanumeric() || next == '.' || exponent_sign {
+                        previous = next;
+                        chars.next();
+                    } else {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            c => normalized.extend(c.to_lowercase()),
+        }
+    }
+
+    normalized
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_with_deadline() {
+        let statement = Statement::new("SELECT 1").bind("id", 42);
+        assert_eq!(statement.deadline(), None);
+
+        let statement = statement.with_deadline(Duration::from_secs(2));
+        assert_eq!(statement.deadline(), Some(Duration::from_secs(2)));
+        assert_eq!(statement.params().count(), 1);
+        let prepared = statement.prepare().bind("id", 43);
+        assert_eq!(prepared.deadline(), Some(Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn test_with_parameters() {
+        let sorted = |statement: &Statement| {
+            let mut params: Vec<(String, Value)> = statement
+                .params()
+                .map(|(name, value)| (name.to_string(), value.clone()))
+                .collect();
+            params.sort_by(|(a, _), (b, _)| a.cmp(b));
+            params
+        };
+        let expected = vec![
+            ("id".to_string(), Value::Int64(42)),
+            ("name".to_string(), Value::String("ferris".to_string())),
+        ];
+
+        let values: HashMap<String, Value> = HashMap::from([
+            ("id".to_string(), Value::Int64(42)),
+            ("name".to_string(), Value::String("ferris".to_string())),
+        ]);
+        let statement = Statement::with_parameters("SELECT 1", &values);
+        assert_eq!(statement.sql(), "SELECT 1");
+        assert_eq!(sorted(&statement), expected);
+        assert_eq!(
+            sorted(&Statement::with_parameters("SELECT 1", values)),
+            expected
+        );
+
+        let refs: BTreeMap<&str, &dyn ToSpanner> = BTreeMap::from([
+            ("id", &42 as &dyn ToSpanner),
+            ("name", &"ferris" as &dyn ToSpanner),
+        ]);
+        assert_eq!(
+            sorted(&Statement::with_parameters("SELECT 1", refs)),
+            expected
+        );
+
+        let slice: &[(&str, &(dyn ToSpanner + Sync))] = &[("id", &42), ("name", &"ferris")];
+        assert_eq!(
+            sorted(&Statement::with_parameters("SELECT 1", slice)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(
+            normalize("SELECT *\n  FROM Person\tWHERE id = 42"),
+            "select*from person where id=?"
+        );
+        assert_eq!(
+            normalize("SELECT name FROM person WHERE name = 'O\\'Reilly' AND alias = \"x\""),
+            "select name from person where name=? and alias=?"
+        );
+        assert_eq!(
+            normalize("SELECT 1.5e-3, 0x1F, col1 FROM t2 -- trailing\n# other\n/* block */"),
+            "select ?,?,col1 from t2"
+        );
+        assert_eq!(
+            normalize("SELECT * FROM `My Table` WHERE id = @id"),
+            "select*from `My Table` where id=@id"
+        );
+    }
+
+    #[test]
+    fn test_fingerprint() {
+        assert_eq!(
+            Fingerprint::of("SELECT * FROM person WHERE id = 42"),
+            Fingerprint::of("select *  from PERSON where id=7 -- lookup"),
+        );
+        assert_eq!(
+            Fingerprint::of("SELECT * FROM person WHERE name = 'ferris'"),
+            Fingerprint::of("SELECT *\nFROM person\nWHERE name = \"crab\""),
+        );
+        assert_ne!(
+            Fingerprint::of("SELECT * FROM person WHERE id = @id"),
+            Fingerprint::of("SELECT * FROM person WHERE id = @other_id"),
+        );
+        assert_ne!(
+            Fingerprint::of("SELECT * FROM person"),
+            Fingerprint::of("SELECT * FROM versions"),
+        );
+        assert_eq!(Fingerprint::of("").value(), 0xcbf29ce484222325);
+        assert_eq!(Fingerprint::of("").to_string(), "cbf29ce484222325");
+    }
+
+    #[test]
+    fn test_statement_kind() {
+        assert_eq!(StatementKind::of("SELECT 1"), StatementKind::Query);
+        assert_eq!(
+            StatementKind::of("  -- comment\n(select * FROM person)"),
+            StatementKind::Query
+        );
+        assert_eq!(
+            StatementKind::of(
+                "@{USE_ADDITIONAL_PARALLELISM=TRUE} WITH t AS (SELECT 1) SELECT * FROM t"
+            ),
+            StatementKind::Query
+        );
+        assert_eq!(
+            StatementKind::of("/* writes */ INSERT INTO person(id) VALUES (1)"),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            StatementKind::of("update person SET name = 'select' WHERE id = 1"),
+            StatementKind::Dml
+        );
+        assert_eq!(
+            StatementKind::of("DELETE FROM person WHERE true"),
+            StatementKind::Dml
+        );
+        assert_eq!(StatementKind::of("DROP TABLE person"), StatementKind::Ddl);
+        assert_eq!(
+            StatementKind::of("CALL cancel_query('1')"),
+            StatementKind::Unknown
+        );
+        assert_eq!(StatementKind::of(""), StatementKind::Unknown);
+    }
+
+    #[test]
+    fn test_bind() {
+        let statement = Statement::new("SELECT @id, @name")
+            .bind("id", 42)
+            .bind("name", "ferris")
+            .bind("id", 7);
+
+        assert_eq!(statement.sql(), "SELECT @id, @name");
+        assert_eq!(
+            statement.params().collect::<Vec<_>>(),
+            vec![
+                ("id", &Value::Int64(7)),
+                ("name", &Value::String("ferris".to_string()))
+            ]
+        );
+        assert!(statement.values().is_ok());
+    }
+
+    #[test]
+    fn test_bind_error() {
+        struct Invalid;
+        impl ToSpanner for Invalid {
+            fn to_spanner(&self) -> Result<Value, Error> {
+                Err(Error::Codec("invalid".to_string()))
+            }
+        }
+
+        let statement = Statement::new("SELECT @invalid").bind("invalid", Invalid);
+        match statement.values() {
+            Err(Error::Codec(msg)) => assert!(msg.contains("invalid")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_prepare() {
+        let prepared = Statement::new("SELECT @id")
+            .bind("id", 0)
+            .bind("name", "ferris")
+            .prepare();
+
+        let statement = prepared.bind("id", 42);
+        assert_eq!(statement.sql(), "SELECT @id");
+        assert_eq!(
+            statement.params().collect::<Vec<_>>(),
+            vec![
+                ("id", &Value::Int64(42)),
+                ("name", &Value::String("ferris".to_string()))
+            ]
+        );
+        assert_eq!(prepared.sql(), "SELECT @id");
+
+        let prepared_params = statement.prepared().unwrap();
+        assert!(prepared_params.matches(statement.values().unwrap()));
+        assert!(!prepared_params.matches(prepared.bind("id", "42").values().unwrap()));
+        assert!(!prepared_params.matches(prepared.bind("other", 42).values().unwrap()));
+        assert!(prepared_params.matches(prepared.bind("name", None::<String>).values().unwrap()));
+    }
+
+    #[test]
+    fn test_from() {
+        let statement = Statement::from("SELECT 1");
+        assert_eq!(statement.sql(), "SELECT 1");
+        assert_eq!(statement.params().count(), 0);
+
+        let prepared = Statement::new("SELECT @id").bind("id", 42).prepare();
+        assert_eq!(prepared.as_ref().sql(), "SELECT @id");
+        let statement = Statement::from(prepared);
+        assert!(statement.prepared().is_some());
+        assert_eq!(
+            statement.params().collect::<Vec<_>>(),
+            vec![("id", &Value::Int64(42))]
+        );
+    }
+
+    #[test]
+    fn test_bulk_insert() {
+        use crate::{Struct, StructType};
+
+        struct Person(i64, &'static str);
+        impl ToSpanner for Person {
+            fn to_spanner(&self) -> Result<Value, Error> {
+                Ok(Value::Struct(Struct::new(
+                    StructType::new(vec![("id", Type::Int64), ("name", Type::String)]),
+                    vec![Value::Int64(self.0), Value::String(self.1.to_string())],
+                )))
+            }
+        }
+        impl SpannerType for Person {
+            fn spanner_type() -> Type {
+                Type::strct(vec![("id", Type::Int64), ("name", Type::String)])
+            }
+        }
+
+        let statement =
+            Statement::bulk_insert("person", vec![Person(1, "ferris"), Person(2, "corro")]);
+        assert_eq!(
+            statement.sql(),
+            "INSERT INTO person (id, name) SELECT * FROM UNNEST(@rows)"
+        );
+        let params = statement.values().unwrap();
+        assert_eq!(params.len(), 1);
+        assert_eq!(params[0].0, "rows");
+        assert_eq!(
+            params[0].1.spanner_type(),
+            Type::array(Person::spanner_type())
+        );
+
+        let statement = Statement::bulk_insert("person", vec![1, 2]);
+        match statement.values() {
+            Err(Error::Codec(msg)) => assert!(msg.contains("STRUCT")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_bind_typed() {
+        let statement = Statement::new("SELECT @a, @b, @c, @d")
+            .bind_typed("a", None::<i64>, Type::String)
+            .bind_typed("b", Vec::<i64>::new(), Type::array(Type::String))
+            .bind_typed("c", vec![1], Type::array(Type::Int64))
+            .bind_typed("d", 42, Type::Int64);
+        assert_eq!(
+            statement.params().collect::<Vec<_>>(),
+            vec![
+                ("a", &Value::Null(Type::String)),
+                ("b", &Value::Array(Type::String, vec![])),
+                ("c", &Value::Array(Type::Int64, vec![Value::Int64(1)])),
+                ("d", &Value::Int64(42)),
+            ]
+        );
+
+        let statement = Statement::new("SELECT @a").bind_typed("a", 42, Type::String);
+        match statement.values() {
+            Err(Error::Codec(msg)) => assert!(msg.contains("cannot be bound as STRING")),
+            other => panic!("unexpected result {:?}", other),
+        }
+    }
 }
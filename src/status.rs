@@ -0,0 +1,376 @@
+//! Decoding of the `google.rpc` error details attached to gRPC statuses.
+//!
+//! Cloud Spanner attaches a serialized `google.rpc.Status` to some errors (in the `grpc-status-details-bin` trailer)
+//! which may contain additional information about the error, e.g.: when to retry or which quota was exceeded.
+
+use std::time::Duration;
+
+use google_api_proto::google::rpc;
+use prost::Message;
+
+const RETRY_INFO: &str = "type.googleapis.com/google.rpc.RetryInfo";
+const BAD_REQUEST: &str = "type.googleapis.com/google.rpc.BadRequest";
+const PRECONDITION_FAILURE: &str = "type.googleapis.com/google.rpc.PreconditionFailure";
+const QUOTA_FAILURE: &str = "type.googleapis.com/google.rpc.QuotaFailure";
+const RESOURCE_INFO: &str = "type.googleapis.com/google.rpc.ResourceInfo";
+const SESSION_RESOURCE: &str = "type.googleapis.com/google.spanner.v1.Session";
+
+/// The metadata holding the id of a request, which is also attached to the status returned when it fails.
+pub(crate) const REQUEST_ID: &str = "x-goog-spanner-request-id";
+
+/// The details attached by Cloud Spanner to an error, see [`crate::Error::details`].
+///
+/// Details of the same kind are merged together, in order, and unknown details are ignored.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ErrorDetails {
+    /// The fields of the request that were invalid, from `google.rpc.BadRequest` details.
+    pub field_violations: Vec<FieldViolation>,
+    /// The preconditions that failed, from `google.rpc.PreconditionFailure` details.
+    pub precondition_violations: Vec<PreconditionViolation>,
+    /// The quotas that were exceeded, from `google.rpc.QuotaFailure` details.
+    pub quota_violations: Vec<QuotaViolation>,
+    /// The resource that the error relates to, from the first `google.rpc.ResourceInfo` detail.
+    pub resource_info: Option<ResourceInfo>,
+}
+
+/// A field of the request that was invalid.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FieldViolation {
+    /// The path to the field, e.g.: `mutations[0].insert.columns`.
+    pub field: String,
+    /// Why the field is invalid.
+    pub description: String,
+}
+
+/// A precondition that failed, e.g.: terms of service that were not accepted.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PreconditionViolation {
+    /// The type of the precondition, specific to the service.
+    pub kind: String,
+    /// The subject of the failure, relative to `kind`.
+    pub subject: String,
+    /// How the precondition failed.
+    pub description: String,
+}
+
+/// A quota that was exceeded.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct QuotaViolation {
+    /// The subject of the quota, e.g.: `project:my-project`.
+    pub subject: String,
+    /// How the quota was exceeded.
+    pub description: String,
+}
+
+/// The resource that an error relates to, e.g.: a missing session.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ResourceInfo {
+    /// The type of the resource, e.g.: `type.googleapis.com/google.spanner.v1.Session`.
+    pub resource_type: String,
+    /// The name of the resource.
+    pub resource_name: String,
+    /// The owner of the resource, if any.
+    pub owner: String,
+    /// A description of the error relating to the resource.
+    pub description: String,
+}
+
+impl ErrorDetails {
+    /// Decodes the details attached to the status, returns `None` when there are none.
+    pub(crate) fn decode(status: &tonic::Status) -> Option<Self> {
+        let status = rpc::Status::decode(status.details()).ok()?;
+        if status.details.is_empty() {
+            return None;
+        }
+        let mut details = ErrorDetails::default();
+        for any in status.details {
+            let value = any.value.as_slice();
+            match any.type_url.as_str() {
+                BAD_REQUEST => {
+                    let violations = rpc::BadRequest::decode(value)
+                        .map(|bad_request| bad_request.field_violations);
+                    details
+                        .field_violations
+                        .extend(
+                            violations
+                                .into_iter()
+                                .flatten()
+                                .map(|violation| FieldViolation {
+                                    field: violation.field,
+                                    description: violation.description,
+                                }),
+                        );
+                }
+                PRECONDITION_FAILURE => {
+                    let violations =
+                        rpc::PreconditionFailure::decode(value).map(|failure| failure.violations);
+                    details
+                        .precondition_violations
+                        .extend(violations.into_iter().flatten().map(|violation| {
+                            PreconditionViolation {
+                                kind: violation.r#type,
+                                subject: violation.subject,
+                                description: violation.description,
+                            }
+                        }));
+                }
+                QUOTA_FAILURE => {
+                    let violations =
+                        rpc::QuotaFailure::decode(value).map(|failure| failure.violations);
+                    details
+                        .quota_violations
+                        .extend(
+                            violations
+                                .into_iter()
+                                .flatten()
+                                .map(|violation| QuotaViolation {
+                                    subject: violation.subject,
+                                    description: violation.description,
+                                }),
+                        );
+                }
+                RESOURCE_INFO if details.resource_info.is_none() => {
+                    details.resource_info =
+                        rpc::ResourceInfo::decode(value)
+                            .ok()
+                            .map(|info| ResourceInfo {
+                                resource_type: info.resource_type,
+                                resource_name: info.resource_name,
+                                owner: info.owner,
+                                description: info.description,
+                            });
+                }
+                _ => (),
+            }
+        }
+        Some(details)
+    }
+}
+
+/// Decodes the first detail of the specified type, if any.
+fn detail<M>(status: &tonic::Status, type_url: &str) -> Option<M>
+where
+    M: Message + Default,
+{
+    rpc::Status::decode(status.details())
+        .ok()?
+        .details
+        .into_iter()
+        .find(|any| any.type_url == type_url)
+        .and_then(|any| M::decode(any.value.as_slice()).ok())
+}
+
+/// Returns the id of the failed request, if any.
+pub(crate) fn request_id(status: &tonic::Status) -> Option<&str> {
+    status.metadata().get(REQUEST_ID)?.to_str().ok()
+}
+
+/// Returns the delay recommended by the server before retrying, if any.
+pub(crate) fn retry_delay(status: &tonic::Status) -> Option<Duration> {
+    detail::<rpc::RetryInfo>(status, RETRY_INFO)?
+        .retry_delay
+        .and_then(|delay| Duration::try_from(delay).ok())
+}
+
+/// Returns a description of the quota(s) that were exceeded, if any.
+pub(crate) fn quota_failure(status: &tonic::Status) -> Option<String> {
+    let violations = detail::<rpc::QuotaFailure>(status, QUOTA_FAILURE)?
+        .violations
+        .into_iter()
+        .map(|violation| {
+            if violation.description.is_empty() {
+                violation.subject
+            } else {
+                format!("{}: {}", violation.subject, violation.description)
+            }
+        })
+        .collect::<Vec<String>>();
+
+    if violations.is_empty() {
+        None
+    } else {
+        Some(violations.join(", "))
+    }
+}
+
+/// Returns true when the status reports that a session does not exist (anymore).
+///
+/// Cloud Spanner identifies the missing session using a `ResourceInfo` detail, the message is used as a fallback.
+pub(crate) fn is_session_not_found(status: &tonic::Status) -> bool {
+    if status.code() != tonic::Code::NotFound {
+        return false;
+    }
+    match detail::<rpc::ResourceInfo>(status, RESOURCE_INFO) {
+        Some(info) => info.resource_type == SESSION_RESOURCE,
+        None => status.message().starts_with("Session not found"),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::*;
+    use prost::bytes::Bytes;
+    use tonic::Code;
+
+    pub(crate) fn status_with_details(code: Code, details: Vec<(&str, Vec<u8>)>) -> tonic::Status {
+        let status = rpc::Status {
+            code: code as i32,
+            message: "message".to_string(),
+            details: details
+                .into_iter()
+                .map(|(type_url, value)| prost_types::Any {
+                    type_url: type_url.to_string(),
+                    value,
+                })
+                .collect(),
+        };
+        tonic::Status::with_details(code, "message", Bytes::from(status.encode_to_vec()))
+    }
+
+    pub(crate) fn retry_info(delay: Duration) -> (&'static str, Vec<u8>) {
+        let retry_info = rpc::RetryInfo {
+            retry_delay: Some(delay.try_into().unwrap()),
+        };
+        (RETRY_INFO, retry_info.encode_to_vec())
+    }
+
+    pub(crate) fn quota_failure_detail(
+        subject: &str,
+        description: &str,
+    ) -> (&'static str, Vec<u8>) {
+        let quota_failure = rpc::QuotaFailure {
+            violations: vec![rpc::quota_failure::Violation {
+                subject: subject.to_string(),
+                description: description.to_string(),
+            }],
+        };
+        (QUOTA_FAILURE, quota_failure.encode_to_vec())
+    }
+
+    pub(crate) fn bad_request(field: &str, description: &str) -> (&'static str, Vec<u8>) {
+        let bad_request = rpc::BadRequest {
+            field_violations: vec![rpc::bad_request::FieldViolation {
+                field: field.to_string(),
+                description: description.to_string(),
+                ..Default::default()
+            }],
+        };
+        (BAD_REQUEST, bad_request.encode_to_vec())
+    }
+
+    pub(crate) fn precondition_failure(
+        kind: &str,
+        subject: &str,
+        description: &str,
+    ) -> (&'static str, Vec<u8>) {
+        let precondition_failure = rpc::PreconditionFailure {
+            violations: vec![rpc::precondition_failure::Violation {
+                r#type: kind.to_string(),
+                subject: subject.to_string(),
+                description: description.to_string(),
+            }],
+        };
+        (PRECONDITION_FAILURE, precondition_failure.encode_to_vec())
+    }
+
+    pub(crate) fn session_resource_info(name: &str) -> (&'static str, Vec<u8>) {
+        let resource_info = rpc::ResourceInfo {
+            resource_type: SESSION_RESOURCE.to_string(),
+            resource_name: name.to_string(),
+            owner: String::new(),
+            description: String::new(),
+        };
+        (RESOURCE_INFO, resource_info.encode_to_vec())
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        let status =
+            status_with_details(Code::Aborted, vec![retry_info(Duration::from_millis(1500))]);
+        assert_eq!(retry_delay(&status), Some(Duration::from_millis(1500)));
+        assert_eq!(quota_failure(&status), None);
+
+        assert_eq!(retry_delay(&tonic::Status::aborted("aborted")), None);
+    }
+
+    #[test]
+    fn test_quota_failure() {
+        let status = status_with_details(
+            Code::ResourceExhausted,
+            vec![
+                quota_failure_detail("project:my-project", "too many requests"),
+                retry_info(Duration::from_secs(2)),
+            ],
+        );
+        assert_eq!(
+            quota_failure(&status),
+            Some("project:my-project: too many requests".to_string())
+        );
+        assert_eq!(retry_delay(&status), Some(Duration::from_secs(2)));
+        assert_eq!(
+            quota_failure(&tonic::Status::resource_exhausted("exhausted")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_error_details() {
+        let status = status_with_details(
+            Code::InvalidArgument,
+            vec![
+                bad_request("mutations[0].insert.columns", "unknown column"),
+                precondition_failure("TOS", "project:my-project", "terms not accepted"),
+                quota_failure_detail("project:my-project", "too many requests"),
+                session_resource_info("sessions/s"),
+                retry_info(Duration::from_secs(1)),
+            ],
+        );
+        assert_eq!(
+            ErrorDetails::decode(&status),
+            Some(ErrorDetails {
+                field_violations: vec![FieldViolation {
+                    field: "mutations[0].insert.columns".to_string(),
+                    description: "unknown column".to_string(),
+                }],
+                precondition_violations: vec![PreconditionViolation {
+                    kind: "TOS".to_string(),
+                    subject: "project:my-project".to_string(),
+                    description: "terms not accepted".to_string(),
+                }],
+                quota_violations: vec![QuotaViolation {
+                    subject: "project:my-project".to_string(),
+                    description: "too many requests".to_string(),
+                }],
+                resource_info: Some(ResourceInfo {
+                    resource_type: SESSION_RESOURCE.to_string(),
+                    resource_name: "sessions/s".to_string(),
+                    ..Default::default()
+                }),
+            })
+        );
+        assert_eq!(
+            ErrorDetails::decode(&tonic::Status::invalid_argument("invalid")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_session_not_found() {
+        let status = status_with_details(
+            Code::NotFound,
+            vec![session_resource_info(
+                "projects/p/instances/i/databases/d/sessions/s",
+            )],
+        );
+        assert!(is_session_not_found(&status));
+        assert!(is_session_not_found(&tonic::Status::not_found(
+            "Session not found: projects/p/instances/i/databases/d/sessions/s"
+        )));
+        assert!(!is_session_not_found(&tonic::Status::not_found(
+            "Table not found: person"
+        )));
+        assert!(!is_session_not_found(&tonic::Status::aborted(
+            "Session not found"
+        )));
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{Error, KeySet, Mutation, Value};
+#[cfg(doc)]
+use crate::{ToSpanner, TransactionContext};
+
+/// A trait for Rust types that map to a row of a table, e.g.: to write them using [`TransactionContext::insert`]
+/// or [`Mutation::set_row`].
+///
+/// # Derive
+///
+/// When the `derive` crate feature is enabled, `ToRow` can be derived for structs with named fields.
+/// Each field is a column named after the field, unless renamed using `#[spanner(rename = "column")]`,
+/// and its value is converted using [`ToSpanner`].
+///
+/// ```ignore
+/// use spanner_rs::ToRow;
+///
+/// #[derive(ToRow)]
+/// struct Person {
+///     id: i64,
+///     #[spanner(rename = "full_name")]
+///     name: String,
+///     email: Option<String>,
+/// }
+/// ```
+///
+/// # Example
+///
+/// `ToRow` can also be implemented manually, which is equivalent to the derived implementation above.
+///
+/// ```
+/// use spanner_rs::{Error, ToRow, ToSpanner, Value};
+///
+/// struct Person {
+///     id: i64,
+///     name: String,
+///     email: Option<String>,
+/// }
+///
+/// impl ToRow for Person {
+///     fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error> {
+///         Ok(vec![
+///             ("id", self.id.to_spanner()?),
+///             ("full_name", self.name.to_spanner()?),
+///             ("email", self.email.to_spanner()?),
+///         ])
+///     }
+/// }
+/// ```
+pub trait ToRow {
+    /// Returns the columns of the row along with their value.
+    fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error>;
+}
+
+/// A [`ToRow`] that identifies its table and primary key, which provides the mutations to write or delete it.
+///
+/// # Derive
+///
+/// When the `derive` crate feature is enabled, `TableRow` can be derived along with [`ToRow`].
+/// The table is named using `#[spanner(table = "name")]`, or after the struct when unspecified,
+/// and the primary key columns are marked using `#[spanner(pk)]`, in the order of the table's primary key.
+///
+/// ```ignore
+/// use spanner_rs::{TableRow, ToRow};
+///
+/// #[derive(ToRow, TableRow)]
+/// #[spanner(table = "person")]
+/// struct Person {
+///     #[spanner(pk)]
+///     id: i64,
+///     name: String,
+/// }
+/// ```
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{Error, TableRow, ToRow, ToSpanner, Value};
+///
+/// struct Person {
+///     id: i64,
+///     name: String,
+/// }
+///
+/// impl ToRow for Person {
+///     fn to_row(&self) -> Result<Vec<(&'static str, Value)>, Error> {
+///         Ok(vec![("id", self.id.to_spanner()?), ("name", self.name.to_spanner()?)])
+///     }
+/// }
+///
+/// impl TableRow for Person {
+///     const TABLE: &'static str = "person";
+///
+///     fn key(&self) -> Result<Vec<Value>, Error> {
+///         Ok(vec![self.id.to_spanner()?])
+///     }
+/// }
+///
+/// let person = Person { id: 42, name: "ferris".to_string() };
+/// let mutations = vec![person.insert_or_update(), person.delete_by_key()];
+/// ```
+pub trait TableRow: ToRow + Sized {
+    /// The name of the table the row belongs to.
+    const TABLE: &'static str;
+
+    /// The names of the table's primary key columns, in the order of the table's primary key.
+    ///
+    /// These are used to check the size of the keys written and deleted, see [`Mutation::with_key_columns`].
+    /// Defaults to none, which skips the check.
+    const KEY_COLUMNS: &'static [&'static str] = &[];
+
+    /// Returns the values of the row's primary key columns, in the order of the table's primary key.
+    fn key(&self) -> Result<Vec<Value>, Error>;
+
+    /// Returns a mutation that inserts this row, or updates it when a row with the same primary key exists.
+    fn insert_or_update(&self) -> Mutation {
+        Mutation::insert_or_update(Self::TABLE)
+            .set_row(self)
+            .with_key_columns(Self::KEY_COLUMNS)
+    }
+
+    /// Returns a mutation that deletes the row with the same primary key as this one, if any.
+    fn delete_by_key(&self) -> Mutation {
+        Mutation::delete(Self::TABLE, KeySet::new().row(self)).with_key_columns(Self::KEY_COLUMNS)
+    }
+}
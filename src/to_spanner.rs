@@ -2,7 +2,7 @@
 use bigdecimal::BigDecimal;
 use prost::bytes::Bytes;
 
-use crate::{Error, Type, Value};
+use crate::{Error, Struct, StructType, Type, Value};
 
 /// A trait for Rust types that can be converted to Cloud Spanner values.
 ///
@@ -26,71 +26,140 @@ use crate::{Error, Type, Value};
 /// | `numeric` | `bigdecimal::BigDecimal` | [`NUMERIC`](https://cloud.google.com/spanner/docs/data-types#numeric_type) |
 /// | `temporal` | `chrono::DateTime<Utc>` | [`TIMESTAMP`](https://cloud.google.com/spanner/docs/data-types#timestamp_type) |
 /// | `temporal` | `chrono::NaiveDate` | [`DATE`](https://cloud.google.com/spanner/docs/data-types#date_type) |
+/// | `uuid` | `uuid::Uuid` | [`STRING`](https://cloud.google.com/spanner/docs/data-types#string_type) |
+///
+/// All of these also implement [`SpannerType`], since their Cloud Spanner type does not depend on their value.
 ///
 /// # Nullability
 ///
-/// `ToSpanner` is implemented for `Option<T>` when `T` implements `ToSpanner`.
-/// `Option<T>` represents a nullable Spanner value.
+/// `ToSpanner` is implemented for `Option<T>` when `T` implements `ToSpanner` and [`SpannerType`].
+/// `Option<T>` represents a nullable Spanner value. A `NULL` whose type is only known at runtime can be bound using [`Null`].
 ///
 /// # Arrays
 ///
-/// `ToSpanner` is implemented for `Vec<T>` when `T` implements `ToSpanner`.
+/// `ToSpanner` is implemented for `Vec<T>` when `T` implements `ToSpanner` and [`SpannerType`].
 /// Such values map to Spanner's [`Array`](https://cloud.google.com/spanner/docs/data-types#array_type) type.
 /// Arrays may contain `null` values (i.e.: `Vec<Option<T>>`). Note that `Vec<Vec<T>>` is not allowed.
+///
+/// # Tuples
+///
+/// `ToSpanner` is implemented for tuples of up to 12 elements that implement `ToSpanner`, and `SpannerType` for those whose elements do.
+/// Such values map to Spanner's [`Struct`](https://cloud.google.com/spanner/docs/data-types#struct_type) type with unnamed fields,
+/// which allows filtering on several columns at once, e.g.: binding a `Vec<(i64, String)>` to `@keys` in
+/// `WHERE STRUCT<id INT64, name STRING>(id, name) IN UNNEST(@keys)`.
+///
+/// # UUIDs
+///
+/// `uuid::Uuid` values are encoded using their hyphenated form, e.g.: `"67e55044-10b1-426f-9247-bb680e5fe0c8"`,
+/// which is meant to be stored in a `STRING(36)` column.
+/// Applications storing UUIDs in a `BYTES(16)` column should bind `Bytes::copy_from_slice(uuid.as_bytes())` instead.
+///
+/// # Custom types
+///
+/// Applications can implement this trait, [`SpannerType`] (and [`crate::FromSpanner`]) for their own types, which makes them usable
+/// anywhere a parameter is bound as well as in `Option<T>` and `Vec<T>`. This is the way to apply cross-cutting encodings,
+/// e.g.: storing a value in a different representation than the one used by the application.
+///
+/// Due to Rust's orphan rules, a newtype is only required for types defined in other crates.
+///
+/// ```
+/// use spanner_rs::{Error, SpannerType, ToSpanner, Type, Value};
+///
+/// /// A secret that is never stored in plain text.
+/// struct Secret(String);
+///
+/// impl ToSpanner for Secret {
+///     fn to_spanner(&self) -> Result<Value, Error> {
+///         // a real implementation would use a proper encryption scheme
+///         Ok(Value::String(self.0.chars().rev().collect()))
+///     }
+/// }
+///
+/// impl SpannerType for Secret {
+///     fn spanner_type() -> Type {
+///         Type::String
+///     }
+/// }
+///
+/// assert_eq!(Secret("ferris".to_string()).to_spanner()?, Value::String("sirref".to_string()));
+/// # Ok::<(), Error>(())
+/// ```
 pub trait ToSpanner {
     /// Creates a new Cloud Spanner value from this value.
     fn to_spanner(&self) -> Result<Value, Error>;
+}
 
-    /// Returns the Cloud Spanner [Type] that this implementation produces.
-    fn spanner_type() -> Type
-    where
-        Self: Sized;
+/// A trait for Rust types whose Cloud Spanner type is known statically, regardless of their value.
+///
+/// This is required of `T` to convert `Option<T>` and `Vec<T>`, since a `NULL` or an empty array still has a type.
+/// It is implemented for all the types listed in [`ToSpanner`], but not for [`Null`] whose type is only known at runtime.
+pub trait SpannerType {
+    /// Returns the Cloud Spanner [Type] of the values of this type.
+    fn spanner_type() -> Type;
 }
 
 impl<T> ToSpanner for Option<T>
 where
-    T: ToSpanner,
+    T: ToSpanner + SpannerType,
 {
     fn to_spanner(&self) -> Result<Value, Error> {
         match self.as_ref() {
             Some(v) => v.to_spanner(),
-            None => Ok(Value::Null(<T as ToSpanner>::spanner_type())),
+            None => Ok(Value::Null(T::spanner_type())),
         }
     }
+}
+
+impl<T> SpannerType for Option<T>
+where
+    T: SpannerType,
+{
     fn spanner_type() -> Type {
-        <T as ToSpanner>::spanner_type()
+        T::spanner_type()
     }
 }
 
 impl<T> ToSpanner for Vec<T>
 where
-    T: ToSpanner,
+    T: ToSpanner + SpannerType,
 {
     fn to_spanner(&self) -> Result<Value, Error> {
         let values = self
             .iter()
             .map(|v| v.to_spanner())
             .collect::<Result<Vec<Value>, Error>>()?;
-        Ok(Value::Array(<T as ToSpanner>::spanner_type(), values))
+        Ok(Value::Array(T::spanner_type(), values))
     }
+}
+
+impl<T> SpannerType for Vec<T>
+where
+    T: SpannerType,
+{
     fn spanner_type() -> Type {
-        Type::Array(Box::new(<T as ToSpanner>::spanner_type()))
+        Type::Array(Box::new(T::spanner_type()))
     }
 }
 
 impl<T> ToSpanner for &[T]
 where
-    T: ToSpanner,
+    T: ToSpanner + SpannerType,
 {
     fn to_spanner(&self) -> Result<Value, Error> {
         let values = self
             .iter()
             .map(|v| v.to_spanner())
             .collect::<Result<Vec<Value>, Error>>()?;
-        Ok(Value::Array(<T as ToSpanner>::spanner_type(), values))
+        Ok(Value::Array(T::spanner_type(), values))
     }
+}
+
+impl<T> SpannerType for &[T]
+where
+    T: SpannerType,
+{
     fn spanner_type() -> Type {
-        Type::Array(Box::new(<T as ToSpanner>::spanner_type()))
+        Type::Array(Box::new(T::spanner_type()))
     }
 }
 
@@ -100,7 +169,9 @@ macro_rules! simple {
             fn to_spanner(&self) -> Result<Value, Error> {
                 Ok(Value::$v($into($($deref)? self)))
             }
+        }
 
+        impl SpannerType for $t {
             fn spanner_type() -> Type {
                 Type::$v
             }
@@ -126,11 +197,97 @@ simple!(serde_json::Value, Json, Clone::clone);
 simple!(chrono::DateTime<chrono::Utc>, Timestamp, Clone::clone);
 #[cfg(feature = "temporal")]
 simple!(chrono::NaiveDate, Date, Clone::clone);
+#[cfg(feature = "uuid")]
+simple!(uuid::Uuid, String, ToString::to_string);
+
+macro_rules! tuple {
+    ($($t:ident: $i:tt),+) => {
+        impl<$($t),+> ToSpanner for ($($t,)+)
+        where
+            $($t: ToSpanner),+
+        {
+            fn to_spanner(&self) -> Result<Value, Error> {
+                let values = vec![$(self.$i.to_spanner()?),+];
+                // the type is derived from the values, since some (e.g.: a `Null`) only know their type at runtime
+                let struct_type =
+                    StructType::new(values.iter().map(|value| ("", value.spanner_type())).collect());
+                Ok(Value::Struct(Struct::new(struct_type, values)))
+            }
+        }
+
+        impl<$($t),+> SpannerType for ($($t,)+)
+        where
+            $($t: SpannerType),+
+        {
+            fn spanner_type() -> Type {
+                Type::Struct(StructType::new(vec![$(("", $t::spanner_type())),+]))
+            }
+        }
+    };
+}
+
+tuple!(A: 0);
+tuple!(A: 0, B: 1);
+tuple!(A: 0, B: 1, C: 2);
+tuple!(A: 0, B: 1, C: 2, D: 3);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10);
+tuple!(A: 0, B: 1, C: 2, D: 3, E: 4, F: 5, G: 6, H: 7, I: 8, J: 9, K: 10, L: 11);
+
+/// A `NULL` parameter of the provided type.
+///
+/// This avoids spelling out the Rust type of a `NULL` parameter, e.g.: `None::<Vec<String>>`,
+/// which is especially useful for arrays and structs. Since its type is only known at runtime,
+/// it does not implement [`SpannerType`] and cannot be used in `Option<T>` or `Vec<T>`.
+///
+/// # Example
+///
+/// ```no_run
+/// # use spanner_rs::{Client, Error, Null, TransactionContext, Type};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Error> {
+/// # let client = Client::configure().connect().await?;
+/// client
+///     .read_write()
+///     .run(|tx| {
+///         tx.execute_update(
+///             "UPDATE person SET tags = @tags WHERE id = 42",
+///             &[("tags", &Null(Type::array(Type::String)))],
+///         )
+///     })
+///     .await?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Null(pub Type);
+
+impl ToSpanner for Null {
+    fn to_spanner(&self) -> Result<Value, Error> {
+        Ok(Value::Null(self.0.clone()))
+    }
+}
 
 #[cfg(test)]
 mod test {
     use super::*;
 
+    #[test]
+    fn test_to_spanner_null() {
+        let tpe = Type::array(Type::String);
+        assert_eq!(Null(tpe.clone()).to_spanner().ok(), Some(Value::Null(tpe)));
+
+        let key = (42, Null(Type::String)).to_spanner().unwrap();
+        assert_eq!(
+            key.spanner_type(),
+            Type::strct(vec![("", Type::Int64), ("", Type::String)])
+        );
+    }
+
     macro_rules! simple_test_int64 {
         ($t:ty) => {
             assert_eq!((0 as $t).to_spanner().ok(), Some(Value::Int64(0)));
@@ -147,6 +304,32 @@ mod test {
         simple_test_int64!(i8, u8, i16, u16, i32, u32, i64);
     }
 
+    #[test]
+    fn test_to_spanner_tuple() {
+        let pair_type = StructType::new(vec![("", Type::Int64), ("", Type::String)]);
+        assert_eq!(
+            <(i64, String)>::spanner_type(),
+            Type::Struct(pair_type.clone())
+        );
+        assert_eq!(
+            (42, "ferris").to_spanner().ok(),
+            Some(Value::Struct(Struct::new(
+                pair_type.clone(),
+                vec![Value::Int64(42), Value::String("ferris".to_string())]
+            )))
+        );
+        assert_eq!(
+            vec![(1_i64, "ferris".to_string())].to_spanner().ok(),
+            Some(Value::Array(
+                Type::Struct(pair_type),
+                vec![Value::Struct(Struct::new(
+                    StructType::new(vec![("", Type::Int64), ("", Type::String)]),
+                    vec![Value::Int64(1), Value::String("ferris".to_string())]
+                ))]
+            ))
+        );
+    }
+
     #[test]
     fn test_to_spanner_opt() {
         let some = Some(0 as u32);
@@ -177,4 +360,17 @@ mod test {
             Some(Value::Array(Type::Int64, vec![]))
         );
     }
+
+    #[cfg(feature = "uuid")]
+    #[test]
+    fn test_to_spanner_uuid() {
+        let uuid = uuid::Uuid::parse_str("67e55044-10b1-426f-9247-bb680e5fe0c8").unwrap();
+        assert_eq!(
+            uuid.to_spanner().ok(),
+            Some(Value::String(
+                "67e55044-10b1-426f-9247-bb680e5fe0c8".to_string()
+            ))
+        );
+        assert_eq!(<uuid::Uuid as SpannerType>::spanner_type(), Type::String);
+    }
 }
@@ -1,6 +1,9 @@
 use std::time::Duration;
 use std::time::SystemTime;
 
+#[cfg(doc)]
+use crate::TxRunner;
+use derive_builder::Builder;
 use google_api_proto::google::spanner::v1 as proto;
 
 /// Specifies the bounds withing wich to make reads in Spanner.
@@ -26,6 +29,8 @@ pub enum TimestampBound {
     ///
     /// This is useful for requesting fresher data than some previous read,
     /// or data that is fresh enough to observe the effects of some previously committed transaction whose timestamp is known.
+    ///
+    /// This bound is only valid for single-use reads, see [`TimestampBound::is_single_use_only`].
     MinReadTimestamp(SystemTime),
 
     /// Executes all reads at a timestamp that is `ExactStaleness` old. The timestamp is chosen soon after the read is started.
@@ -36,9 +41,36 @@ pub enum TimestampBound {
     /// Read data at a timestamp `>= now() - MaxStaleness` seconds.
     ///
     /// Guarantees that all writes that have committed more than the specified number of seconds ago are visible.
+    ///
+    /// This bound is only valid for single-use reads, see [`TimestampBound::is_single_use_only`].
     MaxStaleness(Duration),
 }
 
+impl TimestampBound {
+    /// Returns true when this bound can only be used for single-use reads, i.e.: [`TimestampBound::MinReadTimestamp`] and [`TimestampBound::MaxStaleness`].
+    ///
+    /// These bounds let Cloud Spanner pick the read timestamp based on the data being read, which is not possible
+    /// for transactions that span several reads, such as the ones used by [`crate::Client::batch_read_only`].
+    pub fn is_single_use_only(&self) -> bool {
+        matches!(
+            self,
+            TimestampBound::MinReadTimestamp(_) | TimestampBound::MaxStaleness(_)
+        )
+    }
+
+    // Rejects the bounds that Cloud Spanner would reject when beginning a multi-use read-only transaction.
+    pub(crate) fn multi_use(self) -> Result<Self, super::Error> {
+        if self.is_single_use_only() {
+            Err(super::Error::Client(format!(
+                "{:?} can only be used for single-use reads, use one of Strong, ReadTimestamp or ExactStaleness instead",
+                self
+            )))
+        } else {
+            Ok(self)
+        }
+    }
+}
+
 impl TryFrom<TimestampBound> for proto::transaction_options::read_only::TimestampBound {
     type Error = super::Error;
 
@@ -75,6 +107,93 @@ impl TryFrom<TimestampBound> for proto::transaction_options::read_only::Timestam
     }
 }
 
+/// The priority of requests relative to other requests executed by Cloud Spanner.
+///
+/// See [the Spanner Documentation](https://cloud.google.com/spanner/docs/reference/rpc/google.spanner.v1#google.spanner.v1.RequestOptions.Priority)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Priority {
+    /// For requests that can be delayed, e.g.: batch jobs.
+    Low,
+    /// For requests that are not latency sensitive.
+    Medium,
+    /// The default priority of requests.
+    High,
+}
+
+impl From<Priority> for proto::request_options::Priority {
+    fn from(priority: Priority) -> Self {
+        match priority {
+            Priority::Low => proto::request_options::Priority::Low,
+            Priority::Medium => proto::request_options::Priority::Medium,
+            Priority::High => proto::request_options::Priority::High,
+        }
+    }
+}
+
+/// Settings that apply to every request of a read/write transaction, see [`TxRunner::run_with_options`].
+///
+/// # Example
+///
+/// ```
+/// use spanner_rs::{Priority, TxOptions};
+///
+/// # fn main() -> Result<(), spanner_rs::Error> {
+/// let options = TxOptions::builder()
+///     .tag("bump-version")
+///     .priority(Priority::Low)
+///     .build()?;
+/// # Ok(()) }
+/// ```
+#[derive(Builder, Clone, Debug, Default, PartialEq)]
+#[builder(pattern = "owned", build_fn(error = "crate::Error"))]
+pub struct TxOptions {
+    /// Specify a tag used to group the transaction's statistics, e.g.: in the `SPANNER_SYS` lock and transaction statistics tables.
+    #[builder(setter(into, strip_option), default)]
+    tag: Option<String>,
+
+    /// Specify the priority of the transaction's requests. Leave unspecified to use Cloud Spanner's default (high) priority.
+    #[builder(setter(strip_option), default)]
+    priority: Option<Priority>,
+
+    /// Specify whether Cloud Spanner should compute statistics about the transaction's commit, e.g.: the number of mutations.
+    #[builder(default)]
+    return_commit_stats: bool,
+
+    /// Specify the deadline of the request committing the transaction, overriding the client's [`crate::RpcTimeouts::commit`].
+    #[builder(setter(strip_option), default)]
+    commit_deadline: Option<Duration>,
+}
+
+impl TxOptions {
+    /// Returns a new [`TxOptionsBuilder`] for configuring a transaction's settings.
+    pub fn builder() -> TxOptionsBuilder {
+        TxOptionsBuilder::default()
+    }
+
+    pub(crate) fn priority(&self) -> Option<Priority> {
+        self.priority
+    }
+
+    pub(crate) fn return_commit_stats(&self) -> bool {
+        self.return_commit_stats
+    }
+
+    pub(crate) fn commit_deadline(&self) -> Option<Duration> {
+        self.commit_deadline
+    }
+
+    pub(crate) fn request_options(&self) -> proto::RequestOptions {
+        proto::RequestOptions {
+            priority: self
+                .priority
+                .map_or(proto::request_options::Priority::Unspecified, Into::into)
+                .into(),
+            request_tag: String::new(),
+            transaction_tag: self.tag.clone().unwrap_or_default(),
+        }
+    }
+}
+
 #[derive(Clone, Debug)]
 pub(crate) enum TransactionSelector {
     SingleUse(Option<TimestampBound>),
@@ -91,7 +210,8 @@ impl TryFrom<TransactionSelector> for proto::TransactionSelector {
                     proto::TransactionOptions {
                         mode: Some(proto::transaction_options::Mode::ReadOnly(
                             proto::transaction_options::ReadOnly {
-                                return_read_timestamp: false,
+                                // the read timestamp tells how stale the results are, see `QueryCache`
+                                return_read_timestamp: bound.is_some(),
                                 timestamp_bound: match bound {
                                     Some(bound) => Some(bound.try_into()?),
                                     None => None,
@@ -119,6 +239,61 @@ impl TryFrom<TransactionSelector> for proto::TransactionSelector {
     }
 }
 
+/// The result of committing a read/write transaction, see [`crate::TxOutcome::commit_response`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitResponse {
+    commit_timestamp: SystemTime,
+    commit_stats: Option<CommitStats>,
+}
+
+impl CommitResponse {
+    /// Returns the timestamp at which the transaction committed.
+    pub fn commit_timestamp(&self) -> SystemTime {
+        self.commit_timestamp
+    }
+
+    /// Returns the statistics about the commit, only available when requested using [`TxOptionsBuilder::return_commit_stats`].
+    pub fn commit_stats(&self) -> Option<&CommitStats> {
+        self.commit_stats.as_ref()
+    }
+}
+
+impl TryFrom<proto::CommitResponse> for CommitResponse {
+    type Error = super::Error;
+
+    fn try_from(response: proto::CommitResponse) -> Result<Self, Self::Error> {
+        let commit_timestamp = response
+            .commit_timestamp
+            .ok_or_else(|| super::Error::Codec("missing commit timestamp".to_string()))
+            .and_then(|timestamp| {
+                SystemTime::try_from(timestamp)
+                    .map_err(|_| super::Error::Codec("invalid commit timestamp".to_string()))
+            })?;
+        Ok(Self {
+            commit_timestamp,
+            commit_stats: response.commit_stats.map(|stats| CommitStats {
+                mutation_count: stats.mutation_count,
+            }),
+        })
+    }
+}
+
+/// Statistics about a transaction's commit, see [`CommitResponse::commit_stats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitStats {
+    mutation_count: i64,
+}
+
+impl CommitStats {
+    /// Returns the number of mutations applied by the transaction, which counts every column of every row written
+    /// by DML and mutations, as well as index entries.
+    ///
+    /// See [the Spanner Documentation](https://cloud.google.com/spanner/docs/commit-statistics) for how this is computed.
+    pub fn mutation_count(&self) -> i64 {
+        self.mutation_count
+    }
+}
+
 #[derive(Debug, Clone)]
 pub(crate) struct Transaction {
     spanner_tx: proto::Transaction,
@@ -128,6 +303,14 @@ impl Transaction {
     pub(crate) fn id(&self) -> &prost::bytes::Bytes {
         &self.spanner_tx.id
     }
+
+    /// Returns the timestamp at which a read-only transaction reads, when it was requested.
+    pub(crate) fn read_timestamp(&self) -> Option<SystemTime> {
+        self.spanner_tx
+            .read_timestamp
+            .clone()
+            .and_then(|timestamp| SystemTime::try_from(timestamp).ok())
+    }
 }
 
 impl From<proto::Transaction> for Transaction {
@@ -141,3 +324,52 @@ impl From<Transaction> for proto::Transaction {
         tx.spanner_tx
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_timestamp_bound_multi_use() {
+        assert!(TimestampBound::Strong.multi_use().is_ok());
+        assert!(TimestampBound::ReadTimestamp(SystemTime::now())
+            .multi_use()
+            .is_ok());
+        assert!(TimestampBound::ExactStaleness(Duration::from_secs(10))
+            .multi_use()
+            .is_ok());
+        assert!(matches!(
+            TimestampBound::MaxStaleness(Duration::from_secs(10)).multi_use(),
+            Err(crate::Error::Client(_))
+        ));
+        assert!(matches!(
+            TimestampBound::MinReadTimestamp(SystemTime::now()).multi_use(),
+            Err(crate::Error::Client(_))
+        ));
+    }
+
+    #[test]
+    fn test_tx_options_request_options() {
+        let options = TxOptions::default().request_options();
+        assert_eq!(
+            options.priority,
+            proto::request_options::Priority::Unspecified as i32
+        );
+        assert!(options.transaction_tag.is_empty());
+
+        let options = TxOptions::builder()
+            .tag("cleanup")
+            .priority(Priority::Low)
+            .return_commit_stats(true)
+            .build()
+            .unwrap();
+        assert!(options.return_commit_stats());
+        let request_options = options.request_options();
+        assert_eq!(
+            request_options.priority,
+            proto::request_options::Priority::Low as i32
+        );
+        assert_eq!(request_options.transaction_tag, "cleanup");
+        assert!(request_options.request_tag.is_empty());
+    }
+}
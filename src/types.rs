@@ -203,6 +203,65 @@ impl Type {
     }
 }
 
+impl Type {
+    // Writes the name of this type, optionally as expected in DDL statements where `STRING` and `BYTES` require a length.
+    fn fmt_name(&self, f: &mut std::fmt::Formatter<'_>, ddl: bool) -> std::fmt::Result {
+        match self {
+            Type::Bool => write!(f, "BOOL"),
+            Type::Int64 => write!(f, "INT64"),
+            Type::Float64 => write!(f, "FLOAT64"),
+            Type::String if ddl => write!(f, "STRING(MAX)"),
+            Type::String => write!(f, "STRING"),
+            Type::Bytes if ddl => write!(f, "BYTES(MAX)"),
+            Type::Bytes => write!(f, "BYTES"),
+            #[cfg(feature = "json")]
+            Type::Json => write!(f, "JSON"),
+            #[cfg(feature = "numeric")]
+            Type::Numeric => write!(f, "NUMERIC"),
+            #[cfg(feature = "temporal")]
+            Type::Timestamp => write!(f, "TIMESTAMP"),
+            #[cfg(feature = "temporal")]
+            Type::Date => write!(f, "DATE"),
+            Type::Array(inner) => {
+                write!(f, "ARRAY<")?;
+                inner.fmt_name(f, ddl)?;
+                write!(f, ">")
+            }
+            Type::Struct(StructType(fields)) => {
+                write!(f, "STRUCT<")?;
+                for (idx, (name, tpe)) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    if let Some(name) = name {
+                        write!(f, "{} ", name)?;
+                    }
+                    tpe.fmt_name(f, ddl)?;
+                }
+                write!(f, ">")
+            }
+        }
+    }
+
+    /// Returns the name of this type as used in SQL expressions, e.g.: `CAST(NULL AS STRING)`.
+    pub(crate) fn sql_name(&self) -> String {
+        struct SqlName<'a>(&'a Type);
+        impl<'a> std::fmt::Display for SqlName<'a> {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                self.0.fmt_name(f, false)
+            }
+        }
+        SqlName(self).to_string()
+    }
+}
+
+/// Formats the type using its GoogleSQL DDL name, e.g.: `INT64`, `STRING(MAX)` or `ARRAY<BYTES(MAX)>`.
+impl std::fmt::Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.fmt_name(f, true)
+    }
+}
+
 impl TryFrom<proto::Type> for Type {
     type Error = crate::Error;
 
@@ -493,6 +552,45 @@ mod test {
         assert!(Type::try_from(invalid).is_err());
     }
 
+    #[test]
+    fn test_try_from_pg_annotations() {
+        // PG.NUMERIC, PG.JSONB and PG.OID are annotated versions of other type codes
+        fn annotated(code: proto::TypeCode, annotation: i32) -> proto::Type {
+            proto::Type {
+                type_annotation: annotation,
+                ..scalar_type(code)
+            }
+        }
+
+        #[cfg(feature = "numeric")]
+        assert_eq!(
+            Type::try_from(annotated(proto::TypeCode::Numeric, 2)).unwrap(),
+            Type::Numeric
+        );
+        #[cfg(feature = "json")]
+        assert_eq!(
+            Type::try_from(annotated(proto::TypeCode::Json, 3)).unwrap(),
+            Type::Json
+        );
+        assert_eq!(
+            Type::try_from(annotated(proto::TypeCode::Int64, 4)).unwrap(),
+            Type::Int64
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(Type::Bool.to_string(), "BOOL");
+        assert_eq!(Type::Int64.to_string(), "INT64");
+        assert_eq!(Type::String.to_string(), "STRING(MAX)");
+        assert_eq!(Type::array(Type::Bytes).to_string(), "ARRAY<BYTES(MAX)>");
+        assert_eq!(
+            Type::strct(vec![("id", Type::Int64), ("", Type::String)]).to_string(),
+            "STRUCT<id INT64, STRING(MAX)>"
+        );
+        assert_eq!(Type::array(Type::String).sql_name(), "ARRAY<STRING>");
+    }
+
     #[test]
     fn test_column_index() {
         let strct = StructType(vec![
@@ -106,6 +106,7 @@ impl Value {
             Value::Float64(_) => Type::Float64,
             Value::String(_) => Type::String,
             Value::Bytes(_) => Type::Bytes,
+            #[cfg(feature = "json")]
             Value::Json(_) => Type::Json,
             #[cfg(feature = "numeric")]
             Value::Numeric(_) => Type::Numeric,
@@ -113,11 +114,95 @@ impl Value {
             Value::Timestamp(_) => Type::Timestamp,
             #[cfg(feature = "temporal")]
             Value::Date(_) => Type::Date,
-            Value::Array(inner, _) => inner.clone(),
+            Value::Array(inner, _) => Type::Array(Box::new(inner.clone())),
             Value::Struct(Struct(struct_type, _)) => Type::Struct(struct_type.clone()),
         }
     }
 
+    /// Returns true when this value is of the provided type, without building its [`Type`].
+    pub(crate) fn has_type(&self, tpe: &Type) -> bool {
+        match (self, tpe) {
+            (Value::Null(inner), tpe) => inner == tpe,
+            (Value::Array(inner, _), Type::Array(tpe)) => inner == &**tpe,
+            (Value::Struct(Struct(struct_type, _)), Type::Struct(tpe)) => struct_type == tpe,
+            (Value::Array(..) | Value::Struct(_), _) => false,
+            (value, tpe) => value.spanner_type() == *tpe,
+        }
+    }
+
+    /// Renders this value as a GoogleSQL literal, e.g.: `'ferris'`, `b'\x00'` or `ARRAY<INT64>[1, 2]`.
+    ///
+    /// This is useful for debugging tools and query loggers that need to print a statement with its parameters inlined.
+    /// Literals are typed whenever their type would otherwise be ambiguous, e.g.: `CAST(NULL AS STRING)`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Type, Value};
+    ///
+    /// assert_eq!(Value::String("it's".to_string()).to_sql_literal(), r"'it\'s'");
+    /// assert_eq!(Value::Null(Type::Int64).to_sql_literal(), "CAST(NULL AS INT64)");
+    /// ```
+    pub fn to_sql_literal(&self) -> String {
+        match self {
+            Value::Null(tpe) => format!("CAST(NULL AS {})", tpe.sql_name()),
+            Value::Bool(true) => "TRUE".to_string(),
+            Value::Bool(false) => "FALSE".to_string(),
+            Value::Int64(i) => i.to_string(),
+            Value::Float64(f) if f.is_nan() => "CAST('nan' AS FLOAT64)".to_string(),
+            Value::Float64(f) if f.is_infinite() && f.is_sign_positive() => {
+                "CAST('inf' AS FLOAT64)".to_string()
+            }
+            Value::Float64(f) if f.is_infinite() => "CAST('-inf' AS FLOAT64)".to_string(),
+            // Debug always includes a decimal point or exponent, which makes the literal a FLOAT64 rather than an INT64.
+            Value::Float64(f) => format!("{:?}", f),
+            Value::String(s) => quote(s),
+            Value::Bytes(bytes) => {
+                let mut literal = String::with_capacity(bytes.len() + 3);
+                literal.push_str("b'");
+                for byte in bytes.iter() {
+                    match byte {
+                        b'\\' => literal.push_str("\\\\"),
+                        b'\'' => literal.push_str("\\'"),
+                        0x20..=0x7e => literal.push(char::from(*byte)),
+                        _ => literal.push_str(&format!("\\x{:02x}", byte)),
+                    }
+                }
+                literal.push('\'');
+                literal
+            }
+            #[cfg(feature = "json")]
+            Value::Json(json) => format!("JSON {}", quote(&json.to_string())),
+            #[cfg(feature = "numeric")]
+            Value::Numeric(n) => format!("NUMERIC '{}'", n),
+            #[cfg(feature = "temporal")]
+            Value::Timestamp(dt) => format!(
+                "TIMESTAMP '{}'",
+                dt.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+            ),
+            #[cfg(feature = "temporal")]
+            Value::Date(d) => format!("DATE '{}'", d),
+            Value::Array(tpe, values) => format!(
+                "ARRAY<{}>[{}]",
+                tpe.sql_name(),
+                values
+                    .iter()
+                    .map(Value::to_sql_literal)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+            Value::Struct(Struct(struct_type, values)) => format!(
+                "{}({})",
+                Type::Struct(struct_type.clone()).sql_name(),
+                values
+                    .iter()
+                    .map(Value::to_sql_literal)
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+
     pub(crate) fn try_from(tpe: &Type, value: SpannerValue) -> Result<Self, crate::Error> {
         let kind = value
             .kind
@@ -223,6 +308,25 @@ impl Value {
     }
 }
 
+// Quotes and escapes the provided string as a GoogleSQL string literal.
+fn quote(s: &str) -> String {
+    let mut literal = String::with_capacity(s.len() + 2);
+    literal.push('\'');
+    for c in s.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            '\t' => literal.push_str("\\t"),
+            c if c.is_control() => literal.push_str(&format!("\\u{:04x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
 impl TryFrom<Value> for SpannerValue {
     type Error = crate::Error;
 
@@ -264,6 +368,152 @@ impl TryFrom<Value> for SpannerValue {
     }
 }
 
+/// Converts a value to JSON, e.g.: to return it from an HTTP API or to log it.
+///
+/// Values without a JSON counterpart are encoded as strings, in the same format Cloud Spanner uses: `BYTES` are base64-encoded,
+/// `NUMERIC`s retain their precision, `TIMESTAMP`s use RFC 3339, `DATE`s use `YYYY-MM-DD` and non-finite `FLOAT64`s are
+/// `"NaN"`, `"Infinity"` or `"-Infinity"`. `STRUCT`s are objects keyed by field name, unnamed fields are keyed by their position,
+/// e.g.: `"_0"`.
+///
+/// Since JSON is untyped, converting it back requires the value's type, see [`Value::from_json`].
+#[cfg(feature = "json")]
+impl From<Value> for JsValue {
+    fn from(value: Value) -> Self {
+        match value {
+            Value::Null(_) => JsValue::Null,
+            Value::Bool(b) => JsValue::Bool(b),
+            Value::Int64(i) => JsValue::from(i),
+            Value::Float64(f) if f.is_nan() => JsValue::String("NaN".to_string()),
+            Value::Float64(f) if f.is_infinite() && f.is_sign_positive() => {
+                JsValue::String("Infinity".to_string())
+            }
+            Value::Float64(f) if f.is_infinite() => JsValue::String("-Infinity".to_string()),
+            Value::Float64(f) => JsValue::from(f),
+            Value::String(s) => JsValue::String(s),
+            Value::Bytes(b) => JsValue::String(base64::encode(b)),
+            Value::Json(json) => json,
+            #[cfg(feature = "numeric")]
+            Value::Numeric(n) => JsValue::String(n.to_string()),
+            #[cfg(feature = "temporal")]
+            Value::Timestamp(dt) => {
+                JsValue::String(dt.to_rfc3339_opts(SecondsFormat::AutoSi, true))
+            }
+            #[cfg(feature = "temporal")]
+            Value::Date(d) => JsValue::String(d.to_string()),
+            Value::Array(_, values) => {
+                JsValue::Array(values.into_iter().map(JsValue::from).collect())
+            }
+            Value::Struct(Struct(struct_type, values)) => JsValue::Object(
+                struct_type
+                    .fields()
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (name, _))| json_field_name(index, name))
+                    .zip(values.into_iter().map(JsValue::from))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+fn json_field_name(index: usize, name: &Option<String>) -> String {
+    name.clone().unwrap_or_else(|| format!("_{}", index))
+}
+
+#[cfg(feature = "json")]
+impl Value {
+    /// Converts JSON back into a value of the provided type, accepting the encoding described in `From<Value> for serde_json::Value`.
+    ///
+    /// Additionally, `INT64` values may be provided as strings (which avoids losing precision in JavaScript),
+    /// `NUMERIC` values as numbers and `STRUCT` values as arrays of their fields' values. Missing struct fields are `NULL`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use spanner_rs::{Type, Value};
+    ///
+    /// let value = Value::Array(Type::Int64, vec![Value::Int64(1), Value::Null(Type::Int64)]);
+    /// let json = serde_json::Value::from(value.clone());
+    /// assert_eq!(json, serde_json::json!([1, null]));
+    /// assert_eq!(Value::from_json(json, &Type::array(Type::Int64))?, value);
+    /// # Ok::<(), spanner_rs::Error>(())
+    /// ```
+    pub fn from_json(json: JsValue, tpe: &Type) -> Result<Self, Error> {
+        let invalid =
+            |json: &JsValue| Error::Codec(format!("invalid JSON value for type {}: {}", tpe, json));
+        match (tpe, json) {
+            (tpe, JsValue::Null) => Ok(Value::Null(tpe.clone())),
+            (Type::Json, json) => Ok(Value::Json(json)),
+            (Type::Bool, JsValue::Bool(b)) => Ok(Value::Bool(b)),
+            (Type::Int64, JsValue::Number(n)) => n
+                .as_i64()
+                .map(Value::Int64)
+                .ok_or_else(|| invalid(&JsValue::Number(n))),
+            (Type::Int64, JsValue::String(s)) => s
+                .parse()
+                .map(Value::Int64)
+                .map_err(|_| invalid(&JsValue::String(s))),
+            (Type::Float64, JsValue::Number(n)) => n
+                .as_f64()
+                .map(Value::Float64)
+                .ok_or_else(|| invalid(&JsValue::Number(n))),
+            (Type::Float64, JsValue::String(s)) => match s.as_str() {
+                "NaN" => Ok(Value::Float64(f64::NAN)),
+                "Infinity" => Ok(Value::Float64(f64::INFINITY)),
+                "-Infinity" => Ok(Value::Float64(f64::NEG_INFINITY)),
+                _ => Err(invalid(&JsValue::String(s))),
+            },
+            (Type::String, JsValue::String(s)) => Ok(Value::String(s)),
+            (Type::Bytes, JsValue::String(s)) => base64::decode(&s)
+                .map(|bytes| Value::Bytes(Bytes::from(bytes)))
+                .map_err(|_| invalid(&JsValue::String(s))),
+            #[cfg(feature = "numeric")]
+            (Type::Numeric, JsValue::String(s)) => BigDecimal::from_str(&s)
+                .map(Value::Numeric)
+                .map_err(|_| invalid(&JsValue::String(s))),
+            #[cfg(feature = "numeric")]
+            (Type::Numeric, JsValue::Number(n)) => BigDecimal::from_str(&n.to_string())
+                .map(Value::Numeric)
+                .map_err(|_| invalid(&JsValue::Number(n))),
+            #[cfg(feature = "temporal")]
+            (Type::Timestamp, JsValue::String(s)) => Ok(Value::Timestamp(
+                DateTime::parse_from_rfc3339(&s)?.with_timezone(&Utc),
+            )),
+            #[cfg(feature = "temporal")]
+            (Type::Date, JsValue::String(s)) => Ok(Value::Date(NaiveDate::from_str(&s)?)),
+            (Type::Array(inner), JsValue::Array(values)) => values
+                .into_iter()
+                .map(|value| Value::from_json(value, inner))
+                .collect::<Result<Vec<Value>, Error>>()
+                .map(|values| Value::Array(inner.as_ref().clone(), values)),
+            (Type::Struct(struct_type), JsValue::Object(mut fields)) => struct_type
+                .fields()
+                .iter()
+                .enumerate()
+                .map(|(index, (name, tpe))| {
+                    let value = fields
+                        .remove(&json_field_name(index, name))
+                        .unwrap_or(JsValue::Null);
+                    Value::from_json(value, tpe)
+                })
+                .collect::<Result<Vec<Value>, Error>>()
+                .map(|values| Value::Struct(Struct(struct_type.clone(), values))),
+            (Type::Struct(struct_type), JsValue::Array(values))
+                if values.len() == struct_type.fields().len() =>
+            {
+                struct_type
+                    .types()
+                    .zip(values)
+                    .map(|(tpe, value)| Value::from_json(value, tpe))
+                    .collect::<Result<Vec<Value>, Error>>()
+                    .map(|values| Value::Struct(Struct(struct_type.clone(), values)))
+            }
+            (_, json) => Err(invalid(&json)),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
 
@@ -317,6 +567,31 @@ mod test {
         assert_invalid(Type::Array(Box::new(Type::Bool)), Kind::BoolValue(true));
     }
 
+    #[test]
+    fn test_value_spanner_type() {
+        let person = StructType::new(vec![("id", Type::Int64), ("name", Type::String)]);
+        assert_eq!(
+            Value::Array(Type::Bool, vec![]).spanner_type(),
+            Type::array(Type::Bool)
+        );
+        assert_eq!(
+            Value::Array(Type::Struct(person.clone()), vec![]).spanner_type(),
+            Type::array(Type::Struct(person.clone()))
+        );
+        assert_eq!(
+            Value::Null(Type::Struct(person.clone())).spanner_type(),
+            Type::Struct(person.clone())
+        );
+        assert_eq!(
+            Value::Struct(Struct::new(
+                person.clone(),
+                vec![Value::Int64(42), Value::Null(Type::String)]
+            ))
+            .spanner_type(),
+            Type::Struct(person)
+        );
+    }
+
     #[test]
     fn test_value_bool() {
         assert_try_from_into(Type::Bool, Kind::BoolValue(true), Value::Bool(true));
@@ -526,4 +801,132 @@ mod test {
         assert_nullable(Type::Timestamp);
         assert_invalid(Type::Timestamp, Kind::BoolValue(true));
     }
+
+    #[test]
+    fn test_to_sql_literal() {
+        assert_eq!(
+            Value::Null(Type::String).to_sql_literal(),
+            "CAST(NULL AS STRING)"
+        );
+        assert_eq!(Value::Bool(true).to_sql_literal(), "TRUE");
+        assert_eq!(Value::Int64(-42).to_sql_literal(), "-42");
+        assert_eq!(Value::Float64(1.0).to_sql_literal(), "1.0");
+        assert_eq!(
+            Value::Float64(f64::NAN).to_sql_literal(),
+            "CAST('nan' AS FLOAT64)"
+        );
+        assert_eq!(
+            Value::Float64(f64::NEG_INFINITY).to_sql_literal(),
+            "CAST('-inf' AS FLOAT64)"
+        );
+        assert_eq!(
+            Value::String("it's a\n\\test".to_string()).to_sql_literal(),
+            r"'it\'s a\n\\test'"
+        );
+        assert_eq!(
+            Value::Bytes(Bytes::from_static(b"a'\x00\xff")).to_sql_literal(),
+            r"b'a\'\x00\xff'"
+        );
+        assert_eq!(
+            Value::Array(Type::Int64, vec![Value::Int64(1), Value::Null(Type::Int64)])
+                .to_sql_literal(),
+            "ARRAY<INT64>[1, CAST(NULL AS INT64)]"
+        );
+        assert_eq!(
+            Value::Struct(Struct::new(
+                StructType::new(vec![("id", Type::Int64), ("", Type::String)]),
+                vec![Value::Int64(1), Value::String("one".to_string())],
+            ))
+            .to_sql_literal(),
+            "STRUCT<id INT64, STRING>(1, 'one')"
+        );
+        #[cfg(feature = "numeric")]
+        assert_eq!(
+            Value::Numeric(BigDecimal::from_str("1.5").unwrap()).to_sql_literal(),
+            "NUMERIC '1.5'"
+        );
+        #[cfg(feature = "temporal")]
+        assert_eq!(
+            Value::Date(NaiveDate::from_ymd(2021, 10, 1)).to_sql_literal(),
+            "DATE '2021-10-01'"
+        );
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_value_json() {
+        use serde_json::json;
+
+        let assert_round_trip = |value: Value, expected: JsValue| {
+            let json = JsValue::from(value.clone());
+            assert_eq!(json, expected);
+            assert_eq!(
+                Value::from_json(json, &value.spanner_type()).unwrap(),
+                value
+            );
+        };
+
+        assert_round_trip(Value::Null(Type::String), json!(null));
+        assert_round_trip(Value::Bool(true), json!(true));
+        assert_round_trip(Value::Int64(42), json!(42));
+        assert_round_trip(Value::Float64(4.5), json!(4.5));
+        assert_round_trip(Value::Float64(f64::INFINITY), json!("Infinity"));
+        assert_round_trip(Value::String("ferris".to_string()), json!("ferris"));
+        assert_round_trip(Value::Bytes(Bytes::from_static(b"hi")), json!("aGk="));
+        assert_round_trip(Value::Json(json!({"a": [1]})), json!({"a": [1]}));
+        #[cfg(feature = "numeric")]
+        assert_round_trip(
+            Value::Numeric(BigDecimal::from_str("1.50").unwrap()),
+            json!("1.50"),
+        );
+        #[cfg(feature = "temporal")]
+        assert_round_trip(
+            Value::Timestamp(
+                DateTime::parse_from_rfc3339("2021-10-01T12:34:56.5Z")
+                    .unwrap()
+                    .with_timezone(&Utc),
+            ),
+            json!("2021-10-01T12:34:56.500Z"),
+        );
+        #[cfg(feature = "temporal")]
+        assert_round_trip(
+            Value::Date(NaiveDate::from_ymd(2021, 10, 1)),
+            json!("2021-10-01"),
+        );
+        assert_round_trip(
+            Value::Array(Type::Int64, vec![Value::Int64(1), Value::Null(Type::Int64)]),
+            json!([1, null]),
+        );
+        let pair = StructType::new(vec![("id", Type::Int64), ("", Type::String)]);
+        assert_round_trip(
+            Value::Struct(Struct::new(
+                pair.clone(),
+                vec![Value::Int64(1), Value::String("one".to_string())],
+            )),
+            json!({"id": 1, "_1": "one"}),
+        );
+
+        assert!(
+            Value::from_json(json!(f64::NAN.to_string()), &Type::Float64)
+                .unwrap_or(Value::Float64(0.0))
+                .to_sql_literal()
+                .contains("nan")
+        );
+        assert_eq!(
+            Value::from_json(json!("42"), &Type::Int64).unwrap(),
+            Value::Int64(42)
+        );
+        assert_eq!(
+            Value::from_json(json!([1, "one"]), &Type::Struct(pair.clone())).unwrap(),
+            Value::Struct(Struct::new(
+                pair,
+                vec![Value::Int64(1), Value::String("one".to_string())]
+            ))
+        );
+        assert!(matches!(
+            Value::from_json(json!("ferris"), &Type::Int64),
+            Err(Error::Codec(_))
+        ));
+        assert!(Value::from_json(json!(1.5), &Type::Int64).is_err());
+    }
 }
@@ -0,0 +1,90 @@
+use spanner_rs::{TableRow, ToRow, ToSpanner, Type, Value};
+
+#[derive(ToRow)]
+struct Person {
+    id: i64,
+    #[spanner(rename = "full_name")]
+    name: String,
+    r#type: Option<String>,
+}
+
+#[derive(ToRow, TableRow)]
+#[spanner(table = "album")]
+struct Album {
+    #[spanner(pk)]
+    singer_id: i64,
+    #[spanner(pk, rename = "album_id")]
+    id: i64,
+    title: String,
+}
+
+#[derive(ToRow, TableRow)]
+struct Singer {
+    #[spanner(pk)]
+    id: i64,
+}
+
+#[derive(ToRow)]
+struct Wrapper<T: ToSpanner> {
+    value: T,
+}
+
+#[test]
+fn test_derive_to_row() {
+    let person = Person {
+        id: 42,
+        name: "ferris".to_string(),
+        r#type: None,
+    };
+    assert_eq!(
+        person.to_row().unwrap(),
+        vec![
+            ("id", Value::Int64(42)),
+            ("full_name", Value::String("ferris".to_string())),
+            ("type", Value::Null(Type::String)),
+        ]
+    );
+}
+
+#[test]
+fn test_derive_to_row_generic() {
+    let wrapper = Wrapper { value: true };
+    assert_eq!(
+        wrapper.to_row().unwrap(),
+        vec![("value", Value::Bool(true))]
+    );
+}
+
+#[test]
+fn test_derive_table_row() {
+    let album = Album {
+        singer_id: 1,
+        id: 2,
+        title: "ferris".to_string(),
+    };
+    assert_eq!(Album::TABLE, "album");
+    assert_eq!(Album::KEY_COLUMNS, &["singer_id", "album_id"]);
+    assert_eq!(album.key().unwrap(), vec![Value::Int64(1), Value::Int64(2)]);
+    assert_eq!(
+        album.to_row().unwrap(),
+        vec![
+            ("singer_id", Value::Int64(1)),
+            ("album_id", Value::Int64(2)),
+            ("title", Value::String("ferris".to_string())),
+        ]
+    );
+
+    let mutation = album.insert_or_update();
+    assert_eq!(mutation.table(), "album");
+    let mutation = album.delete_by_key();
+    assert_eq!(mutation.table(), "album");
+    assert_eq!(mutation.mutation_count(), 1);
+}
+
+#[test]
+fn test_derive_table_row_default_table() {
+    let singer = Singer { id: 42 };
+    assert_eq!(Singer::TABLE, "Singer");
+    assert_eq!(singer.key().unwrap(), vec![Value::Int64(42)]);
+    assert_eq!(singer.delete_by_key().table(), "Singer");
+}
@@ -1,8 +1,12 @@
 #![feature(async_closure)]
 
 use std::sync::atomic::{AtomicU16, Ordering};
+use std::time::{Duration, SystemTime};
 
-use spanner_rs::{Error, ReadContext, ResultSet, Statement};
+use spanner_rs::{
+    Error, KeySet, Mutation, OnDelete, Priority, ReadContext, ReadContextExt, ResultSet, Statement,
+    TimestampBound, TxOptions, Type,
+};
 
 #[cfg(not(feature = "gcp"))]
 mod spanner_emulator;
@@ -57,6 +61,24 @@ async fn test_read_only() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_read_only_stale() -> Result<(), Error> {
+    let client = new_client().await?;
+
+    let result_set = client
+        .read_only_stale(Duration::from_secs(10))
+        .execute_query("SELECT * FROM my_table", &[])
+        .await?;
+    assert!(result_set.is_empty());
+
+    let result_set = client
+        .read_only_at(SystemTime::now())
+        .execute_query("SELECT * FROM my_table", &[])
+        .await?;
+    assert!(result_set.is_empty());
+    Ok(())
+}
+
 #[tokio::test]
 async fn test_read_write() -> Result<(), Error> {
     let client = new_client().await?;
@@ -88,6 +110,61 @@ async fn test_read_write() -> Result<(), Error> {
     Ok(())
 }
 
+#[tokio::test]
+async fn test_execute_statement() -> Result<(), Error> {
+    let client = new_client().await?;
+    let insert = Statement::new("INSERT INTO my_table(a,b) VALUES(@a, @b)")
+        .bind("a", 1)
+        .bind("b", "one");
+    let row_count = client
+        .read_write()
+        .run(|ctx| {
+            let insert = insert.clone();
+            Box::pin(async move { ctx.execute_update_statement(&insert).await })
+        })
+        .await?;
+
+    assert_eq!(row_count, 1);
+
+    let result_set = client
+        .read_only()
+        .execute_statement(&Statement::new("SELECT * FROM my_table WHERE a = @a").bind("a", 1))
+        .await?;
+    let row = result_set.iter().next();
+    assert!(row.is_some());
+    assert_eq!(row.unwrap().get_unchecked::<&str, _>("b"), "one");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_with_outcome() -> Result<(), Error> {
+    let client = new_client().await?;
+    let outcome = client
+        .read_write()
+        .run_with_outcome(|ctx| {
+            Box::pin(async move {
+                ctx.execute_update(
+                    "INSERT INTO my_table(a,b) VALUES(@a, @b)",
+                    &[("a", &1), ("b", &"one")],
+                )
+                .await
+            })
+        })
+        .await?;
+
+    assert_eq!(*outcome.value(), 1);
+    assert!(outcome.commit_timestamp().is_some());
+
+    let result_set = outcome
+        .snapshot_reader()
+        .execute_query("SELECT * FROM my_table", &[])
+        .await?;
+    assert_eq!(result_set.iter().count(), 1);
+
+    Ok(())
+}
+
 #[tokio::test]
 #[cfg_attr(not(feature = "gcp"), ignore)]
 async fn test_read_write_abort() -> Result<(), Error> {
@@ -168,18 +245,13 @@ async fn test_execute_updates() -> Result<(), Error> {
         .run(|ctx| {
             Box::pin(async move {
                 ctx.execute_updates(&[
-                    &Statement {
-                        sql: "INSERT INTO my_table(a,b) VALUES(@a, @b)",
-                        params: &[("a", &1), ("b", &"one")],
-                    },
-                    &Statement {
-                        sql: "INSERT INTO my_table(a,b) VALUES(@a, @b)",
-                        params: &[("a", &2), ("b", &"two")],
-                    },
-                    &Statement {
-                        sql: "UPDATE my_table SET b = @b WHERE a > 0",
-                        params: &[("b", &"foo")],
-                    },
+                    &Statement::new("INSERT INTO my_table(a,b) VALUES(@a, @b)")
+                        .bind("a", 1)
+                        .bind("b", "one"),
+                    &Statement::new("INSERT INTO my_table(a,b) VALUES(@a, @b)")
+                        .bind("a", 2)
+                        .bind("b", "two"),
+                    &Statement::new("UPDATE my_table SET b = @b WHERE a > 0").bind("b", "foo"),
                 ])
                 .await
             })
@@ -209,3 +281,206 @@ async fn test_execute_updates() -> Result<(), Error> {
 
     Ok(())
 }
+
+#[tokio::test]
+async fn test_drain() -> Result<(), Error> {
+    let client = new_client().await?;
+    client
+        .read_only()
+        .execute_query("SELECT * FROM my_table", &[])
+        .await?;
+
+    client.drain(Duration::from_secs(5)).await?;
+
+    match client
+        .read_only()
+        .execute_query("SELECT * FROM my_table", &[])
+        .await
+    {
+        Err(Error::Client(_)) => (),
+        other => panic!("unexpected result: {:?}", other.map(|_| ())),
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_health_check() -> Result<(), Error> {
+    let client = new_client().await?;
+    let report = client.health_check().await;
+    assert!(report.is_healthy(), "unhealthy: {:?}", report.error());
+    assert!(report.session_ok());
+    assert!(report.latency().is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_schema() -> Result<(), Error> {
+    let client = new_client().await?;
+    let schema = client.schema().await?;
+
+    let person = schema.table("person").expect("person table");
+    let columns: Vec<&str> = person.columns().iter().map(|c| c.name()).collect();
+    assert_eq!(columns, vec!["id", "name", "data"]);
+
+    let name = person.column("name").unwrap();
+    assert_eq!(name.spanner_type(), "STRING(MAX)");
+    assert_eq!(name.column_type(), Some(&Type::String));
+    assert!(!name.is_nullable());
+    assert!(person.column("data").unwrap().is_nullable());
+
+    let primary_key: Vec<&str> = person.primary_key().iter().map(|c| c.name()).collect();
+    assert_eq!(primary_key, vec!["id"]);
+    assert!(person.indexes().is_empty());
+    assert!(person.interleaved_in().is_none());
+    assert!(person.check_constraints().is_empty());
+
+    let address = schema.table("address").expect("address table");
+    let parent = address.interleaved_in().expect("interleaved table");
+    assert_eq!(parent.parent(), "person");
+    assert_eq!(parent.on_delete(), OnDelete::Cascade);
+    assert_eq!(address.check_constraints().len(), 1);
+    assert_eq!(address.check_constraints()[0].name(), "city_not_empty");
+
+    let pet = schema.table("pet").expect("pet table");
+    assert_eq!(pet.foreign_keys().len(), 1);
+    let owner = &pet.foreign_keys()[0];
+    assert_eq!(owner.name(), "fk_pet_owner");
+    assert_eq!(owner.columns(), &["owner_id".to_string()]);
+    assert_eq!(owner.referenced_table(), "person");
+    assert_eq!(owner.referenced_columns(), &["id".to_string()]);
+    assert_eq!(owner.on_delete(), OnDelete::NoAction);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_execute_query_one() -> Result<(), Error> {
+    let client = new_client().await?;
+    client
+        .read_write()
+        .run(|tx| {
+            Box::pin(async move {
+                tx.execute_update(
+                    "INSERT INTO my_table(a,b) VALUES(@a, @b)",
+                    &[("a", &1), ("b", &"one")],
+                )
+                .await?;
+                let count: i64 = tx
+                    .execute_query_one("SELECT COUNT(*) FROM my_table", &[])
+                    .await?;
+                assert_eq!(count, 1);
+                Ok(())
+            })
+        })
+        .await?;
+
+    let mut read_only = client.read_only();
+    let b: Option<String> = read_only
+        .execute_query_opt("SELECT b FROM my_table WHERE a = @a", &[("a", &1)])
+        .await?;
+    assert_eq!(b, Some("one".to_string()));
+    let missing: Option<String> = read_only
+        .execute_query_opt("SELECT b FROM my_table WHERE a = @a", &[("a", &2)])
+        .await?;
+    assert_eq!(missing, None);
+    assert!(read_only
+        .execute_query_one::<i64>("SELECT a, b FROM my_table", &[])
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_buffer_write() -> Result<(), Error> {
+    let client = new_client().await?;
+    client
+        .read_write()
+        .run(|tx| {
+            tx.buffer_write(vec![
+                Mutation::insert("my_table").set("a", 1).set("b", "one"),
+                Mutation::insert("my_table").set("a", 2).set("b", "two"),
+            ]);
+            Box::pin(async { Ok(()) })
+        })
+        .await?;
+
+    client
+        .read_write()
+        .run(|tx| {
+            Box::pin(async move {
+                tx.execute_update("UPDATE my_table SET b = 'uno' WHERE a = 1", &[])
+                    .await?;
+                tx.buffer_write(vec![Mutation::delete("my_table", KeySet::new().key(&[&2]))]);
+                Ok(())
+            })
+        })
+        .await?;
+
+    let rs = client
+        .read_only()
+        .execute_query("SELECT a, b FROM my_table", &[])
+        .await?;
+    let row = rs.one()?;
+    assert_eq!(row.get::<i64, _>("a")?, 1);
+    assert_eq!(row.get::<&str, _>("b")?, "uno");
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_run_with_options() -> Result<(), Error> {
+    let client = new_client().await?;
+    let options = TxOptions::builder()
+        .tag("test-run-with-options")
+        .priority(Priority::Low)
+        .return_commit_stats(true)
+        .build()?;
+    let outcome = client
+        .read_write()
+        .run_with_options(options, |tx| {
+            tx.execute_update("INSERT INTO my_table(a,b) VALUES(1, 'one')", &[])
+        })
+        .await?;
+    assert_eq!(*outcome.value(), 1);
+
+    let response = outcome.commit_response().expect("committed");
+    assert_eq!(
+        outcome.commit_timestamp(),
+        Some(response.commit_timestamp())
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn test_partition_query() -> Result<(), Error> {
+    use futures_util::TryStreamExt;
+
+    let client = new_client().await?;
+    client
+        .read_write()
+        .run(|tx| {
+            tx.buffer_write(
+                (0..10)
+                    .map(|a| Mutation::insert("my_table").set("a", a).set("b", "value"))
+                    .collect(),
+            );
+            Box::pin(async { Ok(()) })
+        })
+        .await?;
+
+    let partitions = client
+        .partition_query("SELECT a, b FROM my_table", TimestampBound::Strong)
+        .await?;
+    assert!(!partitions.is_empty());
+
+    let rows: Vec<_> = client
+        .execute_partitions_unordered(partitions, 2)
+        .try_collect()
+        .await?;
+    assert_eq!(rows.len(), 10);
+
+    Ok(())
+}
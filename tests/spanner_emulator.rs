@@ -1,4 +1,8 @@
-use spanner_rs::{Client, DatabaseId, Error, InstanceId, ProjectId, SpannerResource};
+#[cfg(feature = "emulator")]
+use spanner_rs::EmulatorClient;
+#[cfg(not(feature = "emulator"))]
+use spanner_rs::SpannerResource;
+use spanner_rs::{Client, DatabaseId, Error, InstanceId, ProjectId};
 
 use ctor::ctor;
 use std::ops::{Deref, DerefMut};
@@ -41,32 +45,36 @@ impl<'a> SpannerContainer<'a> {
         self.container.get_host_port_ipv4(9010)
     }
 
-    async fn post(&self, path: String, body: String) {
-        let response = reqwest::Client::new()
-            .post(format!("http://localhost:{}/v1/{}", self.http_port(), path))
-            .body(body)
-            .send()
-            .await
-            .unwrap();
-
-        assert!(response.status().is_success(), "{:?}", response);
+    #[cfg(feature = "emulator")]
+    async fn bootstrap(
+        &self,
+        instance: &InstanceId,
+        database: &DatabaseId,
+        statements: &[&str],
+    ) -> Result<(), Error> {
+        let emulator = EmulatorClient::with_port(self.http_port());
+        emulator.create_instance(instance).await?;
+        emulator.create_database(database, statements).await
     }
 
-    async fn with_instance(&'a self, instance: &InstanceId) {
+    // Without the `emulator` feature, the emulator's REST endpoint is called directly.
+    #[cfg(not(feature = "emulator"))]
+    async fn bootstrap(
+        &self,
+        instance: &InstanceId,
+        database: &DatabaseId,
+        statements: &[&str],
+    ) -> Result<(), Error> {
         self.post(
             instance.resources_path(),
             format!(r#"{{"instanceId": "{}"}}"#, instance.name()),
         )
         .await;
-    }
-
-    async fn with_database(&self, database: &DatabaseId, extra_statements: Vec<&str>) {
-        let json_statements = extra_statements
-            .into_iter()
+        let json_statements = statements
+            .iter()
             .map(|s| format!(r#""{}""#, s))
             .collect::<Vec<String>>()
             .join(",");
-
         self.post(
             database.resources_path(),
             format!(
@@ -76,6 +84,19 @@ impl<'a> SpannerContainer<'a> {
             ),
         )
         .await;
+        Ok(())
+    }
+
+    #[cfg(not(feature = "emulator"))]
+    async fn post(&self, path: String, body: String) {
+        let response = reqwest::Client::new()
+            .post(format!("http://localhost:{}/v1/{}", self.http_port(), path))
+            .body(body)
+            .send()
+            .await
+            .unwrap();
+
+        assert!(response.status().is_success(), "{:?}", response);
     }
 }
 
@@ -113,15 +134,18 @@ pub(crate) async fn new_client<'a>() -> Result<ClientFixture<'a>, Error> {
     let database_id = DatabaseId::new(instance_id.clone(), "test-database");
     let container = DOCKER.run(SpannerEmulator);
     let container = SpannerContainer { container };
-    container.with_instance(&instance_id).await;
-    container.with_database(
+    container
+        .bootstrap(
+            &instance_id,
             &database_id,
-            vec![
+            &[
                 "CREATE TABLE my_table(a INT64, b STRING(MAX)) PRIMARY KEY(a)",
                 "CREATE TABLE person(id INT64, name STRING(MAX) NOT NULL, data BYTES(MAX)) PRIMARY KEY(id)",
+                "CREATE TABLE address(id INT64, address_id INT64, city STRING(MAX), CONSTRAINT city_not_empty CHECK (city != '')) PRIMARY KEY(id, address_id), INTERLEAVE IN PARENT person ON DELETE CASCADE",
+                "CREATE TABLE pet(id INT64, owner_id INT64, CONSTRAINT fk_pet_owner FOREIGN KEY (owner_id) REFERENCES person(id)) PRIMARY KEY(id)",
             ],
         )
-        .await;
+        .await?;
 
     let client = Client::configure()
         .with_emulator_grpc_port(container.grpc_port())